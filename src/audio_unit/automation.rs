@@ -0,0 +1,195 @@
+//! Playing back recorded parameter automation sample-accurately.
+//!
+//! Rather than setting each parameter as its time comes, the changes within the next render are
+//! scheduled in a single call to `AudioUnitScheduleParameters`, each landing on its own frame.
+//! Changes may also be ramped over a number of frames, avoiding the clicks caused by stepping a
+//! gain from one value to another.
+
+use super::parameter::AudioUnitParameter;
+use super::AudioUnit;
+use crate::error::Error;
+use std::os::raw::c_uint;
+use sys;
+
+/// A change to a parameter scheduled within the next render, as taken by
+/// `AudioUnit::schedule_parameters`.
+///
+/// A rust representation of the `AudioUnitParameterEvent` type.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ParameterEvent {
+    /// Set the parameter to `value` at the given frame offset into the render.
+    Immediate { offset: u32, value: f32 },
+    /// Ramp the parameter linearly from `start_value` to `end_value` over `duration` frames.
+    ///
+    /// `start_offset` is relative to the start of the render and may be negative for ramps that
+    /// began in an earlier render, in which case the unit resumes the ramp part way through.
+    Ramp {
+        start_offset: i32,
+        duration: u32,
+        start_value: f32,
+        end_value: f32,
+    },
+}
+
+impl ParameterEvent {
+    /// Convert the event into an `AudioUnitParameterEvent` for the given parameter.
+    pub(crate) fn to_sys(self, parameter: AudioUnitParameter) -> sys::AudioUnitParameterEvent {
+        let (event_type, event_values) = match self {
+            ParameterEvent::Immediate { offset, value } => {
+                let immediate = sys::AudioUnitParameterEvent__bindgen_ty_1__bindgen_ty_2 {
+                    bufferOffset: offset,
+                    value,
+                };
+                let values = sys::AudioUnitParameterEvent__bindgen_ty_1 { immediate };
+                (sys::kParameterEvent_Immediate, values)
+            }
+            ParameterEvent::Ramp {
+                start_offset,
+                duration,
+                start_value,
+                end_value,
+            } => {
+                let ramp = sys::AudioUnitParameterEvent__bindgen_ty_1__bindgen_ty_1 {
+                    startBufferOffset: start_offset,
+                    durationInFrames: duration,
+                    startValue: start_value,
+                    endValue: end_value,
+                };
+                let values = sys::AudioUnitParameterEvent__bindgen_ty_1 { ramp };
+                (sys::kParameterEvent_Ramped, values)
+            }
+        };
+        sys::AudioUnitParameterEvent {
+            scope: parameter.scope as c_uint,
+            element: parameter.element,
+            parameter: parameter.id,
+            eventType: event_type,
+            eventValues: event_values,
+        }
+    }
+}
+
+impl AudioUnit {
+    /// Schedule the given parameter events within the next render via a single call to
+    /// `AudioUnitScheduleParameters`.
+    ///
+    /// Ramps are the click-free way of changing gains: a mixer volume ramped over a few
+    /// milliseconds of frames fades smoothly, whereas an immediate change steps audibly. Whether
+    /// a parameter may be ramped is described by the `CAN_RAMP` flag of its `parameter_info`.
+    ///
+    /// This allocates the scheduled events, so it's best called outside the render callback.
+    pub fn schedule_parameters(
+        &mut self,
+        events: &[(AudioUnitParameter, ParameterEvent)],
+    ) -> Result<(), Error> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        let events: Vec<_> = events
+            .iter()
+            .map(|&(parameter, event)| event.to_sys(parameter))
+            .collect();
+        let status = unsafe {
+            sys::AudioUnitScheduleParameters(self.instance, events.as_ptr(), events.len() as u32)
+        };
+        Error::from_os_status(status)
+    }
+
+    /// Schedule the given parameter changes to be applied during the next render.
+    ///
+    /// Each event is a frame offset into the next render, the parameter to change and its new
+    /// value. The events may be given in any order: they are grouped by frame, keeping the given
+    /// order of events landing on the same frame, and scheduled via a single call to
+    /// `AudioUnitScheduleParameters`.
+    ///
+    /// Whether a parameter supports being scheduled at a frame offset depends on the unit. Units
+    /// that don't apply the change at the start of the render instead. This allocates the
+    /// scheduled events, so it's best called outside the render callback.
+    pub fn apply_automation(
+        &mut self,
+        events: &[(u32, AudioUnitParameter, f32)],
+    ) -> Result<(), Error> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        let events = parameter_events(events);
+        let status = unsafe {
+            sys::AudioUnitScheduleParameters(self.instance, events.as_ptr(), events.len() as u32)
+        };
+        Error::from_os_status(status)
+    }
+}
+
+/// Convert automation events into immediate parameter events, grouped by frame.
+fn parameter_events(
+    events: &[(u32, AudioUnitParameter, f32)],
+) -> Vec<sys::AudioUnitParameterEvent> {
+    let mut events = events.to_vec();
+    // A stable sort, so that later events on the same frame still win.
+    events.sort_by_key(|&(frame, _, _)| frame);
+    events
+        .iter()
+        .map(|&(offset, parameter, value)| {
+            ParameterEvent::Immediate { offset, value }.to_sys(parameter)
+        })
+        .collect()
+}
+
+#[test]
+fn test_parameter_events_dense_curve() {
+    use super::Scope;
+
+    let cutoff = AudioUnitParameter {
+        id: 0,
+        scope: Scope::Global,
+        element: 0,
+    };
+    let resonance = AudioUnitParameter { id: 1, ..cutoff };
+    // A ramp on every frame of a 512 frame render, given in reverse, with a second parameter
+    // changing on every fourth frame.
+    let mut automation = Vec::new();
+    for frame in (0..512u32).rev() {
+        automation.push((frame, cutoff, frame as f32 / 512.0));
+        if frame % 4 == 0 {
+            automation.push((frame, resonance, 1.0));
+        }
+    }
+    let events = parameter_events(&automation);
+    assert_eq!(events.len(), 512 + 128);
+    let offsets: Vec<u32> = events
+        .iter()
+        .map(|event| unsafe { event.eventValues.immediate.bufferOffset })
+        .collect();
+    assert!(offsets.windows(2).all(|pair| pair[0] <= pair[1]));
+    let first = &events[0];
+    assert_eq!(
+        (first.parameter, first.scope, first.element),
+        (0, Scope::Global as c_uint, 0)
+    );
+    assert_eq!(events[1].parameter, 1);
+    let value = unsafe { events[events.len() - 1].eventValues.immediate.value };
+    assert_eq!(value, 511.0 / 512.0);
+}
+
+#[test]
+fn test_parameter_event_ramp_to_sys() {
+    use super::Scope;
+
+    let volume = AudioUnitParameter {
+        id: 0,
+        scope: Scope::Input,
+        element: 3,
+    };
+    let ramp = ParameterEvent::Ramp {
+        start_offset: -64,
+        duration: 256,
+        start_value: 0.0,
+        end_value: 1.0,
+    };
+    let event = ramp.to_sys(volume);
+    assert_eq!(event.eventType, sys::kParameterEvent_Ramped);
+    assert_eq!((event.scope, event.element), (Scope::Input as c_uint, 3));
+    let ramp = unsafe { event.eventValues.ramp };
+    assert_eq!((ramp.startBufferOffset, ramp.durationInFrames), (-64, 256));
+    assert_eq!((ramp.startValue, ramp.endValue), (0.0, 1.0));
+}
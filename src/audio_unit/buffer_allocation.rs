@@ -0,0 +1,89 @@
+//! Controlling whether an **AudioUnit** allocates the buffers of its elements.
+//!
+//! Hosts that supply their own memory may disable the unit's allocation via
+//! `kAudioUnitProperty_ShouldAllocateBuffer`. An element whose allocation is disabled and for which
+//! no buffer is then provided hands null buffers to its render callback, the classic null-buffer
+//! crash, so the elements are tracked and checked before the unit is initialized.
+
+use super::{AudioUnit, Scope};
+use crate::error::Error;
+use std::os::raw::c_uint;
+use sys;
+
+/// The elements of an **AudioUnit** whose buffer allocation is disabled, and the buffers provided
+/// in place of the unit's own.
+#[derive(Default)]
+pub(crate) struct BufferAllocation {
+    // The raw `(scope, element)` pairs for which allocation is disabled.
+    disabled: Vec<(c_uint, u32)>,
+    // Buffers given to the unit via `kAudioUnitProperty_SetExternalBuffer`. These outlive the
+    // instance, as struct fields are dropped after the instance is disposed of in `drop`.
+    external_buffers: Vec<(c_uint, u32, Box<[u8]>)>,
+}
+
+impl AudioUnit {
+    /// Set whether or not the **AudioUnit** allocates the buffer of the given element.
+    ///
+    /// Allocation is enabled by default. When disabling it for an element of the `Input` scope, a
+    /// buffer must be provided via `set_external_buffer` before the unit is next initialized, or
+    /// `initialize` returns `Error::BufferNotProvided`. Elements of the `Output` scope need no
+    /// buffer, as renders via `render_into` and input callbacks always provide their own.
+    ///
+    /// The property may only be set while the unit is uninitialized.
+    pub fn set_should_allocate_buffer(
+        &mut self,
+        scope: Scope,
+        elem: u32,
+        allocate: bool,
+    ) -> Result<(), Error> {
+        let id = sys::kAudioUnitProperty_ShouldAllocateBuffer;
+        let should_allocate = allocate as u32;
+        super::set_property_raw(self.instance, id, scope, elem, Some(&should_allocate))?;
+        let element = (scope as c_uint, elem);
+        let disabled = &mut self.buffer_allocation.disabled;
+        disabled.retain(|&disabled| disabled != element);
+        if !allocate {
+            disabled.push(element);
+        }
+        Ok(())
+    }
+
+    /// Provide a zeroed buffer of `size` bytes for the unit to use for the given element, in place
+    /// of one allocated by the unit itself.
+    ///
+    /// The buffer is owned by the **AudioUnit** and replaces any buffer previously provided for the
+    /// element. The property may only be set while the unit is uninitialized.
+    pub fn set_external_buffer(&mut self, scope: Scope, elem: u32, size: u32) -> Result<(), Error> {
+        let mut buffer = vec![0u8; size as usize].into_boxed_slice();
+        let external_buffer = sys::AudioUnitExternalBuffer {
+            buffer: buffer.as_mut_ptr(),
+            size,
+        };
+        let id = sys::kAudioUnitProperty_SetExternalBuffer;
+        super::set_property_raw(self.instance, id, scope, elem, Some(&external_buffer))?;
+        let scope = scope as c_uint;
+        let external_buffers = &mut self.buffer_allocation.external_buffers;
+        // The previous buffer is no longer referenced by the unit, so may now be freed.
+        external_buffers.retain(|&(s, e, _)| (s, e) != (scope, elem));
+        external_buffers.push((scope, elem, buffer));
+        Ok(())
+    }
+
+    /// Check that every `Input` scope element whose allocation is disabled has been provided a
+    /// buffer, returning `Error::BufferNotProvided` otherwise.
+    pub(crate) fn check_buffer_provision(&self) -> Result<(), Error> {
+        let allocation = &self.buffer_allocation;
+        let unprovided = allocation.disabled.iter().any(|&(scope, elem)| {
+            scope == Scope::Input as c_uint
+                && !allocation
+                    .external_buffers
+                    .iter()
+                    .any(|&(s, e, _)| (s, e) == (scope, elem))
+        });
+        if unprovided {
+            Err(Error::BufferNotProvided)
+        } else {
+            Ok(())
+        }
+    }
+}
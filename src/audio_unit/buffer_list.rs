@@ -0,0 +1,196 @@
+//! An owned `AudioBufferList` of `f32` samples, used to pull audio from an **AudioUnit**.
+
+use std::mem;
+use std::os::raw::c_void;
+use std::ptr;
+use std::slice;
+use sys;
+
+/// An owned `AudioBufferList` holding `f32` samples for some number of channels.
+///
+/// For an interleaved layout the list holds a single buffer containing every channel, otherwise
+/// it holds one buffer per channel.
+///
+/// Note that an **AudioUnit** may point the buffers at its own memory while rendering, so the
+/// samples should always be read via `buffer` after rendering.
+pub struct BufferList {
+    // Backing storage for the samples of every buffer, stored one buffer after another.
+    samples: Vec<f32>,
+    // Storage for a variable-length `AudioBufferList` with `num_buffers` `AudioBuffer`s.
+    //
+    // The extra element leaves room for the `mNumberBuffers` header preceding the buffers.
+    buffer_list: Vec<sys::AudioBuffer>,
+    channels: usize,
+    max_frames: usize,
+    frames: usize,
+    interleaved: bool,
+}
+
+impl BufferList {
+    /// Allocate a buffer list able to hold up to `max_frames` frames of `channels` channels.
+    pub fn new(channels: usize, max_frames: usize, interleaved: bool) -> Self {
+        let num_buffers = if interleaved { 1 } else { channels };
+        let empty_buffer = sys::AudioBuffer {
+            mNumberChannels: 0,
+            mDataByteSize: 0,
+            mData: ptr::null_mut(),
+        };
+        let mut buffer_list = BufferList {
+            samples: vec![0.0; channels * max_frames],
+            buffer_list: vec![empty_buffer; num_buffers + 1],
+            channels,
+            max_frames,
+            frames: max_frames,
+            interleaved,
+        };
+        buffer_list.set_frames(max_frames);
+        buffer_list
+    }
+
+    /// The number of channels.
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// Whether or not the channels are interleaved within a single buffer.
+    pub fn is_interleaved(&self) -> bool {
+        self.interleaved
+    }
+
+    /// The maximum number of frames the list can hold.
+    pub fn max_frames(&self) -> usize {
+        self.max_frames
+    }
+
+    /// The number of frames the list currently describes.
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    /// The number of buffers in the list.
+    pub fn num_buffers(&self) -> usize {
+        if self.interleaved {
+            1
+        } else {
+            self.channels
+        }
+    }
+
+    /// Reset the buffers to point at the list's own storage, sized to hold `frames` frames.
+    ///
+    /// **Panics** if `frames` is greater than `max_frames`.
+    pub fn set_frames(&mut self, frames: usize) {
+        assert!(frames <= self.max_frames);
+        let num_buffers = self.num_buffers();
+        let channels_per_buffer = self.channels / num_buffers.max(1);
+        let buffer_len = self.max_frames * channels_per_buffer;
+        let data_byte_size = frames * channels_per_buffer * mem::size_of::<f32>();
+        let list = self.buffer_list.as_mut_ptr() as *mut sys::AudioBufferList;
+        unsafe {
+            (*list).mNumberBuffers = num_buffers as u32;
+            let buffers = (*list).mBuffers.as_mut_ptr();
+            for i in 0..num_buffers {
+                *buffers.add(i) = sys::AudioBuffer {
+                    mNumberChannels: channels_per_buffer as u32,
+                    mDataByteSize: data_byte_size as u32,
+                    mData: self.samples.as_mut_ptr().add(i * buffer_len) as *mut c_void,
+                };
+            }
+        }
+        self.frames = frames;
+    }
+
+    /// Copy the first `frames` frames of `other` into the list's own storage, starting at frame
+    /// `offset`.
+    ///
+    /// The lists must have the same channel count and layout.
+    ///
+    /// **Panics** if the frames do not fit within `max_frames` or `other` holds fewer frames.
+    pub(crate) fn copy_frames_from(&mut self, offset: usize, other: &BufferList, frames: usize) {
+        assert_eq!(self.channels, other.channels);
+        assert_eq!(self.interleaved, other.interleaved);
+        assert!(offset + frames <= self.max_frames);
+        let num_buffers = self.num_buffers();
+        let channels_per_buffer = self.channels / num_buffers.max(1);
+        let buffer_len = self.max_frames * channels_per_buffer;
+        for i in 0..num_buffers {
+            let src = &other.buffer(i)[..frames * channels_per_buffer];
+            let start = i * buffer_len + offset * channels_per_buffer;
+            self.samples[start..start + src.len()].copy_from_slice(src);
+        }
+    }
+
+    /// The samples of the buffer at the given index, where each buffer holds a single channel
+    /// unless the list is interleaved.
+    ///
+    /// **Panics** if `index` is out of range.
+    pub fn buffer(&self, index: usize) -> &[f32] {
+        assert!(index < self.num_buffers());
+        unsafe {
+            let buffer = &*self.audio_buffers().add(index);
+            if buffer.mData.is_null() {
+                return &[];
+            }
+            let len = buffer.mDataByteSize as usize / mem::size_of::<f32>();
+            slice::from_raw_parts(buffer.mData as *const f32, len)
+        }
+    }
+
+    /// The sample of the given channel within the given frame.
+    pub fn sample(&self, channel: usize, frame: usize) -> f32 {
+        if self.interleaved {
+            self.buffer(0)[frame * self.channels + channel]
+        } else {
+            self.buffer(channel)[frame]
+        }
+    }
+
+    /// A pointer to the `AudioBufferList` for passing to Core Audio.
+    pub fn as_mut_ptr(&mut self) -> *mut sys::AudioBufferList {
+        self.buffer_list.as_mut_ptr() as *mut sys::AudioBufferList
+    }
+
+    fn audio_buffers(&self) -> *const sys::AudioBuffer {
+        let list = self.buffer_list.as_ptr() as *const sys::AudioBufferList;
+        unsafe { (*list).mBuffers.as_ptr() }
+    }
+}
+
+// The raw pointers only ever point into the list's own storage (or that of an **AudioUnit**
+// during a render).
+unsafe impl Send for BufferList {}
+
+#[test]
+fn test_buffer_list_non_interleaved_layout() {
+    let mut buffer_list = BufferList::new(2, 4, false);
+    assert_eq!(buffer_list.num_buffers(), 2);
+    buffer_list.set_frames(3);
+    assert_eq!(buffer_list.buffer(0).len(), 3);
+    assert_eq!(buffer_list.buffer(1).len(), 3);
+    unsafe {
+        let list = buffer_list.as_mut_ptr();
+        assert_eq!((*list).mNumberBuffers, 2);
+        let right = (*list).mBuffers.as_ptr().add(1);
+        *((*right).mData as *mut f32).add(2) = 0.5;
+    }
+    assert_eq!(buffer_list.sample(1, 2), 0.5);
+    assert_eq!(buffer_list.sample(0, 2), 0.0);
+}
+
+#[test]
+fn test_buffer_list_copy_frames_from() {
+    let mut chunk = BufferList::new(2, 2, true);
+    unsafe {
+        let list = chunk.as_mut_ptr();
+        let data = (*list).mBuffers[0].mData as *mut f32;
+        for (i, &sample) in [1.0, 2.0, 3.0, 4.0].iter().enumerate() {
+            *data.add(i) = sample;
+        }
+    }
+    let mut buffer_list = BufferList::new(2, 4, true);
+    buffer_list.copy_frames_from(2, &chunk, 2);
+    assert_eq!(
+        buffer_list.buffer(0),
+        &[0.0, 0.0, 0.0, 0.0, 1.0, 2.0, 3.0, 4.0]
+    );
+}
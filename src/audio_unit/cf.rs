@@ -0,0 +1,91 @@
+//! Small helpers for bridging the Core Foundation types returned by the Audio Unit API.
+
+use core_foundation_sys::array::{CFArrayGetCount, CFArrayGetValueAtIndex, CFArrayRef};
+use core_foundation_sys::base::{kCFAllocatorDefault, Boolean, CFIndex, CFRelease, CFTypeRef};
+use core_foundation_sys::string::{
+    kCFStringEncodingUTF8, CFStringCreateWithBytes, CFStringGetCString, CFStringGetCStringPtr,
+    CFStringGetLength, CFStringGetMaximumSizeForEncoding, CFStringRef,
+};
+use core_foundation_sys::url::{CFURLCreateFromFileSystemRepresentation, CFURLRef};
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+/// Copy the contents of a `CFString` into an owned `String`.
+///
+/// Returns `None` if the given string is null or could not be converted to UTF-8.
+///
+/// This does not release the given string.
+pub(crate) unsafe fn string_from_cf_string(cf_string: CFStringRef) -> Option<String> {
+    if cf_string.is_null() {
+        return None;
+    }
+
+    // Try the fast path first, which only succeeds if the string is stored internally as UTF-8.
+    let c_string = CFStringGetCStringPtr(cf_string, kCFStringEncodingUTF8);
+    if !c_string.is_null() {
+        return Some(CStr::from_ptr(c_string).to_string_lossy().into_owned());
+    }
+
+    let len = CFStringGetLength(cf_string);
+    let capacity = CFStringGetMaximumSizeForEncoding(len, kCFStringEncodingUTF8) + 1;
+    let mut buf: Vec<c_char> = vec![0; capacity as usize];
+    let result = CFStringGetCString(cf_string, buf.as_mut_ptr(), capacity, kCFStringEncodingUTF8);
+    if result == 0 {
+        return None;
+    }
+    Some(CStr::from_ptr(buf.as_ptr()).to_string_lossy().into_owned())
+}
+
+/// Copy the contents of a `CFArray` of `CFString`s into a `Vec` of owned `String`s.
+///
+/// Returns an empty `Vec` if the given array is null. Elements that could not be converted to
+/// UTF-8 are skipped.
+///
+/// This does not release the given array.
+pub(crate) unsafe fn strings_from_cf_array(cf_array: CFArrayRef) -> Vec<String> {
+    if cf_array.is_null() {
+        return Vec::new();
+    }
+    (0..CFArrayGetCount(cf_array))
+        .filter_map(|i| string_from_cf_string(CFArrayGetValueAtIndex(cf_array, i) as CFStringRef))
+        .collect()
+}
+
+/// Create a `CFString` from the given string, which must be released by the caller.
+///
+/// Returns a null pointer if the string could not be created.
+pub(crate) fn cf_string_from_str(s: &str) -> CFStringRef {
+    unsafe {
+        CFStringCreateWithBytes(
+            kCFAllocatorDefault,
+            s.as_ptr(),
+            s.len() as CFIndex,
+            kCFStringEncodingUTF8,
+            false as Boolean,
+        )
+    }
+}
+
+/// Release a Core Foundation object, ignoring null pointers.
+pub(crate) unsafe fn release<T>(cf: *const T) {
+    if !cf.is_null() {
+        CFRelease(cf as CFTypeRef);
+    }
+}
+
+/// Create a `CFURL` for the given file system path, which must be released by the caller.
+///
+/// Returns a null pointer if the URL could not be created.
+pub(crate) fn url_from_path(path: &Path) -> CFURLRef {
+    let bytes = path.as_os_str().as_bytes();
+    unsafe {
+        CFURLCreateFromFileSystemRepresentation(
+            kCFAllocatorDefault,
+            bytes.as_ptr(),
+            bytes.len() as CFIndex,
+            false as Boolean,
+        )
+    }
+}
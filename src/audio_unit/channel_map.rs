@@ -0,0 +1,99 @@
+//! Routing the channels of an I/O **AudioUnit**'s client stream to the channels of its device.
+//!
+//! By default a stereo stream plays through and records from the first two channels of a
+//! multichannel interface. A channel map instead picks which hardware channels each client
+//! channel is routed to or from, e.g. outputs 3 and 4 for a second pair of monitors.
+//!
+//! Find the original channel map documentation in Technical Note TN2091 [here](https://developer.apple.com/library/archive/technotes/tn2091/_index.html).
+
+use super::{AudioUnit, Element, Scope};
+use crate::error::Error;
+use sys;
+
+/// Whether a **ChannelMap** routes the output or the input of an I/O unit.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Direction {
+    Output,
+    Input,
+}
+
+/// A mapping between the client channels of an I/O unit and the channels of its device, as set
+/// via `AudioUnit::set_channel_map`.
+///
+/// Construct an empty map for either direction and add each route via `route`, e.g.
+/// `ChannelMap::output(8).route(0, 2).route(1, 3)` plays a stereo stream through device outputs 3
+/// and 4.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChannelMap {
+    direction: Direction,
+    // Indexed by device channel for output maps and by client channel for input maps, as Core
+    // Audio expects. `-1` marks an unrouted channel.
+    map: Vec<i32>,
+}
+
+impl ChannelMap {
+    /// An output map for a device with `device_channels` output channels, none of which are
+    /// routed to a client channel until `route` is called. Unrouted device channels are silent.
+    pub fn output(device_channels: usize) -> ChannelMap {
+        ChannelMap {
+            direction: Direction::Output,
+            map: vec![-1; device_channels],
+        }
+    }
+
+    /// An input map for a client stream of `client_channels` channels, none of which are routed
+    /// from a device channel until `route` is called. Unrouted client channels are silent.
+    pub fn input(client_channels: usize) -> ChannelMap {
+        ChannelMap {
+            direction: Direction::Input,
+            map: vec![-1; client_channels],
+        }
+    }
+
+    /// Route the client channel `client_channel` to (or, for an input map, from) the device
+    /// channel `device_channel`, both counted from zero.
+    ///
+    /// An output map may route the same client channel to several device channels, whereas each
+    /// client channel of an input map is routed from a single device channel, so routing it again
+    /// replaces the previous route.
+    ///
+    /// Panics if `device_channel` exceeds the channel count given to `output`, or
+    /// `client_channel` exceeds the channel count given to `input`.
+    pub fn route(mut self, client_channel: usize, device_channel: usize) -> ChannelMap {
+        match self.direction {
+            Direction::Output => self.map[device_channel] = client_channel as i32,
+            Direction::Input => self.map[client_channel] = device_channel as i32,
+        }
+        self
+    }
+
+    /// The raw map as Core Audio expects it, indexed by device channel for output maps and by
+    /// client channel for input maps, with `-1` for unrouted channels.
+    pub fn as_slice(&self) -> &[i32] {
+        &self.map
+    }
+}
+
+impl AudioUnit {
+    /// Route the client channels of the I/O unit to or from the given device channels.
+    ///
+    /// Output maps apply to the device the unit plays through and input maps to the device it
+    /// records from. Most units only accept a channel map while uninitialized.
+    pub fn set_channel_map(&mut self, channel_map: &ChannelMap) -> Result<(), Error> {
+        let id = sys::kAudioOutputUnitProperty_ChannelMap;
+        // The map is set on the client side of the element it applies to.
+        let (scope, element) = match channel_map.direction {
+            Direction::Output => (Scope::Input, Element::Output),
+            Direction::Input => (Scope::Output, Element::Input),
+        };
+        super::set_property_array(self.instance, id, scope, element as u32, &channel_map.map)
+    }
+}
+
+#[test]
+fn test_channel_map_route() {
+    let output = ChannelMap::output(6).route(0, 2).route(1, 3).route(0, 4);
+    assert_eq!(output.as_slice(), &[-1, -1, 0, 1, 0, -1]);
+    let input = ChannelMap::input(2).route(0, 7).route(1, 6).route(1, 5);
+    assert_eq!(input.as_slice(), &[7, 5]);
+}
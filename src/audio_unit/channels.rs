@@ -0,0 +1,89 @@
+//! The channel configurations supported by an **AudioUnit**.
+//!
+//! Units with a variable number of channels list the input and output channel counts they
+//! support, which hosts consult before negotiating stream formats.
+//!
+//! Find the original `AUChannelInfo` documentation [here](https://developer.apple.com/documentation/audiotoolbox/auchannelinfo).
+
+use super::{AudioUnit, Scope};
+use crate::error::{AudioUnitError, Error};
+use sys;
+
+/// A combination of input and output channel counts supported by an **AudioUnit**.
+///
+/// Each count is either a specific number of channels or one of the negative wildcards
+/// described by `AUChannelInfo`:
+///
+/// - `-1` for any number of channels, provided the input and output counts match.
+/// - `-2` for any number of channels, independently of the other count.
+/// - Less than `-2` for any number of channels up to its absolute value.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ChannelConfig {
+    pub input: i16,
+    pub output: i16,
+}
+
+impl AudioUnit {
+    /// The channel configurations supported by the **AudioUnit**.
+    ///
+    /// An empty `Vec` is returned for units that don't list their configurations, which support
+    /// any number of channels provided the input and output counts match.
+    pub fn supported_channel_configs(&self) -> Result<Vec<ChannelConfig>, Error> {
+        let id = sys::kAudioUnitProperty_SupportedNumChannels;
+        let infos =
+            super::get_property_array::<sys::AUChannelInfo>(self.instance, id, Scope::Global, 0);
+        match infos {
+            Ok(infos) => Ok(infos
+                .iter()
+                .map(|info| ChannelConfig {
+                    input: info.inChannels,
+                    output: info.outChannels,
+                })
+                .collect()),
+            Err(Error::AudioUnit(AudioUnitError::InvalidProperty)) => Ok(Vec::new()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// The maximum number of channels supported by the given `Input` or `Output` scope, or `None`
+    /// if the number of channels is unlimited.
+    ///
+    /// This is the largest count within `supported_channel_configs`, where the wildcards limiting
+    /// the count to an absolute value count as that value. Returns `Error::AudioUnit(InvalidScope)`
+    /// for the `Global` scope, which has no channels.
+    pub fn max_channels(&self, scope: Scope) -> Result<Option<u16>, Error> {
+        let configs = self.supported_channel_configs()?;
+        let counts: Vec<i16> = match scope {
+            Scope::Input => configs.iter().map(|config| config.input).collect(),
+            Scope::Output => configs.iter().map(|config| config.output).collect(),
+            _ => return Err(Error::AudioUnit(AudioUnitError::InvalidScope)),
+        };
+        Ok(max_channel_count(&counts))
+    }
+}
+
+/// The maximum of the given `AUChannelInfo` channel counts, or `None` if unlimited.
+fn max_channel_count(counts: &[i16]) -> Option<u16> {
+    let mut max = 0;
+    for &count in counts {
+        match count {
+            -2 | -1 => return None,
+            count => max = max.max(count.unsigned_abs()),
+        }
+    }
+    // Units which list no configurations accept any number of channels.
+    if counts.is_empty() {
+        None
+    } else {
+        Some(max)
+    }
+}
+
+#[test]
+fn test_max_channel_count() {
+    assert_eq!(max_channel_count(&[]), None);
+    assert_eq!(max_channel_count(&[1, 2]), Some(2));
+    assert_eq!(max_channel_count(&[2, -1]), None);
+    assert_eq!(max_channel_count(&[-2]), None);
+    assert_eq!(max_channel_count(&[2, -8]), Some(8));
+}
@@ -0,0 +1,58 @@
+//! Locating the custom Cocoa editor of an **AudioUnit**.
+//!
+//! Units with a custom UI name the bundle containing their `NSView` factory class, and the class
+//! itself, via `kAudioUnitProperty_CocoaUI`. The crate does not instantiate the view, but hosts
+//! with their own Cocoa bridge may load the bundle and instantiate the class to show the editor.
+//!
+//! Find the original `AudioUnitCocoaViewInfo` documentation [here](https://developer.apple.com/documentation/audiotoolbox/audiounitcocoaviewinfo).
+
+use super::{cf, AudioUnit, Scope};
+use crate::error::{AudioUnitError, Error};
+use core_foundation_sys::string::CFStringRef;
+use core_foundation_sys::url::{CFURLGetString, CFURLRef};
+use std::os::raw::c_void;
+use sys;
+
+/// The location of an **AudioUnit**'s custom Cocoa editor, as returned by
+/// `AudioUnit::cocoa_ui_info`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CocoaViewInfo {
+    /// The URL of the bundle containing the view factory classes.
+    pub bundle_url: String,
+    /// The names of the classes within the bundle that implement the `AUCocoaUIBase` protocol,
+    /// any of which may be used to create the view.
+    pub class_names: Vec<String>,
+}
+
+impl AudioUnit {
+    /// The location of the unit's custom Cocoa editor, or `None` if the unit has no custom UI.
+    pub fn cocoa_ui_info(&self) -> Result<Option<CocoaViewInfo>, Error> {
+        let id = sys::kAudioUnitProperty_CocoaUI;
+        // An `AudioUnitCocoaViewInfo` is a bundle `CFURL` followed by a variable number of class
+        // name `CFString`s, all of which the caller is responsible for releasing.
+        let refs = super::get_property_array::<*const c_void>(self.instance, id, Scope::Global, 0);
+        let refs = match refs {
+            Ok(refs) => refs,
+            Err(Error::AudioUnit(AudioUnitError::InvalidProperty)) => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let info = unsafe {
+            let bundle_url = refs
+                .first()
+                .and_then(|&url| cf::string_from_cf_string(CFURLGetString(url as CFURLRef)));
+            let class_names = refs
+                .iter()
+                .skip(1)
+                .filter_map(|&class_name| cf::string_from_cf_string(class_name as CFStringRef))
+                .collect();
+            for &cf_ref in &refs {
+                cf::release(cf_ref);
+            }
+            bundle_url.map(|bundle_url| CocoaViewInfo {
+                bundle_url,
+                class_names,
+            })
+        };
+        Ok(info)
+    }
+}
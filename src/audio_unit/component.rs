@@ -0,0 +1,233 @@
+//! Finding and inspecting the audio components registered with the system.
+//!
+//! An **AudioComponent** is the class of an audio unit, from which **AudioUnit** instances are
+//! created. Components are found by matching against an `AudioComponentDescription`, in which
+//! zeroed fields act as wildcards, so components of every manufacturer may be found and then
+//! instantiated via `AudioUnit::from_component`.
+
+use super::{cf, Type};
+use crate::error::Error;
+use crate::four_char_code::FourCharCode;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::mem;
+use std::ptr;
+use sys;
+
+/// An audio component registered with the system.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AudioComponent {
+    component: sys::AudioComponent,
+}
+
+/// An iterator yielding every **AudioComponent** matching a description.
+///
+/// Created via `find_components`.
+pub struct Components {
+    description: sys::AudioComponentDescription,
+    previous: sys::AudioComponent,
+}
+
+/// Find all components matching the given description.
+///
+/// The `componentType`, `componentSubType` and `componentManufacturer` fields of the description
+/// match any value when zero, so a zeroed description matches every component.
+pub fn find_components(description: sys::AudioComponentDescription) -> Components {
+    Components {
+        description,
+        previous: ptr::null_mut(),
+    }
+}
+
+/// The version of an audio component, as returned by `AudioComponent::version`.
+///
+/// Displayed as `major.minor.bug_fix`, e.g. `2.0.0`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ComponentVersion {
+    /// The major version.
+    pub major: u16,
+    /// The minor version.
+    pub minor: u8,
+    /// The bug-fix version.
+    pub bug_fix: u8,
+}
+
+impl ComponentVersion {
+    /// Unpack a version of the form `0xMMMMmmbb`, as returned by `AudioComponentGetVersion`.
+    pub fn from_u32(version: u32) -> ComponentVersion {
+        ComponentVersion {
+            major: (version >> 16) as u16,
+            minor: (version >> 8) as u8,
+            bug_fix: version as u8,
+        }
+    }
+}
+
+impl fmt::Display for ComponentVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.bug_fix)
+    }
+}
+
+/// A summary of an installed audio component, suitable for presenting in a plugin browser.
+#[derive(Clone, Debug)]
+pub struct ComponentInfo {
+    /// The component itself, from which the unit may be instantiated.
+    pub component: AudioComponent,
+    /// The `componentType` of the component's description, e.g. `'aufx'` for an effect.
+    pub component_type: FourCharCode,
+    /// The `componentSubType` of the component's description.
+    pub sub_type: FourCharCode,
+    /// The `componentManufacturer` of the component's description, e.g. `'appl'` for Apple.
+    pub manufacturer: FourCharCode,
+    /// The name of the component, typically of the form "Manufacturer: Unit Name".
+    pub name: String,
+    /// The `Type` of the component, if it is one known to this crate.
+    pub ty: Option<Type>,
+    /// The version of the component.
+    pub version: ComponentVersion,
+    /// The `kAudioComponentFlag_*` capability flags of the component.
+    pub flags: u32,
+}
+
+/// Describe every audio component installed on the system, of any type and manufacturer.
+///
+/// Components whose description, name or version can't be read are skipped, so that a single
+/// misbehaving plugin doesn't hide every other component.
+pub fn all_components() -> Vec<ComponentInfo> {
+    let description: sys::AudioComponentDescription = unsafe { mem::zeroed() };
+    find_components(description)
+        .filter_map(|component| {
+            let desc = component.description().ok()?;
+            Some(ComponentInfo {
+                component,
+                component_type: FourCharCode(desc.componentType),
+                sub_type: FourCharCode(desc.componentSubType),
+                manufacturer: FourCharCode(desc.componentManufacturer),
+                name: component.name().ok()?,
+                ty: Type::from_u32_pair(desc.componentType, desc.componentSubType),
+                version: component.version().ok()?,
+                flags: desc.componentFlags,
+            })
+        })
+        .collect()
+}
+
+/// Group the given components by their `componentType`, i.e. the code associated with their
+/// `Type` (see `Type::as_u32`), such that effects, instruments, mixers, etc are listed together.
+///
+/// The components within each group retain their given order.
+pub fn group_by_type(components: Vec<ComponentInfo>) -> BTreeMap<FourCharCode, Vec<ComponentInfo>> {
+    let mut groups = BTreeMap::new();
+    for info in components {
+        groups
+            .entry(info.component_type)
+            .or_insert_with(Vec::new)
+            .push(info);
+    }
+    groups
+}
+
+impl Iterator for Components {
+    type Item = AudioComponent;
+    fn next(&mut self) -> Option<AudioComponent> {
+        let component = unsafe { sys::AudioComponentFindNext(self.previous, &self.description) };
+        if component.is_null() {
+            return None;
+        }
+        self.previous = component;
+        Some(AudioComponent { component })
+    }
+}
+
+impl AudioComponent {
+    /// Wrap a raw component, e.g. one returned by `AudioComponentFindNext`.
+    pub(crate) fn from_raw(component: sys::AudioComponent) -> AudioComponent {
+        AudioComponent { component }
+    }
+
+    /// The raw component.
+    pub fn as_raw(&self) -> sys::AudioComponent {
+        self.component
+    }
+
+    /// The full description of the component.
+    pub fn description(&self) -> Result<sys::AudioComponentDescription, Error> {
+        unsafe {
+            let mut desc = mem::MaybeUninit::<sys::AudioComponentDescription>::uninit();
+            let status = sys::AudioComponentGetDescription(self.component, desc.as_mut_ptr());
+            Error::from_os_status(status)?;
+            Ok(desc.assume_init())
+        }
+    }
+
+    /// The name of the component, typically of the form "Manufacturer: Unit Name".
+    pub fn name(&self) -> Result<String, Error> {
+        unsafe {
+            let mut name: sys::CFStringRef = ptr::null();
+            let status = sys::AudioComponentCopyName(self.component, &mut name);
+            Error::from_os_status(status)?;
+            let result = cf::string_from_cf_string(name as _).ok_or(Error::Unspecified);
+            cf::release(name);
+            result
+        }
+    }
+
+    /// The version of the component, e.g. for displaying alongside its `name` in a plugin list.
+    pub fn version(&self) -> Result<ComponentVersion, Error> {
+        let mut version = 0;
+        let status = unsafe { sys::AudioComponentGetVersion(self.component, &mut version) };
+        Error::from_os_status(status)?;
+        Ok(ComponentVersion::from_u32(version))
+    }
+
+    /// The `kAudioComponentFlag_*` capability flags of the component.
+    ///
+    /// Returns `0` if the description of the component could not be read.
+    pub fn flags(&self) -> u32 {
+        self.description()
+            .map(|desc| desc.componentFlags)
+            .unwrap_or(0)
+    }
+
+    /// Whether or not the component may be loaded into a sandboxed process.
+    ///
+    /// Hosts running within the App Sandbox should only load components that are sandbox safe
+    /// in-process.
+    pub fn is_sandbox_safe(&self) -> bool {
+        self.has_flag(sys::kAudioComponentFlag_SandboxSafe)
+    }
+
+    /// Whether or not the component is hidden from searches unless explicitly requested.
+    pub fn is_unsearchable(&self) -> bool {
+        self.has_flag(sys::kAudioComponentFlag_Unsearchable)
+    }
+
+    /// Whether or not the component is implemented as a version 3 (app extension) audio unit.
+    pub fn is_v3_audio_unit(&self) -> bool {
+        self.has_flag(sys::kAudioComponentFlag_IsV3AudioUnit)
+    }
+
+    /// Whether or not the component must be instantiated asynchronously.
+    ///
+    /// **AudioUnit::new** instantiates synchronously, which such components do not support.
+    pub fn requires_async_instantiation(&self) -> bool {
+        self.has_flag(sys::kAudioComponentFlag_RequiresAsyncInstantiation)
+    }
+
+    /// Whether or not a version 3 component may be loaded into the host's process.
+    pub fn can_load_in_process(&self) -> bool {
+        self.has_flag(sys::kAudioComponentFlag_CanLoadInProcess)
+    }
+
+    fn has_flag(&self, flag: u32) -> bool {
+        self.flags() & flag != 0
+    }
+}
+
+#[test]
+fn test_component_version() {
+    let version = ComponentVersion::from_u32(0x0002_0103);
+    assert_eq!((version.major, version.minor, version.bug_fix), (2, 1, 3));
+    assert_eq!(version.to_string(), "2.1.3");
+}
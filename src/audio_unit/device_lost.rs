@@ -0,0 +1,126 @@
+//! Detecting the removal of the device an output **AudioUnit** is bound to.
+//!
+//! When a device such as a USB interface is unplugged while its unit is running, every subsequent
+//! render fails. Listening to the device's `kAudioDevicePropertyDeviceIsAlive` property allows the
+//! unit to be stopped as soon as the device is lost, so that the application can rebind to another
+//! device instead.
+
+use super::{AudioUnit, Element, Scope};
+use crate::error::Error;
+use std::mem;
+use std::os::raw::c_void;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use sys;
+
+/// A callback called with the ID of a device once it has been lost.
+type DeviceLostCallback = Box<dyn FnMut(sys::AudioDeviceID) + Send>;
+
+/// The state of a device lost listener, shared with the HAL notification thread.
+pub(crate) struct DeviceLostListener {
+    instance: sys::AudioUnit,
+    device_id: sys::AudioDeviceID,
+    callback: DeviceLostCallback,
+    // Whether or not the loss has already been handled, so the callback is only called once.
+    lost: AtomicBool,
+    // Shared with the `AudioUnit`, so that it knows it has been stopped.
+    stopped: Arc<AtomicBool>,
+}
+
+/// The address of the property notifying of the device's removal.
+const DEVICE_IS_ALIVE: sys::AudioObjectPropertyAddress = sys::AudioObjectPropertyAddress {
+    mSelector: sys::kAudioDevicePropertyDeviceIsAlive,
+    mScope: sys::kAudioObjectPropertyScopeGlobal,
+    mElement: sys::kAudioObjectPropertyElementMaster,
+};
+
+impl AudioUnit {
+    /// Call `f` once the device the unit is currently bound to is removed from the system.
+    ///
+    /// When the device is lost the unit is stopped before `f` is called, so that it no longer
+    /// attempts to render, and `is_running` returns `false` from then on. `f` is called at most once, with the ID of the lost device, on a Core
+    /// Audio notification thread rather than the render thread. A typical callback notifies the
+    /// application, which then binds the unit to another device (e.g. the new default device)
+    /// and starts it again.
+    ///
+    /// Only one callback may be set at a time, replacing any previously set callback. It must be
+    /// set again after binding the unit to another device.
+    pub fn on_device_lost<F>(&mut self, f: F) -> Result<(), Error>
+    where
+        F: FnMut(sys::AudioDeviceID) + Send + 'static,
+    {
+        let id = sys::kAudioOutputUnitProperty_CurrentDevice;
+        let device_id: sys::AudioDeviceID =
+            self.get_property(id, Scope::Global, Element::Output)?;
+        self.free_device_lost_listener();
+
+        let listener = Box::into_raw(Box::new(DeviceLostListener {
+            instance: self.instance,
+            device_id,
+            callback: Box::new(f),
+            lost: AtomicBool::new(false),
+            stopped: self.stopped_by_device_loss.clone(),
+        }));
+        let status = unsafe {
+            sys::AudioObjectAddPropertyListener(
+                device_id,
+                &DEVICE_IS_ALIVE,
+                Some(device_lost_proc),
+                listener as *mut c_void,
+            )
+        };
+        if let Err(err) = Error::from_os_status(status) {
+            unsafe { drop(Box::from_raw(listener)) };
+            return Err(err);
+        }
+        self.device_lost_listener = Some(listener);
+        Ok(())
+    }
+
+    /// Remove the callback set via `on_device_lost`, if any.
+    pub(crate) fn free_device_lost_listener(&mut self) {
+        if let Some(listener) = self.device_lost_listener.take() {
+            unsafe {
+                // We don't want to panic in `drop`, so we'll ignore returned errors.
+                let status = sys::AudioObjectRemovePropertyListener(
+                    (*listener).device_id,
+                    &DEVICE_IS_ALIVE,
+                    Some(device_lost_proc),
+                    listener as *mut c_void,
+                );
+                Error::from_os_status(status).ok();
+                drop(Box::from_raw(listener));
+            }
+        }
+    }
+}
+
+/// Property listener procedure stopping the unit and calling the user's callback once the device
+/// is no longer alive.
+unsafe extern "C" fn device_lost_proc(
+    device_id: sys::AudioObjectID,
+    _n_addresses: u32,
+    _addresses: *const sys::AudioObjectPropertyAddress,
+    client_data: *mut c_void,
+) -> sys::OSStatus {
+    let listener = client_data as *mut DeviceLostListener;
+    let mut alive: u32 = 0;
+    let mut data_size = mem::size_of::<u32>() as u32;
+    let status = sys::AudioObjectGetPropertyData(
+        device_id,
+        &DEVICE_IS_ALIVE,
+        0,
+        ptr::null(),
+        &mut data_size,
+        &mut alive as *mut _ as *mut c_void,
+    );
+    // A device that can't be queried is as good as gone.
+    let lost = status != 0 || alive == 0;
+    if lost && !(*listener).lost.swap(true, Ordering::SeqCst) {
+        Error::from_os_status(sys::AudioOutputUnitStop((*listener).instance)).ok();
+        (*listener).stopped.store(true, Ordering::SeqCst);
+        ((*listener).callback)(device_id);
+    }
+    0
+}
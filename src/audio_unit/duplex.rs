@@ -0,0 +1,107 @@
+//! Processing the live input of an audio device into its output via a single HAL unit.
+//!
+//! A HAL unit with both input and output enabled calls its input callback with the captured audio
+//! immediately before calling its render callback for the same number of frames, on the same I/O
+//! thread. The captured audio is handed from one to the other via a lock-free ring buffer, so the
+//! I/O thread never blocks.
+
+use super::audio_format::LinearPcmFlags;
+use super::render_callback::{self, data};
+use super::ring_buffer::RingBuffer;
+use super::{AudioUnit, Element, IOType, SampleFormat, Scope, StreamFormat};
+use crate::error::Error;
+use std::sync::Arc;
+use sys;
+
+/// A HAL **AudioUnit** capturing the input of a device and playing the result of processing it
+/// to the output of the same device.
+pub struct Duplex {
+    audio_unit: AudioUnit,
+}
+
+impl Duplex {
+    /// Construct a **Duplex** on the device with the given ID, processing `channels` channels of
+    /// interleaved `f32` audio at `sample_rate` in both directions.
+    ///
+    /// `process` is called on the I/O thread with each block of captured input and the output to
+    /// fill, both holding the same number of interleaved frames. It must not block. Until input
+    /// has been captured, e.g. within the first cycle, the input is padded with silence.
+    ///
+    /// The unit is not started until `start` is called.
+    pub fn new<F>(
+        device_id: sys::AudioDeviceID,
+        channels: u32,
+        sample_rate: f64,
+        mut process: F,
+    ) -> Result<Duplex, Error>
+    where
+        F: FnMut(&[f32], &mut [f32]) + Send + 'static,
+    {
+        // Enabling I/O and changing the stream formats requires the unit to be uninitialized.
+        let mut audio_unit = AudioUnit::new_uninitialized(IOType::HalOutput)?;
+
+        let id = sys::kAudioOutputUnitProperty_EnableIO;
+        audio_unit.set_property(id, Scope::Input, Element::Input, Some(&1u32))?;
+        audio_unit.set_property(id, Scope::Output, Element::Output, Some(&1u32))?;
+        let id = sys::kAudioOutputUnitProperty_CurrentDevice;
+        audio_unit.set_property(id, Scope::Global, Element::Output, Some(&device_id))?;
+
+        let stream_format = StreamFormat {
+            sample_rate,
+            sample_format: SampleFormat::F32,
+            flags: LinearPcmFlags::IS_FLOAT | LinearPcmFlags::IS_PACKED,
+            channels,
+        };
+        audio_unit.set_stream_format(stream_format, Scope::Output, Element::Input)?;
+        audio_unit.set_stream_format(stream_format, Scope::Input, Element::Output)?;
+        audio_unit.initialize()?;
+
+        // Room for two of the largest blocks, so that the input never outruns the output.
+        let max_samples = audio_unit.max_frames_per_slice()? as usize * channels as usize;
+        let ring_buffer = Arc::new(RingBuffer::new(2 * max_samples));
+
+        let producer = ring_buffer.clone();
+        type Args = render_callback::Args<data::Interleaved<f32>>;
+        audio_unit.set_input_callback(move |args: Args| {
+            producer.push(args.data.buffer);
+            Ok(())
+        })?;
+
+        // Only as many samples as the output needs are taken from the ring buffer on each cycle,
+        // so the scratch buffer never grows. The backlog should the input clock run ahead of the
+        // output is bounded by the ring buffer, which drops input once it is full.
+        let mut input = vec![0.0; max_samples];
+        audio_unit.set_render_callback(move |args: Args| {
+            let output = args.data.buffer;
+            let input = &mut input[..output.len().min(max_samples)];
+            let popped = ring_buffer.pop(input);
+            for sample in &mut input[popped..] {
+                *sample = 0.0;
+            }
+            process(input, &mut output[..input.len()]);
+            Ok(())
+        })?;
+
+        Ok(Duplex { audio_unit })
+    }
+
+    /// Start capturing and playing.
+    pub fn start(&mut self) -> Result<(), Error> {
+        self.audio_unit.start()
+    }
+
+    /// Stop capturing and playing.
+    pub fn stop(&mut self) -> Result<(), Error> {
+        self.audio_unit.stop()
+    }
+
+    /// A reference to the underlying HAL **AudioUnit**.
+    pub fn audio_unit(&self) -> &AudioUnit {
+        &self.audio_unit
+    }
+
+    /// A mutable reference to the underlying HAL **AudioUnit**.
+    pub fn audio_unit_mut(&mut self) -> &mut AudioUnit {
+        &mut self.audio_unit
+    }
+}
@@ -0,0 +1,72 @@
+//! A typed wrapper around the dynamics processor **AudioUnit**, a compressor and expander.
+//!
+//! Signals above the threshold are compressed such that they never exceed the threshold by more
+//! than the headroom, while signals below the expansion threshold are attenuated according to the
+//! expansion ratio.
+//!
+//! Find the original dynamics processor parameter documentation [here](https://developer.apple.com/documentation/audiotoolbox/1534131-dynamics_processor_parameters).
+
+use super::{parameter, EffectType, ParameterId, Scope};
+use crate::error::Error;
+
+// The `kDynamicsProcessorParam_*` parameters, all of which live in the `Global` scope.
+const THRESHOLD: ParameterId = 0;
+const HEAD_ROOM: ParameterId = 1;
+const EXPANSION_RATIO: ParameterId = 2;
+const EXPANSION_THRESHOLD: ParameterId = 3;
+const ATTACK_TIME: ParameterId = 4;
+const RELEASE_TIME: ParameterId = 5;
+const MASTER_GAIN: ParameterId = 6;
+const COMPRESSION_AMOUNT: ParameterId = 1000;
+
+unit_wrapper!(
+    /// A dynamics processor **AudioUnit** with typed accessors for its parameters.
+    DynamicsProcessor,
+    EffectType::DynamicsProcessor,
+    "dynamics processor"
+);
+
+impl DynamicsProcessor {
+    /// Set the level above which compression is applied in dB, within `-40.0..=20.0`.
+    pub fn set_threshold(&mut self, db: f32) -> Result<(), Error> {
+        self.set_clamped_parameter(THRESHOLD, db)
+    }
+
+    /// Set how far above the threshold the output may rise in dB, within `0.1..=40.0`.
+    pub fn set_head_room(&mut self, db: f32) -> Result<(), Error> {
+        self.set_clamped_parameter(HEAD_ROOM, db)
+    }
+
+    /// Set the ratio of expansion applied below the expansion threshold, within `1.0..=50.0`.
+    pub fn set_expansion_ratio(&mut self, ratio: f32) -> Result<(), Error> {
+        self.set_clamped_parameter(EXPANSION_RATIO, ratio)
+    }
+
+    /// Set the level below which expansion is applied in dB.
+    pub fn set_expansion_threshold(&mut self, db: f32) -> Result<(), Error> {
+        self.set_clamped_parameter(EXPANSION_THRESHOLD, db)
+    }
+
+    /// Set the time taken to respond to a rise in level in seconds, within `0.0001..=0.2`.
+    pub fn set_attack_time(&mut self, seconds: f32) -> Result<(), Error> {
+        self.set_clamped_parameter(ATTACK_TIME, seconds)
+    }
+
+    /// Set the time taken to respond to a fall in level in seconds, within `0.01..=3.0`.
+    pub fn set_release_time(&mut self, seconds: f32) -> Result<(), Error> {
+        self.set_clamped_parameter(RELEASE_TIME, seconds)
+    }
+
+    /// Set the gain applied to the output in dB, within `-40.0..=40.0`.
+    pub fn set_master_gain(&mut self, db: f32) -> Result<(), Error> {
+        self.set_clamped_parameter(MASTER_GAIN, db)
+    }
+
+    /// The amount of compression currently being applied in dB, for metering.
+    ///
+    /// This is a read-only parameter updated by the unit as it renders.
+    pub fn compression_amount(&self) -> Result<f32, Error> {
+        let instance = self.audio_unit.instance;
+        parameter::get_parameter(instance, COMPRESSION_AMOUNT, Scope::Global, 0)
+    }
+}
@@ -0,0 +1,185 @@
+//! A typed wrapper around the audio file player **AudioUnit** for playing audio files from disk.
+//!
+//! The player is a generator, so its output must be connected to another unit (e.g. an output
+//! unit) to be heard. A file is scheduled as a region of frames, which may loop any number of
+//! times, making the player suited to seamless looping playback such as background ambience.
+//!
+//! Find the original `ScheduledAudioFileRegion` documentation [here](https://developer.apple.com/documentation/audiotoolbox/scheduledaudiofileregion).
+
+use super::{cf, AudioUnit, GeneratorType, Scope};
+use crate::error::Error;
+use std::mem;
+use std::os::raw::{c_uint, c_void};
+use std::path::Path;
+use std::ptr;
+use std::sync::Mutex;
+use sys;
+
+/// A callback called when a scheduled file has finished playing.
+type CompletionCallback = Box<dyn FnMut() + Send>;
+
+/// An audio file opened for reading, closed when dropped.
+struct AudioFile {
+    id: sys::AudioFileID,
+}
+
+impl AudioFile {
+    /// Open the audio file at the given path for reading.
+    fn open(path: &Path) -> Result<AudioFile, Error> {
+        let url = cf::url_from_path(path);
+        if url.is_null() {
+            let msg = "The path cannot be represented as a URL";
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg).into());
+        }
+        let mut id: sys::AudioFileID = ptr::null_mut();
+        let status = unsafe {
+            let status =
+                sys::AudioFileOpenURL(url as _, sys::kAudioFileReadPermission as _, 0, &mut id);
+            cf::release(url);
+            status
+        };
+        Error::from_os_status(status)?;
+        Ok(AudioFile { id })
+    }
+
+    /// Read a fixed size property of the file.
+    fn property<T>(&self, id: c_uint) -> Result<T, Error> {
+        let mut size = mem::size_of::<T>() as u32;
+        unsafe {
+            let mut data = mem::MaybeUninit::<T>::uninit();
+            let status =
+                sys::AudioFileGetProperty(self.id, id, &mut size, data.as_mut_ptr() as *mut c_void);
+            Error::from_os_status(status)?;
+            Ok(data.assume_init())
+        }
+    }
+
+    /// The total number of frames of audio in the file.
+    fn frame_count(&self) -> Result<u64, Error> {
+        let format: sys::AudioStreamBasicDescription =
+            self.property(sys::kAudioFilePropertyDataFormat)?;
+        let packets: u64 = self.property(sys::kAudioFilePropertyAudioDataPacketCount)?;
+        Ok(packets * format.mFramesPerPacket as u64)
+    }
+}
+
+impl Drop for AudioFile {
+    fn drop(&mut self) {
+        // We don't want to panic in `drop`, so we'll ignore returned errors.
+        unsafe {
+            Error::from_os_status(sys::AudioFileClose(self.id)).ok();
+        }
+    }
+}
+
+/// An audio file player **AudioUnit** playing a single scheduled file.
+pub struct FilePlayer {
+    // Declared before the file and completion callback so that the unit is disposed of before
+    // either is freed.
+    audio_unit: AudioUnit,
+    file: Option<AudioFile>,
+    // Boxed so that its address, given to the unit with each scheduled region, remains stable.
+    completion: Box<Mutex<Option<CompletionCallback>>>,
+}
+
+impl FilePlayer {
+    /// Construct a new **FilePlayer** from the system's audio file player **AudioUnit**.
+    pub fn new() -> Result<FilePlayer, Error> {
+        let audio_unit = AudioUnit::new(GeneratorType::AudioFilePlayer)?;
+        Ok(FilePlayer {
+            audio_unit,
+            file: None,
+            completion: Box::new(Mutex::new(None)),
+        })
+    }
+
+    /// A reference to the underlying **AudioUnit**.
+    pub fn audio_unit(&self) -> &AudioUnit {
+        &self.audio_unit
+    }
+
+    /// A mutable reference to the underlying **AudioUnit**.
+    pub fn audio_unit_mut(&mut self) -> &mut AudioUnit {
+        &mut self.audio_unit
+    }
+
+    /// Set a callback to be called each time a file scheduled via `schedule_file` finishes
+    /// playing, e.g. to advance a playlist.
+    ///
+    /// Files that loop indefinitely never finish. The callback is called on a Core Audio thread
+    /// and replaces any previously set callback, including for the file currently scheduled.
+    pub fn on_file_complete<F>(&mut self, f: F)
+    where
+        F: FnMut() + Send + 'static,
+    {
+        let mut completion = self
+            .completion
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        *completion = Some(Box::new(f));
+    }
+
+    /// Schedule the whole of the audio file at the given path to play from the next render,
+    /// replacing any previously scheduled file.
+    ///
+    /// The file plays once and then repeats `loop_count` more times, or loops until the player is
+    /// reset if `loop_count` is `None`. Loops are joined seamlessly, so the file should be cut
+    /// such that its end flows into its start.
+    pub fn schedule_file(&mut self, path: &Path, loop_count: Option<u32>) -> Result<(), Error> {
+        let file = AudioFile::open(path)?;
+        let frames_to_play = file.frame_count()?.min(u32::MAX as u64) as u32;
+        let instance = self.audio_unit.instance;
+
+        // Clear the previous schedule before the previous file is closed.
+        self.audio_unit.reset(Scope::Global, 0)?;
+        self.file = None;
+
+        let id = sys::kAudioUnitProperty_ScheduledFileIDs;
+        let file_ids = [file.id];
+        super::set_property_array(instance, id, Scope::Global, 0, &file_ids)?;
+        self.file = Some(file);
+
+        let mut time_stamp: sys::AudioTimeStamp = unsafe { mem::zeroed() };
+        time_stamp.mFlags = sys::kAudioTimeStampSampleTimeValid;
+        let completion: *const Mutex<Option<CompletionCallback>> = &*self.completion;
+        let region = sys::ScheduledAudioFileRegion {
+            mTimeStamp: time_stamp,
+            mCompletionProc: Some(file_complete_proc),
+            mCompletionProcUserData: completion as *mut c_void,
+            mAudioFile: file_ids[0],
+            // The loop count is the number of repeats following the first play, where `u32::MAX`
+            // repeats indefinitely.
+            mLoopCount: loop_count.unwrap_or(u32::MAX),
+            mStartFrame: 0,
+            mFramesToPlay: frames_to_play,
+        };
+        let id = sys::kAudioUnitProperty_ScheduledFileRegion;
+        super::set_property_raw(instance, id, Scope::Global, 0, Some(&region))?;
+
+        // Read ahead the start of the file with the default number of frames.
+        let id = sys::kAudioUnitProperty_ScheduledFilePrime;
+        super::set_property_raw(instance, id, Scope::Global, 0, Some(&0u32))?;
+
+        // A sample time of `-1` starts playback on the next render cycle.
+        let mut start_time: sys::AudioTimeStamp = unsafe { mem::zeroed() };
+        start_time.mFlags = sys::kAudioTimeStampSampleTimeValid;
+        start_time.mSampleTime = -1.0;
+        let id = sys::kAudioUnitProperty_ScheduleStartTimeStamp;
+        super::set_property_raw(instance, id, Scope::Global, 0, Some(&start_time))
+    }
+}
+
+/// Completion procedure called by the unit once a scheduled region has finished playing.
+unsafe extern "C" fn file_complete_proc(
+    user_data: *mut c_void,
+    _file_region: *mut sys::ScheduledAudioFileRegion,
+    _result: sys::OSStatus,
+) {
+    let completion = &*(user_data as *const Mutex<Option<CompletionCallback>>);
+    let mut completion = completion
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(ref mut f) = *completion {
+        f();
+    }
+}
@@ -0,0 +1,84 @@
+//! Typed wrappers around the simple low-pass, high-pass and band-pass filter **AudioUnit**s.
+//!
+//! Each filter has just two parameters, whose identifiers are shared between the units but whose
+//! meanings differ: the low-pass and high-pass filters take a cutoff frequency and a resonance,
+//! whereas the band-pass filter takes a center frequency and a bandwidth.
+//!
+//! Find the original filter parameter documentation [here](https://developer.apple.com/documentation/audiotoolbox/1534145-low_pass_filter_parameters),
+//! [here](https://developer.apple.com/documentation/audiotoolbox/1534153-high_pass_filter_parameters)
+//! and [here](https://developer.apple.com/documentation/audiotoolbox/1534155-band_pass_filter_parameters).
+
+use super::{EffectType, ParameterId};
+use crate::error::Error;
+
+// The `kLowPassParam_*` and `kHipassParam_*` parameters, all of which live in the `Global` scope.
+const CUTOFF_FREQUENCY: ParameterId = 0;
+const RESONANCE: ParameterId = 1;
+
+// The `kBandpassParam_*` parameters, all of which live in the `Global` scope.
+const CENTER_FREQUENCY: ParameterId = 0;
+const BANDWIDTH: ParameterId = 1;
+
+unit_wrapper!(
+    /// A low-pass filter **AudioUnit**, attenuating frequencies above its cutoff.
+    Lowpass,
+    EffectType::LowPassFilter,
+    "low-pass filter"
+);
+
+unit_wrapper!(
+    /// A high-pass filter **AudioUnit**, attenuating frequencies below its cutoff.
+    Highpass,
+    EffectType::HighPassFilter,
+    "high-pass filter"
+);
+
+unit_wrapper!(
+    /// A band-pass filter **AudioUnit**, attenuating frequencies outside a band around its center
+    /// frequency.
+    Bandpass,
+    EffectType::BandPassFilter,
+    "band-pass filter"
+);
+
+impl Lowpass {
+    /// Set the frequency above which the input is attenuated in Hz, within `10.0` and half the
+    /// sample rate.
+    pub fn set_cutoff(&mut self, hz: f32) -> Result<(), Error> {
+        self.set_clamped_parameter(CUTOFF_FREQUENCY, hz)
+    }
+
+    /// Set the boost applied around the cutoff frequency in dB, within `-20.0..=40.0`.
+    pub fn set_resonance(&mut self, db: f32) -> Result<(), Error> {
+        self.set_clamped_parameter(RESONANCE, db)
+    }
+}
+
+impl Highpass {
+    /// Set the frequency below which the input is attenuated in Hz, within `10.0` and half the
+    /// sample rate.
+    pub fn set_cutoff(&mut self, hz: f32) -> Result<(), Error> {
+        self.set_clamped_parameter(CUTOFF_FREQUENCY, hz)
+    }
+
+    /// Set the boost applied around the cutoff frequency in dB, within `-20.0..=40.0`.
+    pub fn set_resonance(&mut self, db: f32) -> Result<(), Error> {
+        self.set_clamped_parameter(RESONANCE, db)
+    }
+}
+
+impl Bandpass {
+    /// Set the frequency at the center of the passed band in Hz, within `20.0` and half the
+    /// sample rate.
+    pub fn set_center_frequency(&mut self, hz: f32) -> Result<(), Error> {
+        self.set_clamped_parameter(CENTER_FREQUENCY, hz)
+    }
+
+    /// Set the width of the passed band in cents, within `100.0..=12000.0`.
+    ///
+    /// The unit measures the bandwidth in cents rather than Hz, so that a given bandwidth spans
+    /// the same musical interval at any center frequency. `1200.0` cents is one octave.
+    pub fn set_bandwidth(&mut self, cents: f32) -> Result<(), Error> {
+        self.set_clamped_parameter(BANDWIDTH, cents)
+    }
+}
@@ -0,0 +1,249 @@
+//! Providing the musical context of the host to hosted **AudioUnit**s.
+//!
+//! Music devices and effects that sync to the host, e.g. tempo-synced delays and LFOs, query the
+//! host's tempo, beat position and transport state via the callbacks set on
+//! `kAudioUnitProperty_HostCallbacks`. These are called on the render thread during each render.
+//!
+//! Find the original `HostCallbackInfo` documentation [here](https://developer.apple.com/documentation/audiotoolbox/hostcallbackinfo).
+
+use super::{property, AudioUnit, Scope};
+use crate::error::Error;
+use std::os::raw::c_void;
+use std::ptr;
+use sys;
+
+/// A snapshot of the host's musical context, as provided to hosted units by the callback set via
+/// `AudioUnit::set_host_callbacks`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct HostMusicalContext {
+    /// The beat position at the start of the current render.
+    pub current_beat: f64,
+    /// The tempo in beats per minute.
+    pub tempo: f64,
+    /// The number of frames from the start of the current render to the next beat.
+    pub delta_sample_offset_to_next_beat: u32,
+    /// The numerator of the time signature, e.g. `3.0` for 3/4.
+    pub time_sig_numerator: f32,
+    /// The denominator of the time signature, e.g. `4` for 3/4.
+    pub time_sig_denominator: u32,
+    /// The beat position of the downbeat of the current measure.
+    pub current_measure_down_beat: f64,
+    /// Whether or not the transport is playing.
+    pub is_playing: bool,
+    /// Whether or not the transport is recording.
+    pub is_recording: bool,
+    /// Whether or not the transport state changed, e.g. started, stopped or jumped, since the
+    /// previous render.
+    pub transport_state_changed: bool,
+    /// The position of the transport in samples along the host's timeline.
+    pub current_sample_in_time_line: f64,
+    /// Whether or not the transport is looping over a cycle.
+    pub is_cycling: bool,
+    /// The beat position at which the cycle starts.
+    pub cycle_start_beat: f64,
+    /// The beat position at which the cycle ends.
+    pub cycle_end_beat: f64,
+}
+
+/// A boxed host callback.
+pub(crate) type HostCallback = Box<dyn FnMut() -> HostMusicalContext + Send>;
+
+impl AudioUnit {
+    /// Register a callback providing the host's tempo, beat position and transport state to the
+    /// unit, e.g. so that a delay can sync its time to the tempo.
+    ///
+    /// The unit may call the callback several times per render, once for each of the tempo,
+    /// musical time and transport queries it makes, so it should return a snapshot prepared for
+    /// the current render rather than computing one each call.
+    ///
+    /// The callback is called on the render thread, so it must be real-time safe. It replaces any
+    /// previously registered callback and remains registered until the **AudioUnit** is dropped.
+    pub fn set_host_callbacks<F>(&mut self, f: F) -> Result<(), Error>
+    where
+        F: FnMut() -> HostMusicalContext + Send + 'static,
+    {
+        let callback: Box<HostCallback> = Box::new(Box::new(f));
+
+        // Relinquish ownership of the callback so that it can be passed to Core Audio as the user
+        // data. The pointer is converted back into a `Box` when the callback is freed.
+        let callback_ptr = Box::into_raw(callback);
+        let info = sys::HostCallbackInfo {
+            hostUserData: callback_ptr as *mut c_void,
+            beatAndTempoProc: Some(beat_and_tempo_proc),
+            musicalTimeLocationProc: Some(musical_time_location_proc),
+            transportStateProc: Some(transport_state_proc),
+            transportStateProc2: Some(transport_state_proc2),
+        };
+        let id = property::HOST_CALLBACKS;
+        let result = super::set_property_raw(self.instance, id, Scope::Global, 0, Some(&info));
+        if let Err(err) = result {
+            unsafe {
+                drop(Box::from_raw(callback_ptr));
+            }
+            return Err(err);
+        }
+
+        // The previous callback has now been replaced, so it's safe to drop it.
+        if let Some(previous_ptr) = self.host_callback.replace(callback_ptr) {
+            unsafe {
+                drop(Box::from_raw(previous_ptr));
+            }
+        }
+        Ok(())
+    }
+
+    /// Unregister and drop the callback set via `set_host_callbacks`, if any.
+    pub(crate) fn free_host_callbacks(&mut self) {
+        if let Some(callback_ptr) = self.host_callback.take() {
+            let info = sys::HostCallbackInfo {
+                hostUserData: ptr::null_mut(),
+                beatAndTempoProc: None,
+                musicalTimeLocationProc: None,
+                transportStateProc: None,
+                transportStateProc2: None,
+            };
+            let id = property::HOST_CALLBACKS;
+            // We don't want to panic in `drop`, so we'll ignore returned errors.
+            super::set_property_raw(self.instance, id, Scope::Global, 0, Some(&info)).ok();
+            unsafe {
+                drop(Box::from_raw(callback_ptr));
+            }
+        }
+    }
+}
+
+/// Call the host callback behind the given user data.
+unsafe fn musical_context(in_host_user_data: *mut c_void) -> HostMusicalContext {
+    let callback = in_host_user_data as *mut HostCallback;
+    (*callback)()
+}
+
+/// Write `value` to `ptr` unless the unit passed null, indicating that it doesn't need the value.
+unsafe fn write_out<T>(ptr: *mut T, value: T) {
+    if !ptr.is_null() {
+        *ptr = value;
+    }
+}
+
+unsafe extern "C" fn beat_and_tempo_proc(
+    in_host_user_data: *mut c_void,
+    out_current_beat: *mut sys::Float64,
+    out_current_tempo: *mut sys::Float64,
+) -> sys::OSStatus {
+    let context = musical_context(in_host_user_data);
+    write_out(out_current_beat, context.current_beat);
+    write_out(out_current_tempo, context.tempo);
+    0
+}
+
+unsafe extern "C" fn musical_time_location_proc(
+    in_host_user_data: *mut c_void,
+    out_delta_sample_offset_to_next_beat: *mut sys::UInt32,
+    out_time_sig_numerator: *mut sys::Float32,
+    out_time_sig_denominator: *mut sys::UInt32,
+    out_current_measure_down_beat: *mut sys::Float64,
+) -> sys::OSStatus {
+    let context = musical_context(in_host_user_data);
+    write_out(
+        out_delta_sample_offset_to_next_beat,
+        context.delta_sample_offset_to_next_beat,
+    );
+    write_out(out_time_sig_numerator, context.time_sig_numerator);
+    write_out(out_time_sig_denominator, context.time_sig_denominator);
+    write_out(
+        out_current_measure_down_beat,
+        context.current_measure_down_beat,
+    );
+    0
+}
+
+unsafe extern "C" fn transport_state_proc(
+    in_host_user_data: *mut c_void,
+    out_is_playing: *mut sys::Boolean,
+    out_transport_state_changed: *mut sys::Boolean,
+    out_current_sample_in_time_line: *mut sys::Float64,
+    out_is_cycling: *mut sys::Boolean,
+    out_cycle_start_beat: *mut sys::Float64,
+    out_cycle_end_beat: *mut sys::Float64,
+) -> sys::OSStatus {
+    transport_state_proc2(
+        in_host_user_data,
+        out_is_playing,
+        ptr::null_mut(),
+        out_transport_state_changed,
+        out_current_sample_in_time_line,
+        out_is_cycling,
+        out_cycle_start_beat,
+        out_cycle_end_beat,
+    )
+}
+
+// The signature is dictated by `HostCallback_GetTransportState2`.
+#[allow(clippy::too_many_arguments)]
+unsafe extern "C" fn transport_state_proc2(
+    in_host_user_data: *mut c_void,
+    out_is_playing: *mut sys::Boolean,
+    out_is_recording: *mut sys::Boolean,
+    out_transport_state_changed: *mut sys::Boolean,
+    out_current_sample_in_time_line: *mut sys::Float64,
+    out_is_cycling: *mut sys::Boolean,
+    out_cycle_start_beat: *mut sys::Float64,
+    out_cycle_end_beat: *mut sys::Float64,
+) -> sys::OSStatus {
+    let context = musical_context(in_host_user_data);
+    write_out(out_is_playing, context.is_playing as sys::Boolean);
+    write_out(out_is_recording, context.is_recording as sys::Boolean);
+    write_out(
+        out_transport_state_changed,
+        context.transport_state_changed as sys::Boolean,
+    );
+    write_out(
+        out_current_sample_in_time_line,
+        context.current_sample_in_time_line,
+    );
+    write_out(out_is_cycling, context.is_cycling as sys::Boolean);
+    write_out(out_cycle_start_beat, context.cycle_start_beat);
+    write_out(out_cycle_end_beat, context.cycle_end_beat);
+    0
+}
+
+#[test]
+fn test_host_callback_procs() {
+    let context = HostMusicalContext {
+        current_beat: 16.5,
+        tempo: 120.0,
+        delta_sample_offset_to_next_beat: 11025,
+        time_sig_numerator: 3.0,
+        time_sig_denominator: 4,
+        current_measure_down_beat: 15.0,
+        is_playing: true,
+        is_recording: false,
+        transport_state_changed: false,
+        current_sample_in_time_line: 396_900.0,
+        is_cycling: false,
+        cycle_start_beat: 0.0,
+        cycle_end_beat: 0.0,
+    };
+    let mut callback: HostCallback = Box::new(move || context);
+    let user_data = &mut callback as *mut HostCallback as *mut c_void;
+
+    let (mut beat, mut tempo) = (0.0, 0.0);
+    let status = unsafe { beat_and_tempo_proc(user_data, &mut beat, &mut tempo) };
+    assert_eq!((status, beat, tempo), (0, 16.5, 120.0));
+
+    // Units pass null for the values they don't need.
+    let mut is_playing: sys::Boolean = 0;
+    let mut cycle_end_beat = -1.0;
+    let status = unsafe {
+        transport_state_proc(
+            user_data,
+            &mut is_playing,
+            ptr::null_mut(),
+            ptr::null_mut(),
+            ptr::null_mut(),
+            ptr::null_mut(),
+            &mut cycle_end_beat,
+        )
+    };
+    assert_eq!((status, is_playing, cycle_end_beat), (0, 1, 0.0));
+}
@@ -0,0 +1,106 @@
+//! Relating the output of an **AudioUnit** to the input samples it represents.
+//!
+//! Effects with internal latency (e.g. look-ahead limiters or time-stretchers) output audio that
+//! corresponds to input rendered some time earlier. Units supporting
+//! `kAudioUnitProperty_InputSamplesInOutput` report this relationship for each render, which may
+//! be used to align metering or analysis with the audio actually being heard.
+
+use super::{property, AudioUnit, Scope};
+use crate::error::Error;
+use std::os::raw::c_void;
+use std::ptr;
+use sys;
+
+/// The input samples represented by a single render of an **AudioUnit**'s output.
+#[derive(Copy, Clone, Debug)]
+pub struct InputSamplesInOutput {
+    /// The timestamp of the output render being described.
+    pub output_time_stamp: sys::AudioTimeStamp,
+    /// The sample time of the first input sample represented in the output.
+    ///
+    /// This is negative while the unit is still producing output from before any input, e.g.
+    /// while its latency is being filled.
+    pub input_sample: f64,
+    /// The number of input samples represented in the output.
+    pub number_input_samples: f64,
+}
+
+/// A boxed input samples callback.
+pub(crate) type InputSamplesCallback = Box<dyn FnMut(InputSamplesInOutput) + Send>;
+
+impl AudioUnit {
+    /// Register a callback that is called after each render to describe which input samples
+    /// are represented in the rendered output.
+    ///
+    /// Only some units, typically those with internal latency, support this property. Other
+    /// units return an error.
+    ///
+    /// The callback is called on the render thread, so it must be real-time safe. It replaces any
+    /// previously registered callback and remains registered until the **AudioUnit** is dropped.
+    pub fn set_input_samples_in_output_callback<F>(&mut self, f: F) -> Result<(), Error>
+    where
+        F: FnMut(InputSamplesInOutput) + Send + 'static,
+    {
+        let callback: Box<InputSamplesCallback> = Box::new(Box::new(f));
+
+        // Relinquish ownership of the callback so that it can be passed to Core Audio as the user
+        // data. The pointer is converted back into a `Box` when the callback is freed.
+        let callback_ptr = Box::into_raw(callback);
+        let callback_struct = sys::AUInputSamplesInOutputCallbackStruct {
+            inputToOutputCallback: Some(input_samples_in_output_proc),
+            userData: callback_ptr as *mut c_void,
+        };
+        let id = property::INPUT_SAMPLES_IN_OUTPUT;
+        let result =
+            super::set_property_raw(self.instance, id, Scope::Global, 0, Some(&callback_struct));
+        if let Err(err) = result {
+            unsafe {
+                drop(Box::from_raw(callback_ptr));
+            }
+            return Err(err);
+        }
+
+        // The previous callback has now been replaced, so it's safe to drop it.
+        if let Some(previous_ptr) = self.input_samples_callback.replace(callback_ptr) {
+            unsafe {
+                drop(Box::from_raw(previous_ptr));
+            }
+        }
+        Ok(())
+    }
+
+    /// Unregister and drop the callback set via `set_input_samples_in_output_callback`, if any.
+    pub(crate) fn free_input_samples_in_output_callback(&mut self) {
+        if let Some(callback_ptr) = self.input_samples_callback.take() {
+            let callback_struct = sys::AUInputSamplesInOutputCallbackStruct {
+                inputToOutputCallback: None,
+                userData: ptr::null_mut(),
+            };
+            let id = property::INPUT_SAMPLES_IN_OUTPUT;
+            // We don't want to panic in `drop`, so we'll ignore returned errors.
+            super::set_property_raw(self.instance, id, Scope::Global, 0, Some(&callback_struct))
+                .ok();
+            unsafe {
+                drop(Box::from_raw(callback_ptr));
+            }
+        }
+    }
+}
+
+/// Callback procedure called by the **AudioUnit** after each render.
+unsafe extern "C" fn input_samples_in_output_proc(
+    in_ref_con: *mut c_void,
+    in_output_time_stamp: *const sys::AudioTimeStamp,
+    in_input_sample: f64,
+    in_number_input_samples: f64,
+) {
+    if in_output_time_stamp.is_null() {
+        return;
+    }
+    let callback = in_ref_con as *mut InputSamplesCallback;
+    (*callback)(InputSamplesInOutput {
+        output_time_stamp: *in_output_time_stamp,
+        input_sample: in_input_sample,
+        number_input_samples: in_number_input_samples,
+    });
+}
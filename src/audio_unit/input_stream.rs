@@ -0,0 +1,233 @@
+//! Pulling captured input from an async runtime via a `futures::Stream`.
+//!
+//! The audio thread must never block, so captured buffers are handed over through a bounded queue
+//! that drops buffers rather than waiting for the consumer to catch up, counting each one dropped.
+
+use super::render_callback::{self, data, InputCaptureConfig};
+use super::AudioUnit;
+use crate::error::Error;
+use futures_core::Stream;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+/// State shared between the input callback and the **InputStream**.
+struct Shared {
+    queue: Mutex<VecDeque<Vec<f32>>>,
+    // Empty buffers for the input callback to copy samples into. Together with the queue this
+    // always holds `capacity` buffers, so that the input callback never allocates or frees one.
+    pool: Mutex<Vec<Vec<f32>>>,
+    capacity: usize,
+    // The number of samples each buffer is allocated for.
+    buffer_len: usize,
+    waker: Mutex<Option<Waker>>,
+    // Set once the input callback has been dropped.
+    closed: AtomicBool,
+    dropped_buffers: AtomicUsize,
+}
+
+/// The input callback's side of the **Shared** state, closing the stream once dropped.
+struct Producer {
+    shared: Arc<Shared>,
+}
+
+/// A stream of the buffers captured by an input **AudioUnit**, created via
+/// `AudioUnit::input_stream`.
+///
+/// Each item holds the interleaved `f32` samples delivered to a single call of the input
+/// callback. The stream ends once the input callback is removed from the unit or the unit is
+/// dropped.
+pub struct InputStream {
+    shared: Arc<Shared>,
+}
+
+impl AudioUnit {
+    /// Capture the unit's input into a **Stream** holding at most `capacity` buffers, replacing
+    /// any existing input callback.
+    ///
+    /// The unit's input stream format must use interleaved `f32` samples. The input callback never
+    /// blocks the audio thread: should the queue be full, or momentarily locked by the consumer,
+    /// the captured buffer is dropped and counted in `InputStream::dropped_buffers`.
+    ///
+    /// The buffers are allocated up front for the unit's `max_frames_per_slice`, so the callback
+    /// never allocates on the audio thread either. Each buffer yielded by the stream is replaced
+    /// by a new allocation on the consumer's thread.
+    pub fn input_stream(&mut self, capacity: usize) -> Result<InputStream, Error> {
+        let channels = self.input_stream_format()?.channels as usize;
+        let max_frames = self.max_frames_per_slice()?;
+        let (producer, stream) = channel(capacity, max_frames as usize * channels);
+        let config = InputCaptureConfig {
+            max_frames: Some(max_frames),
+            ..InputCaptureConfig::default()
+        };
+        type Args = render_callback::Args<data::Interleaved<f32>>;
+        self.set_input_callback_with_config(config, move |args: Args| {
+            producer.shared.push(args.data.buffer);
+            Ok(())
+        })?;
+        Ok(stream)
+    }
+}
+
+/// Create the two sides of a stream of at most `capacity` buffers of up to `buffer_len` samples.
+fn channel(capacity: usize, buffer_len: usize) -> (Producer, InputStream) {
+    let pool = (0..capacity)
+        .map(|_| Vec::with_capacity(buffer_len))
+        .collect();
+    let shared = Arc::new(Shared {
+        queue: Mutex::new(VecDeque::with_capacity(capacity)),
+        pool: Mutex::new(pool),
+        capacity,
+        buffer_len,
+        waker: Mutex::new(None),
+        closed: AtomicBool::new(false),
+        dropped_buffers: AtomicUsize::new(0),
+    });
+    let producer = Producer {
+        shared: shared.clone(),
+    };
+    (producer, InputStream { shared })
+}
+
+impl Shared {
+    /// Queue a copy of the given samples without blocking or allocating, dropping them if that
+    /// isn't possible.
+    fn push(&self, samples: &[f32]) {
+        if !self.try_push(samples) {
+            self.dropped_buffers.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        // Should the waker be locked, the consumer is registering it and checks the queue again
+        // afterwards, so the buffer is still picked up.
+        if let Ok(mut waker) = self.waker.try_lock() {
+            if let Some(waker) = waker.take() {
+                waker.wake();
+            }
+        }
+    }
+
+    fn try_push(&self, samples: &[f32]) -> bool {
+        let mut queue = match self.queue.try_lock() {
+            Ok(queue) if queue.len() < self.capacity => queue,
+            _ => return false,
+        };
+        let mut pool = match self.pool.try_lock() {
+            Ok(pool) => pool,
+            Err(_) => return false,
+        };
+        let mut buffer = match pool.pop() {
+            Some(buffer) => buffer,
+            None => return false,
+        };
+        if samples.len() > buffer.capacity() {
+            pool.push(buffer);
+            return false;
+        }
+        buffer.clear();
+        buffer.extend_from_slice(samples);
+        queue.push_back(buffer);
+        true
+    }
+
+    fn pop(&self) -> Option<Vec<f32>> {
+        let samples = {
+            let mut queue = self
+                .queue
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            queue.pop_front()?
+        };
+        // Replace the buffer handed to the consumer, allocating here rather than in the callback.
+        let mut pool = self
+            .pool
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        pool.push(Vec::with_capacity(self.buffer_len));
+        Some(samples)
+    }
+}
+
+impl Drop for Producer {
+    fn drop(&mut self) {
+        // Wake a pending `poll_next`, which would otherwise never learn that the stream ended.
+        let shared = &self.shared;
+        shared.closed.store(true, Ordering::Release);
+        let waker = shared
+            .waker
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .take();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl InputStream {
+    /// The number of buffers dropped because the stream was not polled quickly enough.
+    pub fn dropped_buffers(&self) -> usize {
+        self.shared.dropped_buffers.load(Ordering::Relaxed)
+    }
+}
+
+impl Stream for InputStream {
+    type Item = Vec<f32>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Vec<f32>>> {
+        let shared = &self.shared;
+        if let Some(samples) = shared.pop() {
+            return Poll::Ready(Some(samples));
+        }
+        if shared.closed.load(Ordering::Acquire) {
+            return Poll::Ready(None);
+        }
+        {
+            let mut waker = shared
+                .waker
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            *waker = Some(cx.waker().clone());
+        }
+        // Check again in case a buffer was pushed, or the input callback dropped, while the waker
+        // was being registered.
+        if let Some(samples) = shared.pop() {
+            return Poll::Ready(Some(samples));
+        }
+        if shared.closed.load(Ordering::Acquire) {
+            return Poll::Ready(None);
+        }
+        Poll::Pending
+    }
+}
+
+#[test]
+fn test_input_stream_ends_when_producer_dropped() {
+    use std::task::Wake;
+
+    struct CountWakes(AtomicUsize);
+    impl Wake for CountWakes {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let wakes = Arc::new(CountWakes(AtomicUsize::new(0)));
+    let waker = Waker::from(wakes.clone());
+    let mut cx = Context::from_waker(&waker);
+    let (producer, mut stream) = channel(1, 4);
+
+    producer.shared.push(&[1.0, 2.0]);
+    producer.shared.push(&[3.0]);
+    assert_eq!(stream.dropped_buffers(), 1);
+    let poll = Pin::new(&mut stream).poll_next(&mut cx);
+    assert_eq!(poll, Poll::Ready(Some(vec![1.0, 2.0])));
+    producer.shared.push(&[0.0; 5]);
+    assert_eq!(stream.dropped_buffers(), 2);
+
+    assert_eq!(Pin::new(&mut stream).poll_next(&mut cx), Poll::Pending);
+    drop(producer);
+    assert_eq!(wakes.0.load(Ordering::SeqCst), 1);
+    assert_eq!(Pin::new(&mut stream).poll_next(&mut cx), Poll::Ready(None));
+}
@@ -0,0 +1,145 @@
+//! Instantiating audio components asynchronously, e.g. loading them out-of-process.
+//!
+//! `AudioComponentInstantiate` reports the new instance through an Objective-C block. As the
+//! crate doesn't depend on a blocks runtime binding, the block is laid out by hand according to
+//! the [Block ABI](https://clang.llvm.org/docs/Block-ABI-Apple.html).
+//!
+//! Find the original `AudioComponentInstantiate` documentation [here](https://developer.apple.com/documentation/audiotoolbox/1410490-audiocomponentinstantiate).
+
+use super::component::AudioComponent;
+use super::AudioUnit;
+use crate::error::Error;
+use std::mem;
+use std::os::raw::{c_int, c_uint, c_ulong, c_void};
+use sys;
+
+bitflags! {
+    /// Options for instantiating an audio component via `AudioComponent::instantiate`.
+    ///
+    /// Sandboxed and hardened runtime apps, such as those distributed via the App Store, may be
+    /// unable to load the code of third-party units into their own process. Loading those units
+    /// out-of-process instead runs them in a separate process with its own sandbox, at the cost
+    /// of some extra latency and CPU usage for the communication between the two processes.
+    #[derive(Default)]
+    pub struct InstantiationOptions: u32 {
+        /// Load the unit into a separate process, even if it could be loaded into the host's.
+        ///
+        /// This is how sandboxed hosts may use third-party Audio Unit v2 components.
+        ///
+        /// **Available** in OS X v10.11 and later.
+        const LOAD_OUT_OF_PROCESS = 1;
+        /// Load an Audio Unit v3 extension into the host's process where the extension permits
+        /// it, avoiding the latency of communicating with a separate process, rather than
+        /// loading it out-of-process.
+        ///
+        /// **Available** in macOS v10.13 and later.
+        const LOAD_IN_PROCESS = 2;
+    }
+}
+
+extern "C" {
+    static _NSConcreteStackBlock: [*const c_void; 32];
+    fn AudioComponentInstantiate(
+        in_component: sys::AudioComponent,
+        in_options: c_uint,
+        in_completion_handler: *mut c_void,
+    );
+}
+
+/// The callback given to `AudioComponent::instantiate`.
+type Completion = Box<dyn FnOnce(Result<AudioUnit, Error>) + Send>;
+
+#[repr(C)]
+struct BlockDescriptor {
+    reserved: c_ulong,
+    size: c_ulong,
+}
+
+/// A block of type `void (^)(AudioComponentInstance, OSStatus)` capturing the completion.
+#[repr(C)]
+struct CompletionBlock {
+    isa: *const c_void,
+    flags: c_int,
+    reserved: c_int,
+    invoke: unsafe extern "C" fn(*mut CompletionBlock, sys::AudioUnit, sys::OSStatus),
+    descriptor: *const BlockDescriptor,
+    // The captured `Box<Completion>`. The block has no copy or dispose helpers, so copying it
+    // to the heap copies the pointer as is.
+    completion: *mut Completion,
+    component: sys::AudioComponent,
+}
+
+static COMPLETION_BLOCK_DESCRIPTOR: BlockDescriptor = BlockDescriptor {
+    reserved: 0,
+    size: mem::size_of::<CompletionBlock>() as c_ulong,
+};
+
+impl AudioComponent {
+    /// Create an uninitialized instance of the component asynchronously with the given options,
+    /// calling `f` with the new **AudioUnit** or the error that occurred.
+    ///
+    /// Unlike `AudioUnit::from_component` this supports the components that may only be
+    /// instantiated asynchronously, and allows loading units out-of-process (see
+    /// **InstantiationOptions**). `f` may be called on another thread, and possibly before this
+    /// function returns.
+    pub fn instantiate<F>(&self, options: InstantiationOptions, f: F)
+    where
+        F: FnOnce(Result<AudioUnit, Error>) + Send + 'static,
+    {
+        let completion: Box<Completion> = Box::new(Box::new(f));
+        // Core Audio copies the block to the heap before returning, so it may live on the stack.
+        // The completion is converted back into a `Box` when the block is invoked.
+        let mut block = CompletionBlock {
+            isa: unsafe { _NSConcreteStackBlock.as_ptr() as *const c_void },
+            flags: 0,
+            reserved: 0,
+            invoke: invoke_completion,
+            descriptor: &COMPLETION_BLOCK_DESCRIPTOR,
+            completion: Box::into_raw(completion),
+            component: self.as_raw(),
+        };
+        unsafe {
+            let block_ptr = &mut block as *mut CompletionBlock as *mut c_void;
+            AudioComponentInstantiate(self.as_raw(), options.bits(), block_ptr);
+        }
+    }
+}
+
+/// The function of the completion block, called once with the new instance or an error.
+unsafe extern "C" fn invoke_completion(
+    block: *mut CompletionBlock,
+    instance: sys::AudioUnit,
+    status: sys::OSStatus,
+) {
+    let completion = Box::from_raw((*block).completion);
+    let result = Error::from_os_status(status).and_then(|()| {
+        if instance.is_null() {
+            Err(Error::Unspecified)
+        } else {
+            Ok(AudioUnit::from_instance(instance, (*block).component))
+        }
+    });
+    completion(result);
+}
+
+#[test]
+fn test_invoke_completion_error() {
+    use std::ptr;
+    use std::sync::mpsc;
+
+    let (tx, rx) = mpsc::channel();
+    let completion: Box<Completion> = Box::new(Box::new(move |result: Result<AudioUnit, _>| {
+        tx.send(result.err()).unwrap();
+    }));
+    let mut block = CompletionBlock {
+        isa: ptr::null(),
+        flags: 0,
+        reserved: 0,
+        invoke: invoke_completion,
+        descriptor: &COMPLETION_BLOCK_DESCRIPTOR,
+        completion: Box::into_raw(completion),
+        component: ptr::null_mut(),
+    };
+    unsafe { (block.invoke)(&mut block, ptr::null_mut(), -1500) };
+    assert!(matches!(rx.recv(), Ok(Some(Error::Unspecified))));
+}
@@ -15,24 +15,27 @@ use core_foundation_sys::string::{CFStringGetCString, CFStringGetCStringPtr, CFS
 use sys;
 use sys::pid_t;
 use sys::{
-    kAudioDevicePropertyAvailableNominalSampleRates, kAudioDevicePropertyDeviceIsAlive,
+    kAudioAggregateDevicePropertyMasterSubDevice, kAudioDevicePropertyAvailableNominalSampleRates,
+    kAudioDevicePropertyClockSource, kAudioDevicePropertyClockSourceNameForIDCFString,
+    kAudioDevicePropertyClockSources, kAudioDevicePropertyDeviceIsAlive,
     kAudioDevicePropertyDeviceNameCFString, kAudioDevicePropertyHogMode,
     kAudioDevicePropertyNominalSampleRate, kAudioDevicePropertyScopeOutput,
-    kAudioDevicePropertyStreamConfiguration, kAudioHardwareNoError,
+    kAudioDevicePropertyStreamConfiguration, kAudioDevicePropertyStreams, kAudioHardwareNoError,
     kAudioHardwarePropertyDefaultInputDevice, kAudioHardwarePropertyDefaultOutputDevice,
     kAudioHardwarePropertyDevices, kAudioObjectPropertyElementMaster,
     kAudioObjectPropertyElementWildcard, kAudioObjectPropertyScopeGlobal,
     kAudioObjectPropertyScopeInput, kAudioObjectPropertyScopeOutput, kAudioObjectSystemObject,
     kAudioOutputUnitProperty_CurrentDevice, kAudioOutputUnitProperty_EnableIO,
     kAudioStreamPropertyAvailablePhysicalFormats, kAudioStreamPropertyPhysicalFormat,
-    kCFStringEncodingUTF8, AudioDeviceID, AudioObjectAddPropertyListener,
-    AudioObjectGetPropertyData, AudioObjectGetPropertyDataSize, AudioObjectID,
-    AudioObjectPropertyAddress, AudioObjectPropertyScope, AudioObjectRemovePropertyListener,
-    AudioObjectSetPropertyData, AudioStreamBasicDescription, AudioStreamRangedDescription,
-    AudioValueRange, OSStatus,
+    kAudioStreamPropertyVirtualFormat, kCFStringEncodingUTF8, AudioDeviceID,
+    AudioObjectAddPropertyListener, AudioObjectGetPropertyData, AudioObjectGetPropertyDataSize,
+    AudioObjectHasProperty, AudioObjectID, AudioObjectPropertyAddress, AudioObjectPropertyScope,
+    AudioObjectRemovePropertyListener, AudioObjectSetPropertyData, AudioStreamBasicDescription,
+    AudioStreamID, AudioStreamRangedDescription, AudioValueRange, AudioValueTranslation, OSStatus,
 };
 
 use crate::audio_unit::audio_format::{AudioFormat, LinearPcmFlags};
+use crate::audio_unit::cf;
 use crate::audio_unit::sample_format::SampleFormat;
 use crate::audio_unit::stream_format::StreamFormat;
 use crate::audio_unit::{AudioUnit, Element, IOType, Scope};
@@ -596,6 +599,266 @@ pub fn get_supported_physical_stream_formats(
     Ok(allformats)
 }
 
+/// Get the ids of the streams of a device within the given scope.
+///
+/// Devices such as pro audio interfaces may expose several streams per scope, each with its own
+/// format and channels. Use `Scope::Input` or `Scope::Output` to select the direction, any other
+/// scope returns the streams of both directions.
+pub fn get_device_streams(
+    device_id: AudioDeviceID,
+    scope: Scope,
+) -> Result<Vec<AudioStreamID>, Error> {
+    let dev_scope = match scope {
+        Scope::Input => kAudioObjectPropertyScopeInput,
+        Scope::Output => kAudioObjectPropertyScopeOutput,
+        _ => kAudioObjectPropertyScopeGlobal,
+    };
+    let property_address = AudioObjectPropertyAddress {
+        mSelector: kAudioDevicePropertyStreams,
+        mScope: dev_scope,
+        mElement: kAudioObjectPropertyElementMaster,
+    };
+    get_property_data_array(device_id, &property_address)
+}
+
+/// Get the ranges of nominal sample rates a device supports within the given scope.
+///
+/// A range whose `mMinimum` and `mMaximum` are equal is a single rate.
+pub fn get_available_sample_rates(
+    device_id: AudioDeviceID,
+    scope: Scope,
+) -> Result<Vec<AudioValueRange>, Error> {
+    let dev_scope = match scope {
+        Scope::Input => kAudioObjectPropertyScopeInput,
+        Scope::Output => kAudioObjectPropertyScopeOutput,
+        _ => kAudioObjectPropertyScopeGlobal,
+    };
+    let property_address = AudioObjectPropertyAddress {
+        mSelector: kAudioDevicePropertyAvailableNominalSampleRates,
+        mScope: dev_scope,
+        mElement: kAudioObjectPropertyElementMaster,
+    };
+    get_property_data_array(device_id, &property_address)
+}
+
+/// Get the current (virtual) format of a stream, as returned by `get_device_streams`.
+///
+/// This is the format in which the stream's data is presented to clients of the device.
+pub fn get_stream_format(stream_id: AudioStreamID) -> Result<StreamFormat, Error> {
+    let property_address = AudioObjectPropertyAddress {
+        mSelector: kAudioStreamPropertyVirtualFormat,
+        mScope: kAudioObjectPropertyScopeGlobal,
+        mElement: kAudioObjectPropertyElementMaster,
+    };
+    let asbd = unsafe {
+        let mut asbd: AudioStreamBasicDescription = mem::zeroed();
+        let data_size = mem::size_of::<AudioStreamBasicDescription>() as u32;
+        let status = AudioObjectGetPropertyData(
+            stream_id,
+            &property_address as *const _,
+            0,
+            null(),
+            &data_size as *const _ as *mut _,
+            &mut asbd as *mut _ as *mut _,
+        );
+        Error::from_os_status(status)?;
+        asbd
+    };
+    StreamFormat::from_asbd(asbd)
+}
+
+/// Set the sub-device providing the clock of an aggregate device, given the sub-device's UID.
+///
+/// All other sub-devices of the aggregate are synchronized to this clock master, so it should be
+/// the device with the most stable clock (or the one that others are hardware-synced to) in order
+/// to avoid drift and the resulting glitches.
+pub fn set_aggregate_master_sub_device(
+    aggregate_id: AudioDeviceID,
+    sub_device_uid: &str,
+) -> Result<(), Error> {
+    let property_address = AudioObjectPropertyAddress {
+        mSelector: kAudioAggregateDevicePropertyMasterSubDevice,
+        mScope: kAudioObjectPropertyScopeGlobal,
+        mElement: kAudioObjectPropertyElementMaster,
+    };
+    let uid = cf::cf_string_from_str(sub_device_uid);
+    if uid.is_null() {
+        return Err(Error::Unspecified);
+    }
+    let status = unsafe {
+        let status = AudioObjectSetPropertyData(
+            aggregate_id,
+            &property_address as *const _,
+            0,
+            null(),
+            mem::size_of::<CFStringRef>() as u32,
+            &uid as *const _ as *const _,
+        );
+        cf::release(uid);
+        status
+    };
+    Error::from_os_status(status)
+}
+
+/// Get the UID of the sub-device providing the clock of an aggregate device.
+pub fn get_aggregate_master_sub_device(aggregate_id: AudioDeviceID) -> Result<String, Error> {
+    let property_address = AudioObjectPropertyAddress {
+        mSelector: kAudioAggregateDevicePropertyMasterSubDevice,
+        mScope: kAudioObjectPropertyScopeGlobal,
+        mElement: kAudioObjectPropertyElementMaster,
+    };
+    unsafe {
+        let mut uid: CFStringRef = null();
+        let mut data_size = mem::size_of::<CFStringRef>() as u32;
+        let status = AudioObjectGetPropertyData(
+            aggregate_id,
+            &property_address as *const _,
+            0,
+            null(),
+            &mut data_size as *mut _,
+            &mut uid as *mut _ as *mut _,
+        );
+        Error::from_os_status(status)?;
+        // The caller is responsible for releasing the returned string.
+        let result = cf::string_from_cf_string(uid).ok_or(Error::Unspecified);
+        cf::release(uid);
+        result
+    }
+}
+
+/// Get the clock sources of a device (e.g. internal, word clock or ADAT) as pairs of each source's
+/// ID and name.
+///
+/// Returns an empty list for devices that do not have selectable clock sources.
+pub fn get_clock_sources(device_id: AudioDeviceID) -> Result<Vec<(u32, String)>, Error> {
+    let property_address = AudioObjectPropertyAddress {
+        mSelector: kAudioDevicePropertyClockSources,
+        mScope: kAudioObjectPropertyScopeGlobal,
+        mElement: kAudioObjectPropertyElementMaster,
+    };
+    let has_clock_sources = unsafe { AudioObjectHasProperty(device_id, &property_address) } != 0;
+    if !has_clock_sources {
+        return Ok(Vec::new());
+    }
+    let source_ids: Vec<u32> = get_property_data_array(device_id, &property_address)?;
+    source_ids
+        .into_iter()
+        .map(|source_id| Ok((source_id, get_clock_source_name(device_id, source_id)?)))
+        .collect()
+}
+
+/// Get the ID of the current clock source of a device.
+pub fn get_clock_source(device_id: AudioDeviceID) -> Result<u32, Error> {
+    let property_address = AudioObjectPropertyAddress {
+        mSelector: kAudioDevicePropertyClockSource,
+        mScope: kAudioObjectPropertyScopeGlobal,
+        mElement: kAudioObjectPropertyElementMaster,
+    };
+    let mut source_id = 0u32;
+    let mut data_size = mem::size_of::<u32>() as u32;
+    let status = unsafe {
+        AudioObjectGetPropertyData(
+            device_id,
+            &property_address as *const _,
+            0,
+            null(),
+            &mut data_size as *mut _,
+            &mut source_id as *mut _ as *mut _,
+        )
+    };
+    Error::from_os_status(status)?;
+    Ok(source_id)
+}
+
+/// Set the clock source of a device, given one of the IDs returned by `get_clock_sources`.
+///
+/// Devices synced to an external clock should have that clock selected here, otherwise their
+/// internal clock will drift relative to the rest of the setup.
+pub fn set_clock_source(device_id: AudioDeviceID, source_id: u32) -> Result<(), Error> {
+    let property_address = AudioObjectPropertyAddress {
+        mSelector: kAudioDevicePropertyClockSource,
+        mScope: kAudioObjectPropertyScopeGlobal,
+        mElement: kAudioObjectPropertyElementMaster,
+    };
+    let status = unsafe {
+        AudioObjectSetPropertyData(
+            device_id,
+            &property_address as *const _,
+            0,
+            null(),
+            mem::size_of::<u32>() as u32,
+            &source_id as *const _ as *const _,
+        )
+    };
+    Error::from_os_status(status)
+}
+
+/// Get the name of one of a device's clock sources.
+fn get_clock_source_name(device_id: AudioDeviceID, source_id: u32) -> Result<String, Error> {
+    let property_address = AudioObjectPropertyAddress {
+        mSelector: kAudioDevicePropertyClockSourceNameForIDCFString,
+        mScope: kAudioObjectPropertyScopeGlobal,
+        mElement: kAudioObjectPropertyElementMaster,
+    };
+    unsafe {
+        let mut source_id = source_id;
+        let mut name: CFStringRef = null();
+        let mut translation = AudioValueTranslation {
+            mInputData: &mut source_id as *mut _ as *mut _,
+            mInputDataSize: mem::size_of::<u32>() as u32,
+            mOutputData: &mut name as *mut _ as *mut _,
+            mOutputDataSize: mem::size_of::<CFStringRef>() as u32,
+        };
+        let mut data_size = mem::size_of::<AudioValueTranslation>() as u32;
+        let status = AudioObjectGetPropertyData(
+            device_id,
+            &property_address as *const _,
+            0,
+            null(),
+            &mut data_size as *mut _,
+            &mut translation as *mut _ as *mut _,
+        );
+        Error::from_os_status(status)?;
+        // The caller is responsible for releasing the returned string.
+        let result = cf::string_from_cf_string(name).ok_or(Error::Unspecified);
+        cf::release(name);
+        result
+    }
+}
+
+/// Get the value of a variable-length property of an audio object as a `Vec` of `T`.
+fn get_property_data_array<T>(
+    object_id: AudioObjectID,
+    property_address: &AudioObjectPropertyAddress,
+) -> Result<Vec<T>, Error> {
+    unsafe {
+        let mut data_size = 0u32;
+        let status = AudioObjectGetPropertyDataSize(
+            object_id,
+            property_address as *const _,
+            0,
+            null(),
+            &mut data_size as *mut _,
+        );
+        Error::from_os_status(status)?;
+        let len = data_size as usize / mem::size_of::<T>();
+        let mut values: Vec<T> = Vec::with_capacity(len);
+        // Only offer the space allocated, in case the size isn't a multiple of that of `T`.
+        data_size = (len * mem::size_of::<T>()) as u32;
+        let status = AudioObjectGetPropertyData(
+            object_id,
+            property_address as *const _,
+            0,
+            null(),
+            &mut data_size as *mut _,
+            values.as_mut_ptr() as *mut _,
+        );
+        Error::from_os_status(status)?;
+        values.set_len(usize::min(len, data_size as usize / mem::size_of::<T>()));
+        Ok(values)
+    }
+}
+
 /// Changing the sample rate is an asynchronous process.
 /// A RateListener can be used to get notified when the rate is changed.
 pub struct RateListener {
@@ -0,0 +1,135 @@
+//! Mapping MIDI messages to **AudioUnit** parameters, e.g. for implementing "MIDI learn".
+//!
+//! Find the original `AUParameterMIDIMapping` documentation [here](https://developer.apple.com/documentation/audiotoolbox/auparametermidimapping).
+
+use super::{AudioUnit, ParameterId, Scope};
+use crate::error::Error;
+use sys;
+
+bitflags! {
+    /// Flags describing how MIDI messages are applied to a mapped parameter.
+    ///
+    /// Original documentation [here](https://developer.apple.com/documentation/audiotoolbox/auparametermidimappingflags).
+    pub struct MidiMappingFlags: u32 {
+        /// Messages on any MIDI channel match the mapping.
+        const ANY_CHANNEL = 1 << 0;
+        /// Messages for any note match the mapping.
+        const ANY_NOTE = 1 << 1;
+        /// MIDI values are scaled to the `sub_range_min..=sub_range_max` range of the parameter.
+        const SUB_RANGE = 1 << 2;
+        /// The parameter is toggled by each matching message.
+        const TOGGLE = 1 << 3;
+        /// The control is bipolar.
+        const BIPOLAR = 1 << 4;
+        /// The bipolar control is in its "on" state.
+        const BIPOLAR_ON = 1 << 5;
+    }
+}
+
+/// A mapping from a MIDI message to a parameter of an **AudioUnit**.
+///
+/// A rust representation of the `AUParameterMIDIMapping` type.
+#[derive(Copy, Clone, Debug)]
+pub struct MidiMapping {
+    /// The scope of the mapped parameter.
+    pub scope: Scope,
+    /// The element of the mapped parameter.
+    pub element: u32,
+    /// The identifier of the mapped parameter.
+    pub parameter_id: ParameterId,
+    /// How MIDI messages are applied to the parameter.
+    pub flags: MidiMappingFlags,
+    /// The minimum parameter value if `MidiMappingFlags::SUB_RANGE` is set.
+    pub sub_range_min: f32,
+    /// The maximum parameter value if `MidiMappingFlags::SUB_RANGE` is set.
+    pub sub_range_max: f32,
+    /// The MIDI status byte of the message, e.g. `0xB0` for a control change on channel 1.
+    pub status: u8,
+    /// The first MIDI data byte of the message, e.g. the controller number.
+    pub data1: u8,
+}
+
+impl MidiMapping {
+    fn from_sys(mapping: &sys::AUParameterMIDIMapping) -> Self {
+        MidiMapping {
+            // Mappings are only ever made within the known scopes.
+            scope: Scope::from_u32(mapping.mScope).unwrap_or(Scope::Global),
+            element: mapping.mElement,
+            parameter_id: mapping.mParameterID,
+            flags: MidiMappingFlags::from_bits_truncate(mapping.mFlags),
+            sub_range_min: mapping.mSubRangeMin,
+            sub_range_max: mapping.mSubRangeMax,
+            status: mapping.mStatus,
+            data1: mapping.mData1,
+        }
+    }
+
+    fn to_sys(self) -> sys::AUParameterMIDIMapping {
+        sys::AUParameterMIDIMapping {
+            mScope: self.scope as u32,
+            mElement: self.element,
+            mParameterID: self.parameter_id,
+            mFlags: self.flags.bits(),
+            mSubRangeMin: self.sub_range_min,
+            mSubRangeMax: self.sub_range_max,
+            mStatus: self.status,
+            mData1: self.data1,
+            reserved1: 0,
+            reserved2: 0,
+            reserved3: 0,
+        }
+    }
+}
+
+impl AudioUnit {
+    /// All MIDI mappings of the **AudioUnit**'s parameters.
+    pub fn parameter_midi_mappings(&self) -> Result<Vec<MidiMapping>, Error> {
+        let id = sys::kAudioUnitProperty_AllParameterMIDIMappings;
+        let mappings: Vec<sys::AUParameterMIDIMapping> =
+            super::get_property_array(self.instance, id, Scope::Global, 0)?;
+        Ok(mappings.iter().map(MidiMapping::from_sys).collect())
+    }
+
+    /// Replace all MIDI mappings of the **AudioUnit**'s parameters.
+    pub fn set_parameter_midi_mappings(&mut self, mappings: &[MidiMapping]) -> Result<(), Error> {
+        let id = sys::kAudioUnitProperty_AllParameterMIDIMappings;
+        let mappings: Vec<_> = mappings.iter().map(|m| m.to_sys()).collect();
+        super::set_property_array(self.instance, id, Scope::Global, 0, &mappings)
+    }
+
+    /// Add a MIDI mapping for one of the **AudioUnit**'s parameters.
+    pub fn add_parameter_midi_mapping(&mut self, mapping: MidiMapping) -> Result<(), Error> {
+        let id = sys::kAudioUnitProperty_AddParameterMIDIMapping;
+        let mapping = mapping.to_sys();
+        super::set_property_raw(self.instance, id, Scope::Global, 0, Some(&mapping))
+    }
+
+    /// Remove a MIDI mapping previously added to the **AudioUnit**.
+    pub fn remove_parameter_midi_mapping(&mut self, mapping: MidiMapping) -> Result<(), Error> {
+        let id = sys::kAudioUnitProperty_RemoveParameterMIDIMapping;
+        let mapping = mapping.to_sys();
+        super::set_property_raw(self.instance, id, Scope::Global, 0, Some(&mapping))
+    }
+
+    /// Begin "MIDI learn" for a parameter.
+    ///
+    /// The next MIDI message received by the **AudioUnit** is mapped to the parameter described by
+    /// `mapping`, whose `status` and `data1` fields are ignored. Once the mapping has been made it
+    /// appears in `parameter_midi_mappings`. Pass `None` to cancel a pending hot mapping.
+    pub fn set_parameter_hot_midi_mapping(
+        &mut self,
+        mapping: Option<MidiMapping>,
+    ) -> Result<(), Error> {
+        let id = sys::kAudioUnitProperty_HotMapParameterMIDIMapping;
+        let mapping = mapping.map(MidiMapping::to_sys);
+        super::set_property_raw(self.instance, id, Scope::Global, 0, mapping.as_ref())
+    }
+
+    /// The pending hot mapping set via `set_parameter_hot_midi_mapping`, if any.
+    pub fn parameter_hot_midi_mapping(&self) -> Result<Option<MidiMapping>, Error> {
+        let id = sys::kAudioUnitProperty_HotMapParameterMIDIMapping;
+        let mappings: Vec<sys::AUParameterMIDIMapping> =
+            super::get_property_array(self.instance, id, Scope::Global, 0)?;
+        Ok(mappings.first().map(MidiMapping::from_sys))
+    }
+}
@@ -0,0 +1,129 @@
+//! Inspecting the MIDI capabilities of an **AudioUnit** and receiving the MIDI it produces.
+//!
+//! Instruments and MIDI effects accept MIDI input, while some units such as arpeggiators also
+//! produce MIDI output on one or more named outputs.
+
+use super::{cf, AudioUnit, Element, MusicDeviceType, Scope, Type};
+use crate::error::{AudioUnitError, Error};
+#[cfg(feature = "core_midi")]
+use std::os::raw::c_void;
+#[cfg(feature = "core_midi")]
+use std::ptr;
+use sys;
+
+/// A boxed MIDI output callback.
+#[cfg(feature = "core_midi")]
+pub(crate) type MidiOutputCallback = Box<dyn FnMut(&sys::MIDIPacketList) + Send>;
+
+impl AudioUnit {
+    /// Whether or not the unit accepts MIDI input.
+    ///
+    /// This is the case for instruments (**MusicDevice**s), MIDI controlled effects
+    /// (**MusicEffect**s) and **MidiProcessor**s.
+    pub fn accepts_midi(&self) -> Result<bool, Error> {
+        let component_type = self.component_description()?.componentType;
+        // Only the type of the instrument is compared, so any subtype will do.
+        let instrument = Type::MusicDevice(MusicDeviceType::DLSSynth);
+        let midi_types = [
+            instrument.as_u32(),
+            Type::MusicEffect.as_u32(),
+            Type::MidiProcessor.as_u32(),
+        ];
+        Ok(midi_types.contains(&component_type))
+    }
+
+    /// The names of the unit's MIDI outputs, in the order of their output numbers.
+    ///
+    /// Returns an empty list for units that do not produce MIDI.
+    pub fn midi_output_names(&self) -> Result<Vec<String>, Error> {
+        let id = sys::kAudioUnitProperty_MIDIOutputCallbackInfo;
+        let names: sys::CFArrayRef = match self.get_property(id, Scope::Global, Element::Output) {
+            Ok(names) => names,
+            Err(Error::AudioUnit(AudioUnitError::InvalidProperty)) => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        };
+        // The caller is responsible for releasing the returned array.
+        unsafe {
+            let result = cf::strings_from_cf_array(names as _);
+            cf::release(names);
+            Ok(result)
+        }
+    }
+}
+
+#[cfg(feature = "core_midi")]
+impl AudioUnit {
+    /// Register a callback that is called with the MIDI produced by the unit, e.g. the notes
+    /// generated by an arpeggiator.
+    ///
+    /// The callback is called on the render thread during each render in which the unit produces
+    /// MIDI, so it must be real-time safe. Hosts recording or routing the MIDI elsewhere should
+    /// copy the packets into a lock-free queue for another thread to handle.
+    ///
+    /// The callback replaces any previously registered callback and remains registered until the
+    /// **AudioUnit** is dropped.
+    pub fn set_midi_output_callback<F>(&mut self, f: F) -> Result<(), Error>
+    where
+        F: FnMut(&sys::MIDIPacketList) + Send + 'static,
+    {
+        let callback: Box<MidiOutputCallback> = Box::new(Box::new(f));
+
+        // Relinquish ownership of the callback so that it can be passed to Core Audio as the user
+        // data. The pointer is converted back into a `Box` when the callback is freed.
+        let callback_ptr = Box::into_raw(callback);
+        let callback_struct = sys::AUMIDIOutputCallbackStruct {
+            midiOutputCallback: Some(midi_output_proc),
+            userData: callback_ptr as *mut c_void,
+        };
+        let id = sys::kAudioUnitProperty_MIDIOutputCallback;
+        let result =
+            super::set_property_raw(self.instance, id, Scope::Global, 0, Some(&callback_struct));
+        if let Err(err) = result {
+            unsafe {
+                drop(Box::from_raw(callback_ptr));
+            }
+            return Err(err);
+        }
+
+        // The previous callback has now been replaced, so it's safe to drop it.
+        if let Some(previous_ptr) = self.midi_output_callback.replace(callback_ptr) {
+            unsafe {
+                drop(Box::from_raw(previous_ptr));
+            }
+        }
+        Ok(())
+    }
+
+    /// Unregister and drop the callback set via `set_midi_output_callback`, if any.
+    pub(crate) fn free_midi_output_callback(&mut self) {
+        if let Some(callback_ptr) = self.midi_output_callback.take() {
+            let callback_struct = sys::AUMIDIOutputCallbackStruct {
+                midiOutputCallback: None,
+                userData: ptr::null_mut(),
+            };
+            let id = sys::kAudioUnitProperty_MIDIOutputCallback;
+            // We don't want to panic in `drop`, so we'll ignore returned errors.
+            super::set_property_raw(self.instance, id, Scope::Global, 0, Some(&callback_struct))
+                .ok();
+            unsafe {
+                drop(Box::from_raw(callback_ptr));
+            }
+        }
+    }
+}
+
+/// Callback procedure called by the **AudioUnit** with the MIDI it produces.
+#[cfg(feature = "core_midi")]
+unsafe extern "C" fn midi_output_proc(
+    user_data: *mut c_void,
+    _time_stamp: *const sys::AudioTimeStamp,
+    _midi_out_num: u32,
+    packet_list: *const sys::MIDIPacketList,
+) -> sys::OSStatus {
+    if packet_list.is_null() {
+        return 0;
+    }
+    let callback = user_data as *mut MidiOutputCallback;
+    (*callback)(&*packet_list);
+    0
+}
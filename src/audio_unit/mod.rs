@@ -8,7 +8,6 @@
 //!
 //! - MIDISynth            = 1836284270,
 //! - RoundTripAAC         = 1918984547,
-//! - SpatialMixer         = 862217581,
 //! - SphericalHeadPanner  = 1936746610,
 //! - VectorPanner         = 1986158963,
 //! - SoundFieldPanner     = 1634558569,
@@ -18,7 +17,9 @@
 //! If you can find documentation on these, please feel free to submit an issue or PR with the
 //! fixes!
 
-use crate::error::Error;
+use crate::error::{AudioUnitError, Error};
+use crate::four_char_code::FourCharCode;
+use std::fmt;
 use std::mem;
 use std::os::raw::{c_uint, c_void};
 use std::ptr;
@@ -26,20 +27,106 @@ use std::ptr;
 use sys;
 
 pub use self::audio_format::AudioFormat;
-pub use self::sample_format::{Sample, SampleFormat};
+pub use self::automation::ParameterEvent;
+pub use self::buffer_list::BufferList;
+pub use self::channel_map::ChannelMap;
+pub use self::channels::ChannelConfig;
+#[cfg(target_os = "macos")]
+pub use self::cocoa_ui::CocoaViewInfo;
+pub use self::component::{AudioComponent, ComponentInfo, ComponentVersion};
+pub use self::dynamics_processor::DynamicsProcessor;
+#[cfg(feature = "audio_toolbox")]
+pub use self::file_player::FilePlayer;
+pub use self::filter::{Bandpass, Highpass, Lowpass};
+pub use self::host_callbacks::HostMusicalContext;
+pub use self::input_samples::InputSamplesInOutput;
+#[cfg(feature = "futures")]
+pub use self::input_stream::InputStream;
+pub use self::instantiation::InstantiationOptions;
+pub use self::parameter::{AudioUnitParameter, ParameterId, ParameterInfo, Parameters};
+pub use self::parameter_scheduler::ParameterScheduler;
+pub use self::peak_limiter::PeakLimiter;
+pub use self::midi_mapping::{MidiMapping, MidiMappingFlags};
+pub use self::multichannel_mixer::MultiChannelMixer;
+#[cfg(feature = "assert_no_alloc")]
+pub use self::no_alloc::AssertNoAlloc;
+pub use self::preset::{FactoryPreset, PresetData};
+pub use self::property::PropertyId;
+pub use self::property_listener::PropertyListenerId;
+pub use self::render_notify::{RenderNotifyArgs, RenderNotifyId};
+pub use self::render_quality::RenderQuality;
+pub use self::reverb::Reverb;
+pub use self::sample_format::{Fixed8_24, Sample, SampleFormat};
+pub use self::sine_generator::SineGenerator;
+pub use self::spatial_mixer::{SpatialMixer, SpatializationAlgorithm};
+pub use self::src_quality::SrcQuality;
 pub use self::stream_format::StreamFormat;
 pub use self::types::{
     EffectType, FormatConverterType, GeneratorType, IOType, MixerType, MusicDeviceType, Type,
+    UnitCategory,
 };
+#[cfg(target_os = "macos")]
+pub use self::workgroup::{WorkGroupMembership, WorkGroupToken};
+
+// Declared first so that `unit_wrapper!` is in scope for the wrapper modules below.
+#[macro_use]
+mod unit_wrapper;
 
+#[cfg(target_os = "macos")]
+mod cocoa_ui;
+#[cfg(target_os = "macos")]
+mod device_lost;
+#[cfg(target_os = "macos")]
+pub mod duplex;
 #[cfg(target_os = "macos")]
 pub mod macos_helpers;
+#[cfg(target_os = "macos")]
+pub mod recorder;
+#[cfg(target_os = "macos")]
+mod workgroup;
 
 pub mod audio_format;
+mod automation;
+mod buffer_allocation;
+pub mod buffer_list;
+mod bypass;
+mod cf;
+mod channel_map;
+mod channels;
+pub mod component;
+pub mod dynamics_processor;
+#[cfg(feature = "audio_toolbox")]
+pub mod file_player;
+pub mod filter;
+mod host_callbacks;
+pub mod input_samples;
+#[cfg(feature = "futures")]
+mod input_stream;
+mod instantiation;
+pub mod midi_mapping;
+mod midi_output;
+pub mod multichannel_mixer;
+mod no_alloc;
+mod offline;
+pub mod parameter;
+mod parameter_scheduler;
+pub mod peak_limiter;
+pub mod preset;
+pub mod property;
+mod property_listener;
 pub mod render_callback;
+mod render_notify;
+mod render_quality;
+pub mod reverb;
+mod ring_buffer;
 pub mod sample_format;
+pub mod sine_generator;
+pub mod spatial_mixer;
+mod src_quality;
 pub mod stream_format;
 pub mod types;
+pub mod volume;
+pub mod wav;
 
 /// The input and output **Scope**s.
 ///
@@ -57,27 +144,96 @@ pub enum Scope {
     LayerItem = 7,
 }
 
+impl Scope {
+    /// Create a **Scope** from its associated `u32`.
+    pub fn from_u32(u: u32) -> Option<Scope> {
+        let scope = match u {
+            0 => Scope::Global,
+            1 => Scope::Input,
+            2 => Scope::Output,
+            3 => Scope::Group,
+            4 => Scope::Part,
+            5 => Scope::Note,
+            6 => Scope::Layer,
+            7 => Scope::LayerItem,
+            _ => return None,
+        };
+        Some(scope)
+    }
+}
+
 /// Represents the **Input** and **Output** **Element**s.
 ///
 /// These are used when specifying which **Element** we're setting the properties of.
+///
+/// The property methods accept any `Into<u32>`, so that other buses, e.g. input bus `3` of a
+/// mixer, may be addressed by their raw index.
 #[derive(Copy, Clone, Debug)]
 pub enum Element {
     Output = 0,
     Input = 1,
 }
 
+impl From<Element> for u32 {
+    fn from(element: Element) -> u32 {
+        element as u32
+    }
+}
+
+/// The number of input and output buses (elements) of an **AudioUnit**.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BusCounts {
+    /// The number of elements in the `Input` scope.
+    pub inputs: u32,
+    /// The number of elements in the `Output` scope.
+    pub outputs: u32,
+}
+
+/// The size and writability of an **AudioUnit** property, as returned by
+/// `AudioUnit::get_property_info`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PropertyInfo {
+    /// The size of the property's current value in bytes.
+    pub size: u32,
+    /// Whether or not the property may currently be set.
+    pub writable: bool,
+}
+
 /// A rust representation of the sys::AudioUnit, including a pointer to the current rendering callback.
 ///
 /// Find the original Audio Unit Programming Guide [here](https://developer.apple.com/library/mac/documentation/MusicAudio/Conceptual/AudioUnitProgrammingGuide/TheAudioUnit/TheAudioUnit.html).
 pub struct AudioUnit {
     instance: sys::AudioUnit,
+    // The component from which the instance was created.
+    component: sys::AudioComponent,
     maybe_render_callback: Option<*mut render_callback::InputProcFnWrapper>,
     maybe_input_callback: Option<InputCallback>,
+    property_listeners: Vec<*mut property_listener::PropertyListener>,
+    property_changes: Option<property_listener::PropertyChanges>,
+    parameter_cache: Option<parameter::SharedParameterCache>,
+    buffer_allocation: buffer_allocation::BufferAllocation,
+    render_timestamp: Option<Box<render_notify::RenderTimestamp>>,
+    render_notifies: Vec<*mut render_notify::RenderNotify>,
+    parameter_queue: Option<std::sync::Arc<parameter_scheduler::ParameterQueue>>,
+    input_samples_callback: Option<*mut input_samples::InputSamplesCallback>,
+    host_callback: Option<*mut host_callbacks::HostCallback>,
+    #[cfg(feature = "core_midi")]
+    midi_output_callback: Option<*mut midi_output::MidiOutputCallback>,
+    #[cfg(target_os = "macos")]
+    device_lost_listener: Option<*mut device_lost::DeviceLostListener>,
+    // Set by the device lost listener once it has stopped the instance, behind the back of
+    // `running`.
+    #[cfg(target_os = "macos")]
+    stopped_by_device_loss: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    // Whether or not the instance is currently initialized.
+    initialized: bool,
+    // Whether or not the instance has been started and not yet stopped.
+    running: bool,
 }
 
 struct InputCallback {
-    // The audio buffer list to which input data is rendered.
-    buffer_list: *mut sys::AudioBufferList,
+    // The buffer to which input data is rendered.
+    buffer: *mut render_callback::InputBuffer,
     callback: *mut render_callback::InputProcFnWrapper,
 }
 
@@ -114,14 +270,38 @@ impl AudioUnit {
         AudioUnit::new_with_flags(ty, 0, 0)
     }
 
+    /// The same as [**AudioUnit::new**](./struct.AudioUnit#method.new) but without initializing
+    /// the unit.
+    ///
+    /// Properties such as the stream format and the maximum frames per slice may only be set
+    /// while the unit is uninitialized, so this allows configuring the unit before calling
+    /// [**AudioUnit::initialize**](./struct.AudioUnit#method.initialize), rather than
+    /// uninitializing it first.
+    pub fn new_uninitialized<T>(ty: T) -> Result<AudioUnit, Error>
+    where
+        T: Into<Type>,
+    {
+        let desc = AudioUnit::default_description(ty.into(), 0, 0)?;
+        AudioUnit::from_raw_description(&desc, false)
+    }
+
     /// The same as [**AudioUnit::new**](./struct.AudioUnit#method.new) but with the given
     /// component flags and mask.
     pub fn new_with_flags<T>(ty: T, flags: u32, mask: u32) -> Result<AudioUnit, Error>
     where
         T: Into<Type>,
     {
+        let desc = AudioUnit::default_description(ty.into(), flags, mask)?;
+        AudioUnit::from_raw_description(&desc, true)
+    }
+
+    /// Describe the Apple unit of the given type with the given component flags and mask.
+    fn default_description(
+        au_type: Type,
+        flags: u32,
+        mask: u32,
+    ) -> Result<sys::AudioComponentDescription, Error> {
         const MANUFACTURER_IDENTIFIER: u32 = sys::kAudioUnitManufacturer_Apple;
-        let au_type: Type = ty.into();
         let sub_type_u32 = match au_type.as_subtype_u32() {
             Some(u) => u,
             None => return Err(Error::NoKnownSubtype),
@@ -135,7 +315,38 @@ impl AudioUnit {
             componentFlags: flags,
             componentFlagsMask: mask,
         };
+        Ok(desc)
+    }
 
+    /// Construct a new AudioUnit from the first component matching the given type, subtype and
+    /// manufacturer four-character codes, e.g. a third-party effect.
+    ///
+    /// Unlike [**AudioUnit::new**](./struct.AudioUnit#method.new) this is not limited to the
+    /// `kAudioUnitManufacturer_Apple` units, nor to the types and subtypes known to this crate.
+    /// The codes of installed components may be listed via
+    /// [**component::all_components**](./component/fn.all_components.html), and the codes of
+    /// other components parsed from their four characters, e.g. `"Xmpl".parse::<FourCharCode>()`.
+    pub fn from_description(
+        component_type: FourCharCode,
+        sub_type: FourCharCode,
+        manufacturer: FourCharCode,
+    ) -> Result<AudioUnit, Error> {
+        let desc = sys::AudioComponentDescription {
+            componentType: component_type.into(),
+            componentSubType: sub_type.into(),
+            componentManufacturer: manufacturer.into(),
+            componentFlags: 0,
+            componentFlagsMask: 0,
+        };
+        AudioUnit::from_raw_description(&desc, true)
+    }
+
+    /// Construct a new AudioUnit from the first component matching the given description,
+    /// initializing it if `initialize` is `true`.
+    fn from_raw_description(
+        desc: &sys::AudioComponentDescription,
+        initialize: bool,
+    ) -> Result<AudioUnit, Error> {
         unsafe {
             // Find the default audio unit for the description.
             //
@@ -145,26 +356,70 @@ impl AudioUnit {
             // find the first system audio unit matching the description, using a system-defined
             // ordering. If you instead pass a previously found audio unit reference in this
             // parameter, the function locates the next audio unit matching the description.
-            let component = sys::AudioComponentFindNext(ptr::null_mut(), &desc as *const _);
+            let component = sys::AudioComponentFindNext(ptr::null_mut(), desc as *const _);
             if component.is_null() {
                 return Err(Error::NoMatchingDefaultAudioUnitFound);
             }
+            AudioUnit::new_instance(component, initialize)
+        }
+    }
 
-            // Create an instance of the default audio unit using the component.
-            let mut instance_uninit = mem::MaybeUninit::<sys::AudioUnit>::uninit();
-            try_os_status!(sys::AudioComponentInstanceNew(
-                component,
-                instance_uninit.as_mut_ptr() as *mut sys::AudioUnit
-            ));
-            let instance: sys::AudioUnit = instance_uninit.assume_init();
-
-            // Initialise the audio unit!
-            try_os_status!(sys::AudioUnitInitialize(instance));
-            Ok(AudioUnit {
-                instance,
-                maybe_render_callback: None,
-                maybe_input_callback: None,
-            })
+    /// Construct a new AudioUnit from the given component, e.g. one from any manufacturer found
+    /// via [**component::all_components**](./component/fn.all_components.html).
+    ///
+    /// The unit is initialized, as with [**AudioUnit::new**](./struct.AudioUnit#method.new).
+    /// Components that require asynchronous instantiation, e.g. out-of-process units, may be
+    /// instantiated via `AudioComponent::instantiate` instead.
+    pub fn from_component(component: &AudioComponent) -> Result<AudioUnit, Error> {
+        unsafe { AudioUnit::new_instance(component.as_raw(), true) }
+    }
+
+    /// Create an instance of the given non-null component, initializing it if `initialize` is
+    /// `true`.
+    unsafe fn new_instance(
+        component: sys::AudioComponent,
+        initialize: bool,
+    ) -> Result<AudioUnit, Error> {
+        // Create an instance of the audio unit using the component.
+        let mut instance_uninit = mem::MaybeUninit::<sys::AudioUnit>::uninit();
+        try_os_status!(sys::AudioComponentInstanceNew(
+            component,
+            instance_uninit.as_mut_ptr() as *mut sys::AudioUnit
+        ));
+        let instance: sys::AudioUnit = instance_uninit.assume_init();
+        let mut audio_unit = AudioUnit::from_instance(instance, component);
+
+        // Initialise the audio unit!
+        if initialize {
+            audio_unit.initialize()?;
+        }
+        Ok(audio_unit)
+    }
+
+    /// Take ownership of the given uninitialized, non-null instance of `component`.
+    unsafe fn from_instance(instance: sys::AudioUnit, component: sys::AudioComponent) -> AudioUnit {
+        AudioUnit {
+            instance,
+            component,
+            maybe_render_callback: None,
+            maybe_input_callback: None,
+            property_listeners: Vec::new(),
+            property_changes: None,
+            parameter_cache: None,
+            buffer_allocation: Default::default(),
+            render_timestamp: None,
+            render_notifies: Vec::new(),
+            parameter_queue: None,
+            input_samples_callback: None,
+            host_callback: None,
+            #[cfg(feature = "core_midi")]
+            midi_output_callback: None,
+            #[cfg(target_os = "macos")]
+            device_lost_listener: None,
+            #[cfg(target_os = "macos")]
+            stopped_by_device_loss: Default::default(),
+            initialized: false,
+            running: false,
         }
     }
 
@@ -175,10 +430,15 @@ impl AudioUnit {
     ///
     /// Usually, the state of an audio unit (such as its I/O formats and memory allocations)
     /// cannot be changed while an audio unit is initialized.
+    ///
+    /// Returns `Error::BufferNotProvided` if buffer allocation has been disabled for an input
+    /// element without a buffer having been provided (see `set_should_allocate_buffer`).
     pub fn initialize(&mut self) -> Result<(), Error> {
+        self.check_buffer_provision()?;
         unsafe {
             try_os_status!(sys::AudioUnitInitialize(self.instance));
         }
+        self.initialized = true;
         Ok(())
     }
 
@@ -192,9 +452,104 @@ impl AudioUnit {
         unsafe {
             try_os_status!(sys::AudioUnitUninitialize(self.instance));
         }
+        self.initialized = false;
+        Ok(())
+    }
+
+    /// Uninitializes the **AudioUnit** (if it is initialized) and initializes it again, restarting
+    /// it afterwards if it was running.
+    ///
+    /// This is the single call to make after changing processing characteristics such as the
+    /// stream format or sample rate, rather than stopping, uninitializing, initializing and
+    /// starting the unit by hand.
+    ///
+    /// Note that any properties changed since the unit was last initialized only take effect once
+    /// it has been reinitialized.
+    pub fn reinitialize(&mut self) -> Result<(), Error> {
+        let was_running = self.is_running();
+        if was_running {
+            self.stop()?;
+        }
+        if self.initialized {
+            self.uninitialize()?;
+        }
+        self.initialize()?;
+        if was_running {
+            self.start()?;
+        }
+        Ok(())
+    }
+
+    /// Reset the processing state of the given scope and element of the **AudioUnit**, such as
+    /// the delay lines of a delay or the tail of a reverb, without changing its properties or
+    /// parameters.
+    ///
+    /// Hosts should reset the `Global` scope (element `0`) when seeking or restarting playback, so
+    /// that audio from before the discontinuity doesn't spill over into what follows.
+    pub fn reset(&mut self, scope: Scope, element: u32) -> Result<(), Error> {
+        unsafe {
+            try_os_status!(sys::AudioUnitReset(self.instance, scope as c_uint, element));
+        }
         Ok(())
     }
 
+    /// Uninitialize the **AudioUnit** (stopping it first if it is running) until the returned
+    /// guard is dropped.
+    ///
+    /// When the guard is dropped the unit is initialized again and restarted if it was running,
+    /// even on early return via `?` or while unwinding from a panic. The unit may be reconfigured
+    /// via the guard in the meantime, as it dereferences to the **AudioUnit**. The guard mutably
+    /// borrows the unit, so it cannot be used through any other path while the guard is alive.
+    ///
+    /// Errors that occur while reinitializing within `drop` are ignored. Call
+    /// `UninitGuard::finish` instead to handle them.
+    pub fn uninitialized_guard(&mut self) -> Result<UninitGuard<'_>, Error> {
+        let was_running = self.is_running();
+        let was_initialized = self.initialized;
+        if was_running {
+            self.stop()?;
+        }
+        if was_initialized {
+            if let Err(err) = self.uninitialize() {
+                if was_running {
+                    self.start().ok();
+                }
+                return Err(err);
+            }
+        }
+        Ok(UninitGuard {
+            audio_unit: self,
+            was_initialized,
+            was_running,
+            finished: false,
+        })
+    }
+
+    /// Whether or not the **AudioUnit** is currently initialized.
+    pub fn is_initialized(&self) -> bool {
+        self.initialized
+    }
+
+    /// Whether or not the **AudioUnit** has been started and not yet stopped.
+    ///
+    /// A unit stopped by the loss of its device (see `on_device_lost`) is no longer running.
+    pub fn is_running(&self) -> bool {
+        self.running && !self.stopped_by_device_loss()
+    }
+
+    /// Whether or not the unit has been stopped by the loss of its device since it was started.
+    fn stopped_by_device_loss(&self) -> bool {
+        #[cfg(target_os = "macos")]
+        {
+            self.stopped_by_device_loss
+                .load(std::sync::atomic::Ordering::SeqCst)
+        }
+        #[cfg(not(target_os = "macos"))]
+        {
+            false
+        }
+    }
+
     /// Sets the value for some property of the **AudioUnit**.
     ///
     /// To clear an audio unit property value, set the data parameter with `None::<()>`.
@@ -212,13 +567,16 @@ impl AudioUnit {
     /// - **scope**: The audio unit scope for the property.
     /// - **elem**: The audio unit element for the property.
     /// - **maybe_data**: The value that you want to apply to the property.
-    pub fn set_property<T>(
+    pub fn set_property<T, E>(
         &mut self,
         id: u32,
         scope: Scope,
-        elem: Element,
+        elem: E,
         maybe_data: Option<&T>,
-    ) -> Result<(), Error> {
+    ) -> Result<(), Error>
+    where
+        E: Into<u32>,
+    {
         set_property(self.instance, id, scope, elem, maybe_data)
     }
 
@@ -232,18 +590,117 @@ impl AudioUnit {
     /// - **id**: The identifier of the property.
     /// - **scope**: The audio unit scope for the property.
     /// - **elem**: The audio unit element for the property.
-    pub fn get_property<T>(&self, id: u32, scope: Scope, elem: Element) -> Result<T, Error> {
+    pub fn get_property<T, E>(&self, id: u32, scope: Scope, elem: E) -> Result<T, Error>
+    where
+        E: Into<u32>,
+    {
         get_property(self.instance, id, scope, elem)
     }
 
+    /// Gets the size and writability of an **AudioUnit** property.
+    ///
+    /// The size is that of the property's current value, which for variable-length properties
+    /// such as the parameter list or a channel layout varies between units and over time.
+    ///
+    /// Parameters
+    /// ----------
+    ///
+    /// - **id**: The identifier of the property.
+    /// - **scope**: The audio unit scope for the property.
+    /// - **elem**: The audio unit element for the property.
+    pub fn get_property_info<E>(
+        &self,
+        id: u32,
+        scope: Scope,
+        elem: E,
+    ) -> Result<PropertyInfo, Error>
+    where
+        E: Into<u32>,
+    {
+        let mut size = 0u32;
+        let mut writable: sys::Boolean = 0;
+        unsafe {
+            try_os_status!(sys::AudioUnitGetPropertyInfo(
+                self.instance,
+                id,
+                scope as c_uint,
+                elem.into(),
+                &mut size as *mut _,
+                &mut writable as *mut _
+            ));
+        }
+        Ok(PropertyInfo {
+            size,
+            writable: writable != 0,
+        })
+    }
+
+    /// Whether or not an **AudioUnit** property may currently be set.
+    ///
+    /// Parameters
+    /// ----------
+    ///
+    /// - **id**: The identifier of the property.
+    /// - **scope**: The audio unit scope for the property.
+    /// - **elem**: The audio unit element for the property.
+    pub fn is_property_writable<E>(&self, id: u32, scope: Scope, elem: E) -> Result<bool, Error>
+    where
+        E: Into<u32>,
+    {
+        Ok(self.get_property_info(id, scope, elem)?.writable)
+    }
+
+    /// Gets the value of a variable-length **AudioUnit** property as a `Vec` of `T`, such as the
+    /// `ParameterList` as a `Vec<u32>`.
+    ///
+    /// Unlike `get_property`, which requires the value to be exactly the size of `T`, the size of
+    /// the property is queried first so that a buffer of the right length is allocated. Any
+    /// trailing bytes that don't make up a whole `T` are dropped.
+    ///
+    /// Parameters
+    /// ----------
+    ///
+    /// - **id**: The identifier of the property.
+    /// - **scope**: The audio unit scope for the property.
+    /// - **elem**: The audio unit element for the property.
+    pub fn get_property_vec<T, E>(&self, id: u32, scope: Scope, elem: E) -> Result<Vec<T>, Error>
+    where
+        E: Into<u32>,
+    {
+        get_property_array(self.instance, id, scope, elem.into())
+    }
+
+    /// Gets the raw bytes of an **AudioUnit** property's value.
+    ///
+    /// This suits properties whose value is a header followed by a variable number of entries,
+    /// such as an `AudioChannelLayout`, which may be decoded from the bytes by the caller.
+    ///
+    /// Parameters
+    /// ----------
+    ///
+    /// - **id**: The identifier of the property.
+    /// - **scope**: The audio unit scope for the property.
+    /// - **elem**: The audio unit element for the property.
+    pub fn get_property_bytes<E>(&self, id: u32, scope: Scope, elem: E) -> Result<Vec<u8>, Error>
+    where
+        E: Into<u32>,
+    {
+        self.get_property_vec(id, scope, elem)
+    }
+
     /// Starts an I/O **AudioUnit**, which in turn starts the audio unit processing graph that it is
     /// connected to.
     ///
     /// **Available** in OS X v10.0 and later.
     pub fn start(&mut self) -> Result<(), Error> {
+        // Cleared beforehand so that a loss of the device while starting isn't forgotten.
+        #[cfg(target_os = "macos")]
+        self.stopped_by_device_loss
+            .store(false, std::sync::atomic::Ordering::SeqCst);
         unsafe {
             try_os_status!(sys::AudioOutputUnitStart(self.instance));
         }
+        self.running = true;
         Ok(())
     }
 
@@ -252,9 +709,13 @@ impl AudioUnit {
     ///
     /// **Available** in OS X v10.0 and later.
     pub fn stop(&mut self) -> Result<(), Error> {
-        unsafe {
-            try_os_status!(sys::AudioOutputUnitStop(self.instance));
+        // A unit stopped by the loss of its device has already been stopped.
+        if !self.stopped_by_device_loss() {
+            unsafe {
+                try_os_status!(sys::AudioOutputUnitStop(self.instance));
+            }
         }
+        self.running = false;
         Ok(())
     }
 
@@ -272,6 +733,175 @@ impl AudioUnit {
         self.get_property(id, Scope::Input, Element::Output)
     }
 
+    /// Get the ranges of sample rates the **AudioUnit** supports on the given scope, as
+    /// `(minimum, maximum)` pairs. A range whose minimum and maximum are equal is a single rate.
+    ///
+    /// Only I/O units bound to a device (`IOType::HalOutput` and the default and system output
+    /// units) report the ranges, i.e. the nominal sample rates of their current device. Other
+    /// units accept any sample rate, so an empty list is returned for them.
+    #[cfg(target_os = "macos")]
+    pub fn available_sample_rates(&self, scope: Scope) -> Result<Vec<(f64, f64)>, Error> {
+        let id = sys::kAudioOutputUnitProperty_CurrentDevice;
+        let device_id: sys::AudioDeviceID =
+            match self.get_property(id, Scope::Global, Element::Output) {
+                Ok(device_id) => device_id,
+                Err(Error::AudioUnit(AudioUnitError::InvalidProperty)) => return Ok(Vec::new()),
+                Err(err) => return Err(err),
+            };
+        let ranges = macos_helpers::get_available_sample_rates(device_id, scope)?;
+        Ok(ranges
+            .into_iter()
+            .map(|range| (range.mMinimum, range.mMaximum))
+            .collect())
+    }
+
+    /// Get the sample rate that the **AudioUnit**'s output is actually running at.
+    ///
+    /// The rate requested via `set_sample_rate` or a **StreamFormat** is not always honoured by
+    /// the hardware. For I/O units bound to a device, call this after `start` to find the rate that
+    /// was negotiated with the device, i.e. the rate at which the render callback will be called.
+    ///
+    /// This reads the sample rate of the output scope's stream format.
+    pub fn actual_sample_rate(&self) -> Result<f64, Error> {
+        let id = sys::kAudioUnitProperty_StreamFormat;
+        // Read the raw description, as the device side format may not be one that
+        // **StreamFormat** supports.
+        let asbd: sys::AudioStreamBasicDescription =
+            self.get_property(id, Scope::Output, Element::Output)?;
+        Ok(asbd.mSampleRate)
+    }
+
+    /// Get the processing latency of the **AudioUnit** in seconds.
+    ///
+    /// This is the delay between input arriving at the unit and the corresponding output, which
+    /// hosts compensate for when aligning the outputs of several units.
+    pub fn latency_seconds(&self) -> Result<f64, Error> {
+        let id = sys::kAudioUnitProperty_Latency;
+        self.get_property(id, Scope::Global, Element::Output)
+    }
+
+    /// Get the processing latency of the **AudioUnit** in frames at its output sample rate,
+    /// rounded to the nearest frame.
+    ///
+    /// This is the number of frames by which to delay the other paths of a mix, or to advance the
+    /// unit's own output, in order to compensate for the latency of a chain of effects.
+    pub fn latency_frames(&self) -> Result<u32, Error> {
+        let frames = self.latency_seconds()? * self.actual_sample_rate()?;
+        Ok(frames.round() as u32)
+    }
+
+    /// Get the tail time of the **AudioUnit** in seconds.
+    ///
+    /// This is how long the unit keeps producing output after its input falls silent, e.g. the
+    /// decay of a reverb or the echoes of a delay.
+    pub fn tail_time_seconds(&self) -> Result<f64, Error> {
+        let id = sys::kAudioUnitProperty_TailTime;
+        self.get_property(id, Scope::Global, Element::Output)
+    }
+
+    /// Get the sample time of the **AudioUnit**'s most recent render.
+    ///
+    /// A watchdog thread may poll this to detect a stall, i.e. a running unit whose sample time
+    /// has stopped advancing. Unlike `last_render_timestamp`, this requires no render notify
+    /// callback to be installed.
+    pub fn last_render_sample_time(&self) -> Result<f64, Error> {
+        let id = sys::kAudioUnitProperty_LastRenderSampleTime;
+        self.get_property(id, Scope::Global, Element::Output)
+    }
+
+    /// Get the maximum number of frames the **AudioUnit** may be asked to render in a single
+    /// call.
+    pub fn max_frames_per_slice(&self) -> Result<u32, Error> {
+        let id = sys::kAudioUnitProperty_MaximumFramesPerSlice;
+        self.get_property(id, Scope::Global, Element::Output)
+    }
+
+    /// Set the maximum number of frames the **AudioUnit** may be asked to render in a single call,
+    /// returning the value that is now in effect.
+    ///
+    /// The property may only be set while the unit is uninitialized, so the unit is uninitialized
+    /// (and stopped if running) for the duration of the call and restored afterwards.
+    ///
+    /// Some units limit the slice size depending on their current stream format, either rejecting
+    /// larger values or silently clamping them. The value is therefore read back after being set,
+    /// and if it was not accepted the largest accepted value below `frames` is found by binary
+    /// search and applied instead. Compare the returned value against `frames` to detect this, and
+    /// call this again after changing the stream format, as a change of format may change the
+    /// limit.
+    ///
+    /// Returns `TooManyFramesToProcess` if no value up to `frames` is accepted, in which case the
+    /// previous value is restored.
+    pub fn set_max_frames_per_slice(&mut self, frames: u32) -> Result<u32, Error> {
+        let mut guard = self.uninitialized_guard()?;
+        let previous = guard.max_frames_per_slice()?;
+        let result = match largest_accepted(frames, |n| guard.try_set_max_frames_per_slice(n)) {
+            // The last value tried may have been rejected, so apply the accepted one again.
+            Some(accepted) => {
+                let id = sys::kAudioUnitProperty_MaximumFramesPerSlice;
+                guard
+                    .set_property(id, Scope::Global, Element::Output, Some(&accepted))
+                    .map(|()| accepted)
+            }
+            None => {
+                let id = sys::kAudioUnitProperty_MaximumFramesPerSlice;
+                guard
+                    .set_property(id, Scope::Global, Element::Output, Some(&previous))
+                    .ok();
+                Err(Error::AudioUnit(AudioUnitError::TooManyFramesToProcess))
+            }
+        };
+        guard.finish()?;
+        result
+    }
+
+    /// Attempt to set the maximum frames per slice, returning whether the unit accepted the value
+    /// as is.
+    fn try_set_max_frames_per_slice(&mut self, frames: u32) -> bool {
+        let id = sys::kAudioUnitProperty_MaximumFramesPerSlice;
+        self.set_property(id, Scope::Global, Element::Output, Some(&frames))
+            .is_ok()
+            && self.max_frames_per_slice().ok() == Some(frames)
+    }
+
+    /// Get the number of elements (buses) within the given scope of the **AudioUnit**.
+    pub fn element_count(&self, scope: Scope) -> Result<u32, Error> {
+        let id = sys::kAudioUnitProperty_ElementCount;
+        self.get_property(id, scope, Element::Output)
+    }
+
+    /// Set the number of elements (buses) within the given scope of the **AudioUnit**, e.g. the
+    /// number of inputs of a mixer.
+    ///
+    /// Returns `Error::ElementCountFixed` with the current count if the unit doesn't support
+    /// changing the number of elements within the scope. Most units only accept the change while
+    /// uninitialized.
+    pub fn set_element_count(&mut self, scope: Scope, count: u32) -> Result<(), Error> {
+        let id = sys::kAudioUnitProperty_ElementCount;
+        if !self.is_property_writable(id, scope, Element::Output)? {
+            return Err(Error::ElementCountFixed(self.element_count(scope)?));
+        }
+        self.set_property(id, scope, Element::Output, Some(&count))
+    }
+
+    /// Check that the element (bus) with the given index exists within the given scope.
+    ///
+    /// Returns `Error::ElementOutOfRange` if `elem` is not less than the scope's `element_count`.
+    pub fn validate_element(&self, scope: Scope, elem: u32) -> Result<(), Error> {
+        if elem < self.element_count(scope)? {
+            Ok(())
+        } else {
+            Err(Error::ElementOutOfRange)
+        }
+    }
+
+    /// Get the number of both input and output buses of the **AudioUnit**.
+    pub fn bus_counts(&self) -> Result<BusCounts, Error> {
+        Ok(BusCounts {
+            inputs: self.element_count(Scope::Input)?,
+            outputs: self.element_count(Scope::Output)?,
+        })
+    }
+
     /// Sets the current **StreamFormat** for the AudioUnit.
     ///
     /// Core Audio uses slightly different defaults depending on the platform.
@@ -286,19 +916,27 @@ impl AudioUnit {
     /// > - Mac input and output: Linear PCM with 32-bit floating point samples.
     /// > - Mac audio units and other audio processing: Noninterleaved linear PCM with 32-bit
     /// floating-point
-    pub fn set_stream_format(
+    ///
+    /// The format is checked via `StreamFormat::validate` before being handed to the unit.
+    pub fn set_stream_format<E>(
         &mut self,
         stream_format: StreamFormat,
         scope: Scope,
-        element: Element,
-    ) -> Result<(), Error> {
+        element: E,
+    ) -> Result<(), Error>
+    where
+        E: Into<u32>,
+    {
         let id = sys::kAudioUnitProperty_StreamFormat;
-        let asbd = stream_format.to_asbd();
+        let asbd = stream_format.to_asbd_checked()?;
         self.set_property(id, scope, element, Some(&asbd))
     }
 
     /// Return the current Stream Format for the AudioUnit.
-    pub fn stream_format(&self, scope: Scope, element: Element) -> Result<StreamFormat, Error> {
+    pub fn stream_format<E>(&self, scope: Scope, element: E) -> Result<StreamFormat, Error>
+    where
+        E: Into<u32>,
+    {
         let id = sys::kAudioUnitProperty_StreamFormat;
         let asbd = self.get_property(id, scope, element)?;
         StreamFormat::from_asbd(asbd)
@@ -313,6 +951,156 @@ impl AudioUnit {
     pub fn input_stream_format(&self) -> Result<StreamFormat, Error> {
         self.stream_format(Scope::Output, Element::Input)
     }
+
+    /// Set the input stream format of the bus `dest_bus` to the output stream format of the bus
+    /// `source_bus` of `source`, ready for the two to be connected.
+    ///
+    /// The format is copied as is, including formats which **StreamFormat** doesn't support. Most
+    /// units only accept stream format changes while uninitialized.
+    pub fn match_input_to(
+        &mut self,
+        source: &AudioUnit,
+        source_bus: u32,
+        dest_bus: u32,
+    ) -> Result<(), Error> {
+        let id = sys::kAudioUnitProperty_StreamFormat;
+        let asbd: sys::AudioStreamBasicDescription =
+            get_property_raw(source.instance, id, Scope::Output, source_bus)?;
+        set_property_raw(self.instance, id, Scope::Input, dest_bus, Some(&asbd))
+    }
+
+    /// Connect the output bus `source_bus` of `source` to the input bus `dest_bus` of this unit,
+    /// so that this unit pulls its input by rendering `source` rather than via a render callback.
+    ///
+    /// This allows chains such as generator → effect → output to be built without shuttling
+    /// buffers between the units by hand. The input stream format of `dest_bus` should first be
+    /// matched to the source, e.g. via `match_input_to`. Any render callback previously set on
+    /// `dest_bus` is superseded by the connection.
+    ///
+    /// # Safety
+    ///
+    /// The connection refers to `source` without keeping it alive, and rendering this unit
+    /// renders `source`. `source` must therefore not be dropped while it is connected: call
+    /// `disconnect_input` first, or drop this unit first.
+    pub unsafe fn connect_input(
+        &mut self,
+        source: &AudioUnit,
+        source_bus: u32,
+        dest_bus: u32,
+    ) -> Result<(), Error> {
+        let connection = sys::AudioUnitConnection {
+            sourceAudioUnit: source.instance,
+            sourceOutputNumber: source_bus,
+            destInputNumber: dest_bus,
+        };
+        let id = sys::kAudioUnitProperty_MakeConnection;
+        set_property_raw(self.instance, id, Scope::Input, dest_bus, Some(&connection))
+    }
+
+    /// Remove the connection made via `connect_input` to the input bus `dest_bus`, if any.
+    pub fn disconnect_input(&mut self, dest_bus: u32) -> Result<(), Error> {
+        // A connection with a null source unit breaks the existing connection.
+        let connection = sys::AudioUnitConnection {
+            sourceAudioUnit: ptr::null_mut(),
+            sourceOutputNumber: 0,
+            destInputNumber: dest_bus,
+        };
+        let id = sys::kAudioUnitProperty_MakeConnection;
+        set_property_raw(self.instance, id, Scope::Input, dest_bus, Some(&connection))
+    }
+
+    /// The component from which the **AudioUnit** instance was created.
+    ///
+    /// This is the component that was found for the unit's type (or passed to `from_component`),
+    /// captured when the instance is created. Its raw handle is available via `as_raw`.
+    pub fn component(&self) -> AudioComponent {
+        AudioComponent::from_raw(self.component)
+    }
+
+    /// The component description of the **AudioUnit** instance.
+    ///
+    /// This describes the type, subtype and manufacturer of the unit that was actually
+    /// instantiated.
+    fn component_description(&self) -> Result<sys::AudioComponentDescription, Error> {
+        unsafe {
+            let mut desc = mem::MaybeUninit::<sys::AudioComponentDescription>::uninit();
+            try_os_status!(sys::AudioComponentGetDescription(
+                self.component,
+                desc.as_mut_ptr()
+            ));
+            Ok(desc.assume_init())
+        }
+    }
+
+    /// The broad kind of the **AudioUnit**, derived from its component type.
+    ///
+    /// Unlike `Type::from_u32_pair`, this doesn't require the unit's subtype to be known, so it
+    /// also categorizes third-party units.
+    pub fn category(&self) -> Result<UnitCategory, Error> {
+        let desc = self.component_description()?;
+        UnitCategory::from_type_u32(desc.componentType).ok_or(Error::Unspecified)
+    }
+
+    /// Returns `Ok` if the **AudioUnit** has one of the given component subtypes.
+    ///
+    /// Used by the unit-specific wrappers to validate the unit they are given.
+    fn expect_subtype(&self, sub_types: &[u32]) -> Result<(), Error> {
+        let desc = self.component_description()?;
+        if sub_types.contains(&desc.componentSubType) {
+            Ok(())
+        } else {
+            Err(Error::UnexpectedSubtype)
+        }
+    }
+}
+
+/// Keeps an **AudioUnit** uninitialized until dropped. See `AudioUnit::uninitialized_guard`.
+pub struct UninitGuard<'a> {
+    audio_unit: &'a mut AudioUnit,
+    was_initialized: bool,
+    was_running: bool,
+    finished: bool,
+}
+
+impl<'a> UninitGuard<'a> {
+    /// Restore the **AudioUnit** to its prior state, returning any error that occurs while doing
+    /// so.
+    pub fn finish(mut self) -> Result<(), Error> {
+        self.restore()
+    }
+
+    fn restore(&mut self) -> Result<(), Error> {
+        self.finished = true;
+        if self.was_initialized {
+            self.audio_unit.initialize()?;
+        }
+        if self.was_running {
+            self.audio_unit.start()?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> ::std::ops::Deref for UninitGuard<'a> {
+    type Target = AudioUnit;
+    fn deref(&self) -> &AudioUnit {
+        self.audio_unit
+    }
+}
+
+impl<'a> ::std::ops::DerefMut for UninitGuard<'a> {
+    fn deref_mut(&mut self) -> &mut AudioUnit {
+        self.audio_unit
+    }
+}
+
+impl<'a> Drop for UninitGuard<'a> {
+    fn drop(&mut self) {
+        if !self.finished {
+            // We don't want to panic in `drop`, so we'll ignore returned errors.
+            self.restore().ok();
+        }
+    }
 }
 
 impl AsRef<sys::AudioUnit> for AudioUnit {
@@ -327,6 +1115,25 @@ impl AsMut<sys::AudioUnit> for AudioUnit {
     }
 }
 
+impl fmt::Debug for AudioUnit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut debug = f.debug_struct("AudioUnit");
+        debug.field("instance", &self.instance);
+        if let Ok(desc) = self.component_description() {
+            debug
+                .field("type", &desc.componentType)
+                .field("sub_type", &desc.componentSubType);
+        }
+        if let Ok(bus_counts) = self.bus_counts() {
+            debug.field("bus_counts", &bus_counts);
+        }
+        debug
+            .field("initialized", &self.initialized)
+            .field("running", &self.is_running())
+            .finish()
+    }
+}
+
 unsafe impl Send for AudioUnit {}
 
 impl Drop for AudioUnit {
@@ -334,17 +1141,38 @@ impl Drop for AudioUnit {
         unsafe {
             use crate::error;
 
-            // We don't want to panic in `drop`, so we'll ignore returned errors.
+            // We don't want to panic in `drop`, so errors are only passed to the handler set via
+            // `set_drop_error_handler`, if any.
             //
             // A user should explicitly terminate the `AudioUnit` if they want to handle errors (we
             // still need to provide a way to actually do that).
-            self.stop().ok();
-            error::Error::from_os_status(sys::AudioUnitUninitialize(self.instance)).ok();
+            //
+            // The device lost listener is removed first so that it can't stop the unit while it is
+            // being torn down.
+            #[cfg(target_os = "macos")]
+            self.free_device_lost_listener();
+            // Only I/O units may be stopped, so other units would report a spurious error.
+            if self.is_running() {
+                error::report_drop_error(self.stop());
+            }
+            if self.initialized {
+                let status = sys::AudioUnitUninitialize(self.instance);
+                error::report_drop_error(error::Error::from_os_status(status));
+            }
 
             self.free_render_callback();
             self.free_input_callback();
+            self.free_property_listeners();
+            self.free_render_timestamp();
+            self.free_render_notifies();
+            self.free_parameter_queue();
+            self.free_input_samples_in_output_callback();
+            self.free_host_callbacks();
+            #[cfg(feature = "core_midi")]
+            self.free_midi_output_callback();
 
-            error::Error::from_os_status(sys::AudioComponentInstanceDispose(self.instance)).ok();
+            let status = sys::AudioComponentInstanceDispose(self.instance);
+            error::report_drop_error(error::Error::from_os_status(status));
         }
     }
 }
@@ -367,11 +1195,28 @@ impl Drop for AudioUnit {
 /// - **scope**: The audio unit scope for the property.
 /// - **elem**: The audio unit element for the property.
 /// - **maybe_data**: The value that you want to apply to the property.
-pub fn set_property<T>(
+pub fn set_property<T, E>(
     au: sys::AudioUnit,
     id: u32,
     scope: Scope,
-    elem: Element,
+    elem: E,
+    maybe_data: Option<&T>,
+) -> Result<(), Error>
+where
+    E: Into<u32>,
+{
+    set_property_raw(au, id, scope, elem.into(), maybe_data)
+}
+
+/// The same as `set_property` but addressing the element by its raw `u32` value.
+///
+/// This allows for addressing buses beyond the `Input` and `Output` **Element**s, e.g. the inputs
+/// of a mixer.
+fn set_property_raw<T>(
+    au: sys::AudioUnit,
+    id: u32,
+    scope: Scope,
+    elem: u32,
     maybe_data: Option<&T>,
 ) -> Result<(), Error> {
     let (data_ptr, size) = maybe_data
@@ -382,7 +1227,6 @@ pub fn set_property<T>(
         })
         .unwrap_or_else(|| (::std::ptr::null(), 0));
     let scope = scope as c_uint;
-    let elem = elem as c_uint;
     unsafe {
         try_os_status!(sys::AudioUnitSetProperty(
             au, id, scope, elem, data_ptr, size
@@ -402,14 +1246,19 @@ pub fn set_property<T>(
 /// - **id**: The identifier of the property.
 /// - **scope**: The audio unit scope for the property.
 /// - **elem**: The audio unit element for the property.
-pub fn get_property<T>(
-    au: sys::AudioUnit,
-    id: u32,
-    scope: Scope,
-    elem: Element,
-) -> Result<T, Error> {
+pub fn get_property<T, E>(au: sys::AudioUnit, id: u32, scope: Scope, elem: E) -> Result<T, Error>
+where
+    E: Into<u32>,
+{
+    get_property_raw(au, id, scope, elem.into())
+}
+
+/// The same as `get_property` but addressing the element by its raw `u32` value.
+///
+/// Some properties (e.g. `kAudioUnitProperty_ParameterInfo`) use the "element" field to address
+/// something other than an **Element**.
+fn get_property_raw<T>(au: sys::AudioUnit, id: u32, scope: Scope, elem: u32) -> Result<T, Error> {
     let scope = scope as c_uint;
-    let elem = elem as c_uint;
     let mut size = ::std::mem::size_of::<T>() as u32;
     unsafe {
         let mut data_uninit = ::std::mem::MaybeUninit::<T>::uninit();
@@ -423,6 +1272,100 @@ pub fn get_property<T>(
     }
 }
 
+/// Gets the value of a variable-length **AudioUnit** property as a `Vec` of `T`.
+///
+/// The size of the property is queried first so that the buffer can be allocated accordingly.
+fn get_property_array<T>(
+    au: sys::AudioUnit,
+    id: u32,
+    scope: Scope,
+    elem: u32,
+) -> Result<Vec<T>, Error> {
+    let scope = scope as c_uint;
+    let mut size = 0u32;
+    unsafe {
+        try_os_status!(sys::AudioUnitGetPropertyInfo(
+            au,
+            id,
+            scope,
+            elem,
+            &mut size as *mut _,
+            ptr::null_mut()
+        ));
+        // Only request as many bytes as fit within the allocation, should the reported size not
+        // be a whole number of `T`s.
+        let (len, truncated_size) = array_len_and_size::<T>(size);
+        size = truncated_size;
+        let mut data: Vec<T> = Vec::with_capacity(len);
+        if len > 0 {
+            let data_ptr = data.as_mut_ptr() as *mut c_void;
+            try_os_status!(sys::AudioUnitGetProperty(
+                au,
+                id,
+                scope,
+                elem,
+                data_ptr,
+                &mut size as *mut _
+            ));
+            data.set_len(usize::min(len, size as usize / mem::size_of::<T>()));
+        }
+        Ok(data)
+    }
+}
+
+/// The number of whole `T`s within a property of `size` bytes, along with their size in bytes.
+fn array_len_and_size<T>(size: u32) -> (usize, u32) {
+    let len = size as usize / mem::size_of::<T>();
+    (len, (len * mem::size_of::<T>()) as u32)
+}
+
+/// Sets the value of a variable-length **AudioUnit** property from a slice of `T`.
+fn set_property_array<T>(
+    au: sys::AudioUnit,
+    id: u32,
+    scope: Scope,
+    elem: u32,
+    data: &[T],
+) -> Result<(), Error> {
+    let scope = scope as c_uint;
+    let data_ptr = data.as_ptr() as *const c_void;
+    let size = mem::size_of_val(data) as u32;
+    unsafe {
+        try_os_status!(sys::AudioUnitSetProperty(
+            au, id, scope, elem, data_ptr, size
+        ))
+    }
+    Ok(())
+}
+
+/// Find the largest value within `1..=max` for which `accepts` returns `true`, trying `max` first.
+///
+/// Assumes that if a value is accepted then so is every smaller value.
+fn largest_accepted<F>(max: u32, mut accepts: F) -> Option<u32>
+where
+    F: FnMut(u32) -> bool,
+{
+    if max == 0 {
+        return None;
+    }
+    if accepts(max) {
+        return Some(max);
+    }
+    // The largest accepted value is within `lo..hi` if there is one, where `lo - 1` is accepted.
+    let (mut lo, mut hi) = (1, max);
+    let mut accepted = None;
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if accepts(mid) {
+            accepted = Some(mid);
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    accepted
+}
+
 /// Gets the value of a specified audio session property.
 ///
 /// **Available** in iOS 2.0 and later.
@@ -443,3 +1386,20 @@ pub fn audio_session_get_property<T>(id: u32) -> Result<T, Error> {
         Ok(data)
     }
 }
+
+#[test]
+fn test_largest_accepted() {
+    assert_eq!(largest_accepted(4096, |n| n <= 4096), Some(4096));
+    assert_eq!(largest_accepted(4096, |n| n <= 1156), Some(1156));
+    assert_eq!(largest_accepted(4096, |n| n <= 1), Some(1));
+    assert_eq!(largest_accepted(4096, |_| false), None);
+    assert_eq!(largest_accepted(0, |_| true), None);
+}
+
+#[test]
+fn test_array_len_and_size() {
+    assert_eq!(array_len_and_size::<f64>(32), (4, 32));
+    // Trailing bytes that don't make up a whole element are not requested.
+    assert_eq!(array_len_and_size::<f64>(35), (4, 32));
+    assert_eq!(array_len_and_size::<u32>(3), (0, 0));
+}
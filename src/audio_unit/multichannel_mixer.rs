@@ -0,0 +1,66 @@
+//! A typed wrapper around the multichannel mixer **AudioUnit**.
+//!
+//! The mixer sums any number of input buses into a single output bus, with a volume for each input
+//! bus and for the output. Volumes are linear gains, and may also be set in decibels (see the
+//! [**volume**](../volume/index.html) module for the curve used).
+//!
+//! Find the original multichannel mixer parameter documentation [here](https://developer.apple.com/documentation/audiotoolbox/1534196-multichannel_mixer_unit_paramete).
+
+use super::volume::{db_to_scalar, scalar_to_db};
+use super::{parameter, MixerType, ParameterId, Scope};
+use crate::error::Error;
+
+// The `kMultiChannelMixerParam_*` parameters.
+const VOLUME: ParameterId = 0;
+
+unit_wrapper!(
+    /// A multichannel mixer **AudioUnit** with typed accessors for its volumes.
+    MultiChannelMixer,
+    MixerType::MultiChannelMixer,
+    "multichannel mixer"
+);
+
+impl MultiChannelMixer {
+    /// The volume of the given input bus as a linear gain.
+    pub fn input_volume(&self, bus: u32) -> Result<f32, Error> {
+        parameter::get_parameter(self.audio_unit.instance, VOLUME, Scope::Input, bus)
+    }
+
+    /// Set the volume of the given input bus as a linear gain, where `1.0` is unity gain.
+    ///
+    /// Returns `Error::ElementOutOfRange` if the mixer has no such input bus.
+    pub fn set_input_volume(&mut self, bus: u32, volume: f32) -> Result<(), Error> {
+        self.audio_unit.validate_element(Scope::Input, bus)?;
+        parameter::set_parameter(self.audio_unit.instance, VOLUME, Scope::Input, bus, volume)
+    }
+
+    /// The volume of the given input bus in decibels.
+    pub fn input_volume_db(&self, bus: u32) -> Result<f32, Error> {
+        self.input_volume(bus).map(scalar_to_db)
+    }
+
+    /// Set the volume of the given input bus in decibels, where `0.0` is unity gain.
+    pub fn set_input_volume_db(&mut self, bus: u32, db: f32) -> Result<(), Error> {
+        self.set_input_volume(bus, db_to_scalar(db))
+    }
+
+    /// The volume of the output as a linear gain.
+    pub fn output_volume(&self) -> Result<f32, Error> {
+        parameter::get_parameter(self.audio_unit.instance, VOLUME, Scope::Output, 0)
+    }
+
+    /// Set the volume of the output as a linear gain, where `1.0` is unity gain.
+    pub fn set_output_volume(&mut self, volume: f32) -> Result<(), Error> {
+        parameter::set_parameter(self.audio_unit.instance, VOLUME, Scope::Output, 0, volume)
+    }
+
+    /// The volume of the output in decibels.
+    pub fn output_volume_db(&self) -> Result<f32, Error> {
+        self.output_volume().map(scalar_to_db)
+    }
+
+    /// Set the volume of the output in decibels, where `0.0` is unity gain.
+    pub fn set_output_volume_db(&mut self, db: f32) -> Result<(), Error> {
+        self.set_output_volume(db_to_scalar(db))
+    }
+}
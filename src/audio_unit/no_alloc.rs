@@ -0,0 +1,102 @@
+//! Detecting allocation within render callbacks, enabled by the `assert_no_alloc` feature.
+//!
+//! Allocating or freeing memory on the render thread may block on the allocator's locks and
+//! cause audible dropouts. The render callback plumbing of this crate allocates its buffers when
+//! a callback is registered, sizing the buffers that input is captured into for the unit's
+//! `max_frames_per_slice`. Should the unit render more frames than that at once, the callback
+//! returns `AudioUnitError::TooManyFramesToProcess` rather than reallocating. Any allocation made
+//! while a callback runs thus comes from the callback itself. With the feature enabled and
+//! **AssertNoAlloc** installed as the global allocator, debug builds abort the process with a
+//! message on such an allocation.
+
+#[cfg(feature = "assert_no_alloc")]
+use std::alloc::{GlobalAlloc, Layout, System};
+#[cfg(all(feature = "assert_no_alloc", debug_assertions))]
+use std::cell::Cell;
+
+#[cfg(all(feature = "assert_no_alloc", debug_assertions))]
+thread_local! {
+    // The number of render or input callbacks currently running on this thread.
+    static RENDER_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Marks the current thread as running a render or input callback for as long as it is alive.
+pub(crate) struct RenderGuard(());
+
+impl RenderGuard {
+    /// Mark the current thread as running a callback until the guard is dropped.
+    pub(crate) fn enter() -> Self {
+        #[cfg(all(feature = "assert_no_alloc", debug_assertions))]
+        RENDER_DEPTH.with(|depth| depth.set(depth.get() + 1));
+        RenderGuard(())
+    }
+}
+
+#[cfg(all(feature = "assert_no_alloc", debug_assertions))]
+impl Drop for RenderGuard {
+    fn drop(&mut self) {
+        RENDER_DEPTH.with(|depth| depth.set(depth.get() - 1));
+    }
+}
+
+/// A global allocator that aborts the process when memory is allocated or freed within a render
+/// or input callback, forwarding to the system allocator otherwise.
+///
+/// Install it in the application (not in a library) to check that its callbacks are real-time
+/// safe:
+///
+/// ```ignore
+/// #[global_allocator]
+/// static ALLOCATOR: coreaudio::audio_unit::AssertNoAlloc = coreaudio::audio_unit::AssertNoAlloc;
+/// ```
+///
+/// The check is only made in debug builds. In release builds this is the system allocator.
+#[cfg(feature = "assert_no_alloc")]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct AssertNoAlloc;
+
+#[cfg(feature = "assert_no_alloc")]
+impl AssertNoAlloc {
+    /// Abort if the current thread is running a render or input callback.
+    #[inline]
+    fn check(&self) {
+        #[cfg(debug_assertions)]
+        {
+            // The check is disabled before reporting, so that the report may itself allocate.
+            let depth = RENDER_DEPTH.try_with(|depth| depth.replace(0)).unwrap_or(0);
+            if depth > 0 {
+                let msg = b"coreaudio-rs: memory was allocated or freed within a render callback\n";
+                std::io::Write::write_all(&mut std::io::stderr(), msg).ok();
+                std::process::abort();
+            }
+        }
+    }
+}
+
+// Check the crate's own render-thread code in its tests.
+#[cfg(all(test, feature = "assert_no_alloc"))]
+#[global_allocator]
+static ALLOCATOR: AssertNoAlloc = AssertNoAlloc;
+
+#[cfg(feature = "assert_no_alloc")]
+unsafe impl GlobalAlloc for AssertNoAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.check();
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        self.check();
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        self.check();
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.check();
+        System.dealloc(ptr, layout)
+    }
+}
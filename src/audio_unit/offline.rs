@@ -0,0 +1,329 @@
+//! Pulling audio from an **AudioUnit** manually, e.g. to render its output to a file faster than
+//! real-time.
+
+use super::audio_format::LinearPcmFlags;
+use super::buffer_list::BufferList;
+use super::render_callback::{self, data, RenderFlags};
+use super::wav::{WavSampleFormat, WavSpec, WavWriter};
+use super::{property, AudioUnit, Element, SampleFormat, Scope};
+use crate::error::{AudioUnitError, Error};
+use std::io::{Seek, Write};
+use std::mem;
+use std::path::Path;
+use sys;
+
+impl AudioUnit {
+    /// Render `num_frames` frames of the output bus `bus` into the given **BufferList**.
+    ///
+    /// This calls `AudioUnitRender` directly, pulling audio through the unit and any render
+    /// callback or connection providing its input. `num_frames` must not exceed the `max_frames`
+    /// of the buffer list.
+    ///
+    /// Units refuse to render more than `max_frames_per_slice` frames at once, returning
+    /// `TooManyFramesToProcess`. Larger renders are therefore split into consecutive chunks of at
+    /// most `max_frames_per_slice` frames, with the sample time of `time_stamp` advanced for each
+    /// chunk, and the chunks are copied into the buffer list. In that case the buffer list always
+    /// holds the samples in its own storage afterwards.
+    ///
+    /// `action_flags` are passed to the unit and updated with the flags it returns. If
+    /// `OUTPUT_IS_SILENCE` is set on return, every rendered frame is silent and the caller may skip
+    /// processing the buffers.
+    pub fn render_into(
+        &mut self,
+        action_flags: &mut RenderFlags,
+        time_stamp: &sys::AudioTimeStamp,
+        bus: u32,
+        num_frames: u32,
+        buffers: &mut BufferList,
+    ) -> Result<(), Error> {
+        if num_frames as usize > buffers.max_frames() {
+            return Err(Error::AudioUnit(AudioUnitError::TooManyFramesToProcess));
+        }
+        let max_frames_per_slice = self.max_frames_per_slice()?;
+        if num_frames <= max_frames_per_slice {
+            return self.render(action_flags, time_stamp, bus, num_frames, buffers);
+        }
+
+        let mut chunk = BufferList::new(
+            buffers.channels(),
+            max_frames_per_slice as usize,
+            buffers.is_interleaved(),
+        );
+        let mut chunk_time_stamp = *time_stamp;
+        let initial_flags = *action_flags;
+        // The whole render is only silent if every chunk of it is.
+        let mut is_silence = true;
+        let mut rendered_frames = 0;
+        while rendered_frames < num_frames {
+            let frames = (num_frames - rendered_frames).min(max_frames_per_slice);
+            *action_flags = initial_flags;
+            self.render(action_flags, &chunk_time_stamp, bus, frames, &mut chunk)?;
+            is_silence &= action_flags.contains(RenderFlags::OUTPUT_IS_SILENCE);
+            buffers.copy_frames_from(rendered_frames as usize, &chunk, frames as usize);
+            chunk_time_stamp.mSampleTime += frames as f64;
+            rendered_frames += frames;
+        }
+        buffers.set_frames(num_frames as usize);
+        action_flags.set(RenderFlags::OUTPUT_IS_SILENCE, is_silence);
+        Ok(())
+    }
+
+    /// Render `num_frames` frames of the output bus `bus` into the given **BufferList** via a
+    /// single call to `AudioUnitRender`.
+    ///
+    /// Unlike `render_into` this never splits the render or allocates, so it may be used on the
+    /// render thread, e.g. to pull an effect from within another unit's render callback. The unit
+    /// returns `TooManyFramesToProcess` if `num_frames` exceeds its `max_frames_per_slice`, as
+    /// does this if `num_frames` exceeds the `max_frames` of the buffer list.
+    ///
+    /// `action_flags` are passed to the unit and updated with the flags it returns.
+    pub fn render(
+        &mut self,
+        action_flags: &mut RenderFlags,
+        time_stamp: &sys::AudioTimeStamp,
+        bus: u32,
+        num_frames: u32,
+        buffers: &mut BufferList,
+    ) -> Result<(), Error> {
+        if num_frames as usize > buffers.max_frames() {
+            return Err(Error::AudioUnit(AudioUnitError::TooManyFramesToProcess));
+        }
+        buffers.set_frames(num_frames as usize);
+        let mut raw_flags: sys::AudioUnitRenderActionFlags = action_flags.bits();
+        let status = unsafe {
+            sys::AudioUnitRender(
+                self.instance,
+                &mut raw_flags as *mut _,
+                time_stamp as *const _,
+                bus,
+                num_frames,
+                buffers.as_mut_ptr(),
+            )
+        };
+        *action_flags = RenderFlags::from_bits_truncate(raw_flags);
+        Error::from_os_status(status)
+    }
+
+    /// Render one block of `buffers.max_frames()` frames of the tail that follows the end of the
+    /// unit's input, returning whether or not the tail has fully decayed.
+    ///
+    /// This automates bouncing an effect offline, where silence must keep being rendered through
+    /// the unit after its input ends in order to capture e.g. the decay of a reverb. Once the input
+    /// has ended, the render callback or connection providing it should supply silence, and this
+    /// should be called repeatedly with an advancing `time_stamp` until it returns `true`.
+    /// `tail_start` is the sample time at which the input ended.
+    ///
+    /// The tail is considered decayed once both:
+    ///
+    /// - at least `tail_time_seconds` has passed since `tail_start`, so that silent gaps such as
+    ///   those between the echoes of a delay aren't mistaken for the end of the tail, and
+    /// - the rendered block is silent, i.e. the unit flags it with `OUTPUT_IS_SILENCE` or its peak
+    ///   sample on every channel is below -90 dBFS, in case the unit under-reports its tail time.
+    ///
+    /// Units whose output never falls silent, such as a delay with full feedback, are never
+    /// considered decayed, so callers should also bound the number of blocks rendered.
+    pub fn render_tail(
+        &mut self,
+        time_stamp: &sys::AudioTimeStamp,
+        tail_start: f64,
+        buffers: &mut BufferList,
+    ) -> Result<bool, Error> {
+        let tail_frames = self.tail_time_seconds()? * self.actual_sample_rate()?;
+        let num_frames = buffers.max_frames() as u32;
+        let mut action_flags = RenderFlags::empty();
+        self.render_into(&mut action_flags, time_stamp, 0, num_frames, buffers)?;
+        let peak = if action_flags.contains(RenderFlags::OUTPUT_IS_SILENCE) {
+            0.0
+        } else {
+            (0..buffers.num_buffers())
+                .flat_map(|index| buffers.buffer(index).iter())
+                .fold(0.0f32, |peak, sample| peak.max(sample.abs()))
+        };
+        let elapsed_frames = time_stamp.mSampleTime + num_frames as f64 - tail_start;
+        Ok(is_tail_decayed(elapsed_frames, tail_frames, peak))
+    }
+
+    /// Render `duration_frames` frames of the **AudioUnit**'s output to a WAV file at `path`.
+    ///
+    /// The unit is rendered in chunks of at most `max_frames_per_slice` frames, with the final
+    /// chunk holding whatever frames remain. The file is written as 32-bit float samples with the
+    /// channel count and sample rate of the unit's output stream format, which must use `f32`
+    /// samples (the default for audio units on macOS).
+    ///
+    /// Generators render on their own, whereas effects pull their input from their render
+    /// callback or connection, which must be set up beforehand. The unit is flagged as rendering
+    /// offline for the duration of the bounce where it supports it.
+    ///
+    /// WAV files are limited to 4 GiB, so a bounce whose samples exceed that fails with an I/O
+    /// error once the limit is reached.
+    pub fn bounce_to_wav(&mut self, path: &Path, duration_frames: u64) -> Result<(), Error> {
+        let stream_format = self.stream_format(Scope::Output, Element::Output)?;
+        if stream_format.sample_format != SampleFormat::F32 {
+            return Err(Error::UnsupportedStreamFormat);
+        }
+        let channels = stream_format.channels as usize;
+        let interleaved = !stream_format
+            .flags
+            .contains(LinearPcmFlags::IS_NON_INTERLEAVED);
+        let max_frames = self.max_frames_per_slice()?;
+
+        let spec = WavSpec {
+            channels: channels as u16,
+            sample_rate: stream_format.sample_rate as u32,
+            sample_format: WavSampleFormat::F32,
+        };
+        let mut writer = WavWriter::create(path, spec)?;
+        let mut buffers = BufferList::new(channels, max_frames as usize, interleaved);
+
+        // Not all units support offline rendering, in which case they render as usual.
+        let offline = property::OFFLINE_RENDER;
+        self.set_property(offline, Scope::Global, Element::Output, Some(&1u32))
+            .ok();
+
+        let result = self.render_frames_to_wav(&mut writer, &mut buffers, duration_frames);
+
+        self.set_property(offline, Scope::Global, Element::Output, Some(&0u32))
+            .ok();
+        result?;
+        writer.finalize()?;
+        Ok(())
+    }
+
+    /// Feed the given input through the **AudioUnit** and return its output, one `Vec` per output
+    /// channel.
+    ///
+    /// `input` holds one slice per input channel, all of the same length, which must match the
+    /// channel count of the unit's input stream format. The input is supplied via a temporary
+    /// render callback, replacing any existing one, and the unit is then rendered via
+    /// `AudioUnitRender` for as many frames as the input holds. The render callback is cleared
+    /// again before returning.
+    ///
+    /// This requires no audio hardware, making it useful for deterministically testing the effect
+    /// of an effect unit's parameters. Both the input and output stream formats must use
+    /// non-interleaved `f32` samples, the default for effect units on macOS.
+    pub fn probe_render(&mut self, input: &[&[f32]]) -> Result<Vec<Vec<f32>>, Error> {
+        let input_format = self.stream_format(Scope::Input, Element::Output)?;
+        let output_format = self.stream_format(Scope::Output, Element::Output)?;
+        if output_format.sample_format != SampleFormat::F32
+            || !output_format
+                .flags
+                .contains(LinearPcmFlags::IS_NON_INTERLEAVED)
+        {
+            return Err(Error::UnsupportedStreamFormat);
+        }
+        let num_frames = input.first().map_or(0, |channel| channel.len());
+        if input.len() != input_format.channels as usize
+            || input.iter().any(|channel| channel.len() != num_frames)
+        {
+            return Err(Error::RenderCallbackBufferFormatDoesNotMatchAudioUnitStreamFormat);
+        }
+
+        // Render calls are made synchronously below, so the callback reads the input in order.
+        let input: Vec<Vec<f32>> = input.iter().map(|channel| channel.to_vec()).collect();
+        let mut position = 0;
+        type Args = render_callback::Args<data::NonInterleaved<f32>>;
+        self.set_render_callback(move |args: Args| {
+            let Args {
+                num_frames,
+                mut data,
+                ..
+            } = args;
+            for (channel, input) in data.channels_mut().zip(&input) {
+                // Pad with silence should the unit pull more frames than remain.
+                let start = position.min(input.len());
+                let end = (position + num_frames).min(input.len());
+                let (head, tail) = channel.split_at_mut(end - start);
+                head.copy_from_slice(&input[start..end]);
+                for sample in tail {
+                    *sample = 0.0;
+                }
+            }
+            position += num_frames;
+            Ok(())
+        })?;
+
+        let result = self.probe_render_frames(num_frames as u64, output_format.channels as usize);
+        self.clear_render_callback()?;
+        result
+    }
+
+    /// Render `num_frames` frames of the unit's output into one `Vec` per channel.
+    fn probe_render_frames(
+        &mut self,
+        num_frames: u64,
+        channels: usize,
+    ) -> Result<Vec<Vec<f32>>, Error> {
+        let max_frames = self.max_frames_per_slice()? as usize;
+        let mut buffers = BufferList::new(channels, max_frames, false);
+        let mut output = vec![Vec::with_capacity(num_frames as usize); channels];
+        let mut time_stamp: sys::AudioTimeStamp = unsafe { mem::zeroed() };
+        time_stamp.mFlags = sys::kAudioTimeStampSampleTimeValid;
+        let mut rendered_frames = 0u64;
+        while rendered_frames < num_frames {
+            let frames = (num_frames - rendered_frames).min(max_frames as u64) as u32;
+            time_stamp.mSampleTime = rendered_frames as f64;
+            let mut action_flags = RenderFlags::empty();
+            self.render_into(&mut action_flags, &time_stamp, 0, frames, &mut buffers)?;
+            for (channel, output) in output.iter_mut().enumerate() {
+                output.extend_from_slice(&buffers.buffer(channel)[..frames as usize]);
+            }
+            rendered_frames += frames as u64;
+        }
+        Ok(output)
+    }
+
+    /// Render `duration_frames` frames in chunks of up to `buffers.max_frames()` frames, writing
+    /// each chunk to `writer`.
+    fn render_frames_to_wav<W>(
+        &mut self,
+        writer: &mut WavWriter<W>,
+        buffers: &mut BufferList,
+        duration_frames: u64,
+    ) -> Result<(), Error>
+    where
+        W: Write + Seek,
+    {
+        let mut time_stamp: sys::AudioTimeStamp = unsafe { mem::zeroed() };
+        time_stamp.mFlags = sys::kAudioTimeStampSampleTimeValid;
+        let mut rendered_frames = 0u64;
+        while rendered_frames < duration_frames {
+            let remaining = duration_frames - rendered_frames;
+            let num_frames = remaining.min(buffers.max_frames() as u64) as u32;
+            time_stamp.mSampleTime = rendered_frames as f64;
+            let mut action_flags = RenderFlags::empty();
+            self.render_into(&mut action_flags, &time_stamp, 0, num_frames, buffers)?;
+            let is_silence = action_flags.contains(RenderFlags::OUTPUT_IS_SILENCE);
+            for frame in 0..num_frames as usize {
+                for channel in 0..buffers.channels() {
+                    let sample = if is_silence {
+                        0.0
+                    } else {
+                        buffers.sample(channel, frame)
+                    };
+                    writer.write_sample(sample)?;
+                }
+            }
+            rendered_frames += num_frames as u64;
+        }
+        Ok(())
+    }
+}
+
+/// The peak level below which a block of the tail is considered silent, -90 dBFS.
+const TAIL_SILENCE_THRESHOLD: f32 = 3.162_278e-5;
+
+/// Whether or not a tail with the given peak level has decayed once `elapsed_frames` of the
+/// unit's `tail_frames` have been rendered.
+fn is_tail_decayed(elapsed_frames: f64, tail_frames: f64, peak: f32) -> bool {
+    elapsed_frames >= tail_frames && peak < TAIL_SILENCE_THRESHOLD
+}
+
+#[test]
+fn test_is_tail_decayed() {
+    assert!(is_tail_decayed(48_000.0, 48_000.0, 0.0));
+    assert!(is_tail_decayed(1_024.0, 0.0, 1e-6));
+    // Silent, but within the tail time.
+    assert!(!is_tail_decayed(1_024.0, 48_000.0, 0.0));
+    // Past the tail time, but still audible.
+    assert!(!is_tail_decayed(96_000.0, 48_000.0, 0.01));
+}
@@ -0,0 +1,464 @@
+//! Types and methods for inspecting the parameters of an **AudioUnit**.
+//!
+//! Parameters are the user-adjustable, real-time controls of an audio unit such as the cutoff
+//! frequency of a filter or the volume of a mixer input.
+//!
+//! Find the original Audio Unit Parameters reference [here](https://developer.apple.com/documentation/audiotoolbox/audio_unit_parameters).
+
+use super::{cf, property, AudioUnit, Scope};
+use crate::error::Error;
+use std::os::raw::c_uint;
+use std::sync::{Arc, Mutex};
+use sys;
+
+/// The identifier of an **AudioUnit** parameter.
+pub type ParameterId = sys::AudioUnitParameterID;
+
+/// The unit of measurement in which a parameter's value is expressed.
+///
+/// Original documentation [here](https://developer.apple.com/documentation/audiotoolbox/audiounitparameterunit).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ParameterUnit {
+    /// Untyped value generally between 0.0 and 1.0.
+    Generic = 0,
+    /// Takes an integer value (good for menu selections).
+    Indexed = 1,
+    /// 0.0 means `false`, non-zero means `true`.
+    Boolean = 2,
+    /// Usually from 0 -> 100, sometimes -50 -> +50.
+    Percent = 3,
+    /// Absolute or relative time.
+    Seconds = 4,
+    /// One sample frame equals (1.0 / sample rate) seconds.
+    SampleFrames = 5,
+    /// -180 to 180 degrees.
+    Phase = 6,
+    /// Rate multiplier, for playback speed, etc. (e.g. 2.0 == twice as fast).
+    Rate = 7,
+    /// Absolute frequency/pitch in cycles/second.
+    Hertz = 8,
+    /// Unit of relative pitch.
+    Cents = 9,
+    /// Useful for coarse detuning.
+    RelativeSemiTones = 10,
+    /// Absolute pitch as defined in the MIDI spec (exact freq may depend on tuning table).
+    MidiNoteNumber = 11,
+    /// A generic MIDI controller value from 0 -> 127.
+    MidiController = 12,
+    /// Logarithmic relative gain.
+    Decibels = 13,
+    /// Linear relative gain.
+    LinearGain = 14,
+    /// -180 to 180 degrees, similar to phase but more general (good for 3D coord system).
+    Degrees = 15,
+    /// 0 -> 1, pow(x, 3.0) (used for crossfades).
+    EqualPowerCrossfade = 16,
+    /// 0 -> 1, pow(x, 3.0) (used for mixer faders).
+    MixerFaderCurve1 = 17,
+    /// Standard left to right mixer pan.
+    Pan = 18,
+    /// Distance measured in meters.
+    Meters = 19,
+    /// Absolute frequency measurement: if f is freq in hertz then
+    /// `absoluteCents = 1200 * log2(f / 440) + 6900`.
+    AbsoluteCents = 20,
+    /// Octaves in relative pitch where a value of 1 is equal to 1200 cents.
+    Octaves = 21,
+    /// Beats per minute, i.e. tempo.
+    Bpm = 22,
+    /// Time relative to tempo, i.e. 1.0 at 120 BPM would equal 1/2 a second.
+    Beats = 23,
+    /// Parameter is expressed in milliseconds.
+    Milliseconds = 24,
+    /// For compression, expansion ratio, etc.
+    Ratio = 25,
+    /// The unit is described by the `unit_name` of the **ParameterInfo**.
+    CustomUnit = 26,
+}
+
+impl ParameterUnit {
+    /// Create a **ParameterUnit** from its associated `u32`.
+    pub fn from_u32(u: u32) -> Option<ParameterUnit> {
+        let unit = match u {
+            0 => ParameterUnit::Generic,
+            1 => ParameterUnit::Indexed,
+            2 => ParameterUnit::Boolean,
+            3 => ParameterUnit::Percent,
+            4 => ParameterUnit::Seconds,
+            5 => ParameterUnit::SampleFrames,
+            6 => ParameterUnit::Phase,
+            7 => ParameterUnit::Rate,
+            8 => ParameterUnit::Hertz,
+            9 => ParameterUnit::Cents,
+            10 => ParameterUnit::RelativeSemiTones,
+            11 => ParameterUnit::MidiNoteNumber,
+            12 => ParameterUnit::MidiController,
+            13 => ParameterUnit::Decibels,
+            14 => ParameterUnit::LinearGain,
+            15 => ParameterUnit::Degrees,
+            16 => ParameterUnit::EqualPowerCrossfade,
+            17 => ParameterUnit::MixerFaderCurve1,
+            18 => ParameterUnit::Pan,
+            19 => ParameterUnit::Meters,
+            20 => ParameterUnit::AbsoluteCents,
+            21 => ParameterUnit::Octaves,
+            22 => ParameterUnit::Bpm,
+            23 => ParameterUnit::Beats,
+            24 => ParameterUnit::Milliseconds,
+            25 => ParameterUnit::Ratio,
+            26 => ParameterUnit::CustomUnit,
+            _ => return None,
+        };
+        Some(unit)
+    }
+}
+
+bitflags! {
+    /// Flags describing the capabilities and display hints of a parameter.
+    ///
+    /// Original documentation [here](https://developer.apple.com/documentation/audiotoolbox/audiounitparameteroptions).
+    pub struct ParameterFlags: u32 {
+        /// The host is responsible for releasing the `CFString` names of the parameter info.
+        const CF_NAME_RELEASE = 1 << 4;
+        /// The parameter should not be saved in presets.
+        const OMIT_FROM_PRESETS = 1 << 13;
+        /// The host may want to plot the parameter's history.
+        const PLOT_HISTORY = 1 << 14;
+        /// The parameter is a read-only meter.
+        const METER_READ_ONLY = 1 << 15;
+        /// Display the value as its square root.
+        const DISPLAY_SQUARE_ROOT = 1 << 16;
+        /// Display the value squared.
+        const DISPLAY_SQUARED = 2 << 16;
+        /// Display the value cubed.
+        const DISPLAY_CUBED = 3 << 16;
+        /// Display the value as its cube root.
+        const DISPLAY_CUBE_ROOT = 4 << 16;
+        /// Display the value exponentially.
+        const DISPLAY_EXPONENTIAL = 5 << 16;
+        /// The parameter belongs to the clump identified by `clump_id`.
+        const HAS_CLUMP = 1 << 20;
+        /// The parameter's values have associated string names.
+        const VALUES_HAVE_STRINGS = 1 << 21;
+        /// Display the value logarithmically.
+        const DISPLAY_LOGARITHMIC = 1 << 22;
+        /// The parameter benefits from high resolution control.
+        const IS_HIGH_RESOLUTION = 1 << 23;
+        /// Changing the parameter is not real-time safe.
+        const NON_REAL_TIME = 1 << 24;
+        /// The parameter may be ramped.
+        const CAN_RAMP = 1 << 25;
+        /// The parameter is intended for expert users.
+        const EXPERT_MODE = 1 << 26;
+        /// The parameter name is stored in the `cfNameString` field.
+        const HAS_CF_NAME_STRING = 1 << 27;
+        /// Changing the parameter may change the values of other global scope parameters.
+        const IS_GLOBAL_META = 1 << 28;
+        /// Changing the parameter may change the values of other parameters in the same element.
+        const IS_ELEMENT_META = 1 << 29;
+        /// The parameter may be read.
+        const IS_READABLE = 1 << 30;
+        /// The parameter may be written.
+        const IS_WRITABLE = 1 << 31;
+    }
+}
+
+/// A description of a single **AudioUnit** parameter.
+///
+/// A rust representation of the `AudioUnitParameterInfo` type.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParameterInfo {
+    /// The identifier of the parameter.
+    pub id: ParameterId,
+    /// The name of the parameter.
+    pub name: String,
+    /// The unit of measurement of the parameter, or `None` if it is not known to this crate.
+    pub unit: Option<ParameterUnit>,
+    /// The name of the unit if `unit` is `ParameterUnit::CustomUnit`.
+    pub unit_name: Option<String>,
+    /// The clump (group) the parameter belongs to, if any.
+    pub clump_id: Option<u32>,
+    /// The minimum value of the parameter.
+    pub min_value: f32,
+    /// The maximum value of the parameter.
+    pub max_value: f32,
+    /// The default value of the parameter.
+    pub default_value: f32,
+    /// The capabilities and display hints of the parameter.
+    pub flags: ParameterFlags,
+}
+
+impl ParameterInfo {
+    /// Convert an `AudioUnitParameterInfo` into a **ParameterInfo**.
+    ///
+    /// If the `CF_NAME_RELEASE` flag is set, the `CFString`s held by `info` are released.
+    ///
+    /// # Safety
+    ///
+    /// `info` must have been filled in by the `kAudioUnitProperty_ParameterInfo` property so that
+    /// its `CFString` fields are valid where the flags say they are.
+    unsafe fn from_sys(id: ParameterId, info: &sys::AudioUnitParameterInfo) -> Self {
+        let flags = ParameterFlags::from_bits_truncate(info.flags);
+        let unit = ParameterUnit::from_u32(info.unit);
+
+        let name = if flags.contains(ParameterFlags::HAS_CF_NAME_STRING) {
+            cf::string_from_cf_string(info.cfNameString as _)
+        } else {
+            None
+        };
+        let name = name.unwrap_or_else(|| {
+            // The name fills the whole array when it isn't NUL terminated, so don't read past it.
+            let bytes: Vec<u8> = info.name.iter().map(|&c| c as u8).collect();
+            let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+            String::from_utf8_lossy(&bytes[..len]).into_owned()
+        });
+
+        let unit_name = match unit {
+            Some(ParameterUnit::CustomUnit) => cf::string_from_cf_string(info.unitName as _),
+            _ => None,
+        };
+
+        if flags.contains(ParameterFlags::CF_NAME_RELEASE) {
+            if flags.contains(ParameterFlags::HAS_CF_NAME_STRING) {
+                cf::release(info.cfNameString);
+            }
+            if let Some(ParameterUnit::CustomUnit) = unit {
+                cf::release(info.unitName);
+            }
+        }
+
+        let clump_id = if flags.contains(ParameterFlags::HAS_CLUMP) {
+            Some(info.clumpID)
+        } else {
+            None
+        };
+
+        ParameterInfo {
+            id,
+            name,
+            unit,
+            unit_name,
+            clump_id,
+            min_value: info.minValue,
+            max_value: info.maxValue,
+            default_value: info.defaultValue,
+            flags,
+        }
+    }
+}
+
+/// The address of a parameter of an **AudioUnit**, e.g. for scheduling via
+/// `AudioUnit::apply_automation`.
+#[derive(Copy, Clone, Debug)]
+pub struct AudioUnitParameter {
+    /// The identifier of the parameter.
+    pub id: ParameterId,
+    /// The scope in which the parameter lives.
+    pub scope: Scope,
+    /// The raw element in which the parameter lives, e.g. the input bus of a mixer.
+    pub element: u32,
+}
+
+/// An iterator over the infos of the parameters within a scope, as returned by
+/// `AudioUnit::parameters`.
+pub struct Parameters<'a> {
+    audio_unit: &'a AudioUnit,
+    scope: Scope,
+    ids: std::vec::IntoIter<ParameterId>,
+}
+
+impl<'a> Iterator for Parameters<'a> {
+    type Item = Result<ParameterInfo, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let id = self.ids.next()?;
+        Some(self.audio_unit.parameter_info(self.scope, id))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.ids.size_hint()
+    }
+}
+
+impl<'a> ExactSizeIterator for Parameters<'a> {}
+
+/// The parameter infos cached by `AudioUnit::cached_parameter_infos`, shared with the listener
+/// invalidating them.
+pub(crate) type SharedParameterCache = Arc<Mutex<ParameterCache>>;
+
+/// The parameter infos cached by `AudioUnit::cached_parameter_infos`.
+#[derive(Default)]
+pub(crate) struct ParameterCache {
+    // Incremented each time the cache is invalidated.
+    generation: u64,
+    // The infos of each cached scope, keyed by the raw scope.
+    scopes: Vec<(c_uint, Vec<ParameterInfo>)>,
+}
+
+impl AudioUnit {
+    /// The identifiers of all parameters within the given scope.
+    ///
+    /// Parameters generally live on the `Global` scope, although mixers also expose per-bus
+    /// parameters on their `Input` and `Output` scopes.
+    pub fn parameter_list(&self, scope: Scope) -> Result<Vec<ParameterId>, Error> {
+        let id = sys::kAudioUnitProperty_ParameterList;
+        super::get_property_array(self.instance, id, scope, 0)
+    }
+
+    /// Describe the parameter with the given identifier within the given scope.
+    pub fn parameter_info(&self, scope: Scope, id: ParameterId) -> Result<ParameterInfo, Error> {
+        // The parameter info property is addressed using the parameter ID as its element.
+        let property_id = sys::kAudioUnitProperty_ParameterInfo;
+        let info: sys::AudioUnitParameterInfo =
+            super::get_property_raw(self.instance, property_id, scope, id)?;
+        Ok(unsafe { ParameterInfo::from_sys(id, &info) })
+    }
+
+    /// An iterator describing each parameter within the given scope in turn.
+    ///
+    /// The parameter list is fetched up front, while each parameter's info is only fetched as the
+    /// iterator reaches it, so hosts may stop early, e.g. when searching for a parameter by name.
+    pub fn parameters(&self, scope: Scope) -> Result<Parameters<'_>, Error> {
+        let ids = self.parameter_list(scope)?;
+        Ok(Parameters {
+            audio_unit: self,
+            scope,
+            ids: ids.into_iter(),
+        })
+    }
+
+    /// Describe every parameter within the given scope.
+    ///
+    /// This collects `parameters`, fetching the `parameter_info` for each entry, so it still
+    /// makes one call per parameter internally. Units with hundreds of parameters may want
+    /// to hold on to the result rather than calling this on every editor refresh.
+    pub fn all_parameter_infos(&self, scope: Scope) -> Result<Vec<ParameterInfo>, Error> {
+        self.parameters(scope)?.collect()
+    }
+
+    /// Describe every parameter within the given scope, caching the result.
+    ///
+    /// The first call for each scope enumerates the parameters as in `all_parameter_infos`, after
+    /// which the infos are returned from the cache. The cache is invalidated whenever the unit
+    /// notifies that its `ParameterList` has changed, as units with a dynamic parameter set do, so
+    /// the result stays correct while making repeated editor refreshes cheap.
+    ///
+    /// Changes to the info of a single parameter, e.g. its range, are only noticed if the unit
+    /// posts a `ParameterList` change for them too.
+    pub fn cached_parameter_infos(&mut self, scope: Scope) -> Result<Vec<ParameterInfo>, Error> {
+        let cache = match self.parameter_cache {
+            Some(ref cache) => cache.clone(),
+            None => {
+                let cache = Arc::new(Mutex::new(ParameterCache::default()));
+                let listener_cache = cache.clone();
+                self.add_raw_property_listener(property::PARAMETER_LIST, move |_au, _scope, _elem| {
+                    let mut cache = listener_cache
+                        .lock()
+                        .unwrap_or_else(|poisoned| poisoned.into_inner());
+                    cache.generation += 1;
+                    cache.scopes.clear();
+                })?;
+                self.parameter_cache = Some(cache.clone());
+                cache
+            }
+        };
+
+        let raw_scope = scope as c_uint;
+        let generation = {
+            let cache = cache.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            if let Some((_, infos)) = cache.scopes.iter().find(|(s, _)| *s == raw_scope) {
+                return Ok(infos.clone());
+            }
+            cache.generation
+        };
+
+        // Enumerate without holding the lock, so that the listener is never blocked on the unit.
+        let infos = self.all_parameter_infos(scope)?;
+        let mut cache = cache.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        // Only cache the infos if the parameter list didn't change while they were enumerated.
+        if cache.generation == generation {
+            cache.scopes.push((raw_scope, infos.clone()));
+        }
+        Ok(infos)
+    }
+
+    /// Set the value of the parameter with the given identifier on the given element.
+    ///
+    /// The element is a raw bus number, as parameters commonly live on the individual input buses
+    /// of mixers. `Global` parameters live on element `0`. The value is not checked against the
+    /// range described by the parameter's `parameter_info`.
+    pub fn set_parameter(
+        &mut self,
+        id: ParameterId,
+        scope: Scope,
+        element: u32,
+        value: f32,
+    ) -> Result<(), Error> {
+        set_parameter(self.instance, id, scope, element, value)
+    }
+
+    /// Set the value of the parameter as in `set_parameter`, clamped to the range described by
+    /// the parameter's `parameter_info`.
+    ///
+    /// The typed effect wrappers clamp through this, as some of their ranges depend on the sample
+    /// rate.
+    pub(crate) fn set_clamped_parameter(
+        &mut self,
+        id: ParameterId,
+        scope: Scope,
+        element: u32,
+        value: f32,
+    ) -> Result<(), Error> {
+        let info = self.parameter_info(scope, id)?;
+        // Unlike `clamp`, this doesn't panic on a NaN or a unit reporting an inverted range.
+        let value = value.max(info.min_value).min(info.max_value);
+        set_parameter(self.instance, id, scope, element, value)
+    }
+
+    /// The current value of the parameter with the given identifier on the given element.
+    pub fn get_parameter(&self, id: ParameterId, scope: Scope, element: u32) -> Result<f32, Error> {
+        get_parameter(self.instance, id, scope, element)
+    }
+}
+
+/// Set the value of a parameter on the given element.
+///
+/// The element is addressed by its raw `u32` value, as parameters commonly live on the input buses
+/// of mixers.
+pub(crate) fn set_parameter(
+    au: sys::AudioUnit,
+    id: ParameterId,
+    scope: Scope,
+    elem: u32,
+    value: f32,
+) -> Result<(), Error> {
+    let scope = scope as c_uint;
+    let status = unsafe { sys::AudioUnitSetParameter(au, id, scope, elem, value, 0) };
+    Error::from_os_status(status)
+}
+
+/// Get the value of a parameter on the given element.
+///
+/// The element is addressed by its raw `u32` value, as with `set_parameter`.
+pub(crate) fn get_parameter(
+    au: sys::AudioUnit,
+    id: ParameterId,
+    scope: Scope,
+    elem: u32,
+) -> Result<f32, Error> {
+    let scope = scope as c_uint;
+    let mut value = 0.0;
+    let status = unsafe { sys::AudioUnitGetParameter(au, id, scope, elem, &mut value) };
+    Error::from_os_status(status)?;
+    Ok(value)
+}
+
+#[test]
+fn test_parameter_info_name_without_nul() {
+    let mut info: sys::AudioUnitParameterInfo = unsafe { std::mem::zeroed() };
+    for c in info.name.iter_mut() {
+        *c = b'a' as _;
+    }
+    let info = unsafe { ParameterInfo::from_sys(0, &info) };
+    assert_eq!(info.name, "a".repeat(52));
+}
@@ -0,0 +1,234 @@
+//! Scheduling parameter changes from within a render callback.
+//!
+//! Changes are queued against the sample time of the render in which they were requested. A
+//! render notification then schedules the changes falling within each render via
+//! `AudioUnitScheduleParameters` immediately before the unit renders, each landing on its own
+//! frame. Changes further in the future stay queued for a later render.
+
+use super::automation::ParameterEvent;
+use super::parameter::AudioUnitParameter;
+use super::AudioUnit;
+use crate::error::Error;
+use std::os::raw::c_void;
+use std::sync::{Arc, Mutex};
+use sys;
+
+/// A parameter change waiting for the render in which it is due.
+#[derive(Copy, Clone)]
+struct PendingChange {
+    sample_time: f64,
+    parameter: AudioUnitParameter,
+    value: f32,
+}
+
+/// The queue of pending changes, shared between the render notification and the schedulers.
+pub(crate) struct ParameterQueue {
+    instance: sys::AudioUnit,
+    state: Mutex<QueueState>,
+}
+
+struct QueueState {
+    // Both are allocated up front so that the render thread never allocates.
+    pending: Vec<PendingChange>,
+    events: Vec<sys::AudioUnitParameterEvent>,
+}
+
+/// A handle for scheduling sample-accurate parameter changes on an **AudioUnit**, as returned by
+/// `AudioUnit::parameter_scheduler`.
+///
+/// Handles are cheap to clone and may be moved into the unit's render callback. Scheduling never
+/// blocks or allocates, so it is safe to do from the render thread.
+#[derive(Clone)]
+pub struct ParameterScheduler {
+    queue: Arc<ParameterQueue>,
+}
+
+impl QueueState {
+    fn with_capacity(capacity: usize) -> Self {
+        QueueState {
+            pending: Vec::with_capacity(capacity),
+            events: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Queue the given change, returning `false` if the queue is full.
+    fn push(&mut self, change: PendingChange) -> bool {
+        if self.pending.len() == self.pending.capacity() {
+            return false;
+        }
+        self.pending.push(change);
+        true
+    }
+
+    /// Move the changes due before the end of the render of `frames` frames starting at
+    /// `sample_time` into `events`, ordered by frame.
+    ///
+    /// Changes that were due before the render started are applied at its first frame.
+    fn take_due(&mut self, sample_time: f64, frames: u32) {
+        self.events.clear();
+        let end = sample_time + frames as f64;
+        // Insertion keeps the events ordered by frame without allocating, keeping the queued
+        // order of changes landing on the same frame so that later changes still win.
+        let QueueState { pending, events } = self;
+        pending.retain(|change| {
+            if change.sample_time >= end {
+                return true;
+            }
+            let offset = (change.sample_time - sample_time).max(0.0) as u32;
+            let value = change.value;
+            let event = ParameterEvent::Immediate { offset, value }.to_sys(change.parameter);
+            let index = events
+                .iter()
+                .rposition(|e| unsafe { e.eventValues.immediate.bufferOffset } <= offset)
+                .map_or(0, |i| i + 1);
+            events.insert(index, event);
+            false
+        });
+    }
+}
+
+impl ParameterScheduler {
+    /// Schedule `parameter` to be set to `value` at `frame` frames from the start of the render
+    /// with the given timestamp, typically the `time_stamp` of the render callback's `Args`.
+    ///
+    /// The change is applied during whichever later render covers that frame. As the render
+    /// callback is called while its render is already underway, changes landing within the
+    /// current render are applied at the start of the next one instead.
+    ///
+    /// Returns `false` if the change could not be queued, either because the queue is full or
+    /// because it is momentarily in use by another thread.
+    pub fn schedule(
+        &self,
+        time_stamp: &sys::AudioTimeStamp,
+        frame: u32,
+        parameter: AudioUnitParameter,
+        value: f32,
+    ) -> bool {
+        let change = PendingChange {
+            sample_time: time_stamp.mSampleTime + frame as f64,
+            parameter,
+            value,
+        };
+        match self.queue.state.try_lock() {
+            Ok(mut state) => state.push(change),
+            Err(_) => false,
+        }
+    }
+}
+
+// The instance is only used by the render notification to schedule the due changes.
+unsafe impl Send for ParameterQueue {}
+unsafe impl Sync for ParameterQueue {}
+
+impl AudioUnit {
+    /// A handle for scheduling sample-accurate parameter changes from the render callback.
+    ///
+    /// Up to `capacity` changes may be pending at once. The first call installs a render
+    /// notification on the unit with a queue of the given capacity; later calls return handles to
+    /// the same queue and ignore `capacity`.
+    ///
+    /// Whether a parameter supports being scheduled at a frame offset depends on the unit. Units
+    /// that don't apply the change at the start of the render instead.
+    pub fn parameter_scheduler(&mut self, capacity: usize) -> Result<ParameterScheduler, Error> {
+        if let Some(queue) = &self.parameter_queue {
+            return Ok(ParameterScheduler {
+                queue: queue.clone(),
+            });
+        }
+        let queue = Arc::new(ParameterQueue {
+            instance: self.instance,
+            state: Mutex::new(QueueState::with_capacity(capacity)),
+        });
+        let ptr = Arc::as_ptr(&queue) as *mut c_void;
+        let status = unsafe {
+            sys::AudioUnitAddRenderNotify(self.instance, Some(parameter_queue_notify), ptr)
+        };
+        Error::from_os_status(status)?;
+        self.parameter_queue = Some(queue.clone());
+        Ok(ParameterScheduler { queue })
+    }
+
+    /// Remove the render notification installed by `parameter_scheduler`, if any.
+    pub(crate) fn free_parameter_queue(&mut self) {
+        if let Some(queue) = self.parameter_queue.take() {
+            let ptr = Arc::as_ptr(&queue) as *mut c_void;
+            // We don't want to panic in `drop`, so we'll ignore returned errors.
+            let status = unsafe {
+                sys::AudioUnitRemoveRenderNotify(self.instance, Some(parameter_queue_notify), ptr)
+            };
+            Error::from_os_status(status).ok();
+        }
+    }
+}
+
+/// Render notification procedure scheduling the queued changes due within each render.
+unsafe extern "C" fn parameter_queue_notify(
+    in_ref_con: *mut c_void,
+    io_action_flags: *mut sys::AudioUnitRenderActionFlags,
+    in_time_stamp: *const sys::AudioTimeStamp,
+    _in_bus_number: sys::UInt32,
+    in_number_frames: sys::UInt32,
+    _io_data: *mut sys::AudioBufferList,
+) -> sys::OSStatus {
+    if *io_action_flags & sys::kAudioUnitRenderAction_PreRender == 0 || in_time_stamp.is_null() {
+        return 0;
+    }
+    let queue = &*(in_ref_con as *const ParameterQueue);
+    // Should a scheduler hold the lock, the due changes are applied in the next render instead.
+    let mut state = match queue.state.try_lock() {
+        Ok(state) => state,
+        Err(_) => return 0,
+    };
+    state.take_due((*in_time_stamp).mSampleTime, in_number_frames);
+    if state.events.is_empty() {
+        return 0;
+    }
+    sys::AudioUnitScheduleParameters(
+        queue.instance,
+        state.events.as_ptr(),
+        state.events.len() as u32,
+    )
+}
+
+#[test]
+fn test_queue_take_due() {
+    use super::Scope;
+
+    let parameter = AudioUnitParameter {
+        id: 0,
+        scope: Scope::Global,
+        element: 0,
+    };
+    let change = |sample_time, value| PendingChange {
+        sample_time,
+        parameter,
+        value,
+    };
+    let mut state = QueueState::with_capacity(4);
+    assert!(state.push(change(1100.0, 1.0)));
+    assert!(state.push(change(1030.0, 2.0)));
+    assert!(state.push(change(1000.0, 3.0)));
+    assert!(state.push(change(1030.0, 4.0)));
+    assert!(!state.push(change(2000.0, 5.0)));
+
+    // A render of 64 frames starting at 1010: the change at 1000 is late, the one at 1100 is not
+    // yet due.
+    state.take_due(1010.0, 64);
+    let events: Vec<(u32, f32)> = state
+        .events
+        .iter()
+        .map(|e| unsafe {
+            (
+                e.eventValues.immediate.bufferOffset,
+                e.eventValues.immediate.value,
+            )
+        })
+        .collect();
+    assert_eq!(events, vec![(0, 3.0), (20, 2.0), (20, 4.0)]);
+    assert_eq!(state.pending.len(), 1);
+
+    state.take_due(1074.0, 64);
+    let offset = unsafe { state.events[0].eventValues.immediate.bufferOffset };
+    assert_eq!((state.events.len(), offset), (1, 26));
+    assert!(state.pending.is_empty());
+}
@@ -0,0 +1,61 @@
+//! A typed wrapper around the peak limiter **AudioUnit**.
+//!
+//! The limiter keeps peaks from exceeding full scale, making it a common final stage before the
+//! output to prevent clipping.
+//!
+//! Find the original peak limiter parameter documentation [here](https://developer.apple.com/documentation/audiotoolbox/1534192-peak_limiter_parameters).
+
+use super::{EffectType, ParameterId};
+use crate::error::Error;
+
+// The `kLimiterParam_*` parameters, all of which live in the `Global` scope.
+const ATTACK_TIME: ParameterId = 0;
+const DECAY_TIME: ParameterId = 1;
+const PRE_GAIN: ParameterId = 2;
+
+unit_wrapper!(
+    /// A peak limiter **AudioUnit** with typed setters for its parameters.
+    PeakLimiter,
+    EffectType::PeakLimiter,
+    "peak limiter"
+);
+
+impl PeakLimiter {
+    /// Set the time taken to respond to a peak in seconds, within `0.001..=0.03`.
+    pub fn set_attack_time(&mut self, seconds: f32) -> Result<(), Error> {
+        self.set_clamped_parameter(ATTACK_TIME, seconds)
+    }
+
+    /// Set the time taken to recover once a peak has passed in seconds, within `0.001..=0.06`.
+    pub fn set_decay_time(&mut self, seconds: f32) -> Result<(), Error> {
+        self.set_clamped_parameter(DECAY_TIME, seconds)
+    }
+
+    /// Set the gain applied to the input before limiting in dB, within `-40.0..=40.0`.
+    ///
+    /// Raising the pre-gain makes the signal louder while the limiter holds its peaks in check.
+    pub fn set_pre_gain(&mut self, db: f32) -> Result<(), Error> {
+        self.set_clamped_parameter(PRE_GAIN, db)
+    }
+}
+
+#[cfg(target_os = "macos")]
+#[test]
+fn test_peak_limiter_bounds_hot_signal() {
+    let mut limiter = PeakLimiter::new().unwrap();
+    limiter.set_pre_gain(0.0).unwrap();
+    let format = limiter
+        .audio_unit()
+        .stream_format(super::Scope::Input, super::Element::Output)
+        .unwrap();
+    // A square wave at four times full scale.
+    let hot: Vec<f32> = (0..8192)
+        .map(|i| if (i / 64) % 2 == 0 { 4.0 } else { -4.0 })
+        .collect();
+    let input = vec![&hot[..]; format.channels as usize];
+    let output = limiter.audio_unit_mut().probe_render(&input).unwrap();
+    // Allow the attack time for the limiter to catch the first peak.
+    for channel in &output {
+        assert!(channel[1024..].iter().all(|sample| sample.abs() <= 1.0));
+    }
+}
@@ -0,0 +1,265 @@
+//! Saving and restoring the full state of an **AudioUnit**.
+//!
+//! The state of an audio unit (its parameter values along with any other internal state) is
+//! exposed via the `kAudioUnitProperty_ClassInfo` property as a Core Foundation property list.
+//! **PresetData** owns such a property list and can convert it to and from bytes (an XML property
+//! list) so that it may be persisted without Core Foundation types leaking into user code. The
+//! XML form is the format of the `.aupreset` files used by other hosts, so presets may be shared
+//! with them.
+//!
+//! Find the original ClassInfo documentation in the Audio Unit Properties reference [here](https://developer.apple.com/library/ios/documentation/AudioUnit/Reference/AudioUnitPropertiesReference/index.html#//apple_ref/doc/uid/TP40007288).
+
+use super::{cf, property, AudioUnit, Scope};
+use crate::error::{AudioUnitError, Error};
+use core_foundation_sys::array::{CFArrayGetCount, CFArrayGetValueAtIndex, CFArrayRef};
+use core_foundation_sys::base::{kCFAllocatorDefault, CFIndex, CFRetain};
+use core_foundation_sys::data::{CFDataCreate, CFDataGetBytePtr, CFDataGetLength};
+use core_foundation_sys::propertylist::{
+    kCFPropertyListImmutable, kCFPropertyListXMLFormat_v1_0, CFPropertyListCreateData,
+    CFPropertyListCreateWithData, CFPropertyListRef,
+};
+use std::fs;
+use std::path::Path;
+use std::ptr;
+use std::slice;
+use sys;
+
+/// A preset built into an **AudioUnit** by its manufacturer, as listed by
+/// `AudioUnit::factory_presets`, or the user preset returned by `AudioUnit::present_preset`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FactoryPreset {
+    /// The number identifying the preset within the unit.
+    pub number: i32,
+    /// The name of the preset.
+    pub name: String,
+}
+
+/// The saved state of an **AudioUnit**, as returned by `AudioUnit::class_info`.
+pub struct PresetData {
+    // An owned (retained) reference to the ClassInfo property list.
+    plist: CFPropertyListRef,
+}
+
+impl PresetData {
+    /// Take ownership of an already retained property list.
+    ///
+    /// Returns `Error::InvalidPresetData` if `plist` is null.
+    unsafe fn from_retained(plist: CFPropertyListRef) -> Result<PresetData, Error> {
+        if plist.is_null() {
+            return Err(Error::InvalidPresetData);
+        }
+        Ok(PresetData { plist })
+    }
+
+    /// Serialize the preset to the bytes of an XML property list.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        unsafe {
+            let data = CFPropertyListCreateData(
+                kCFAllocatorDefault,
+                self.plist,
+                kCFPropertyListXMLFormat_v1_0,
+                0,
+                ptr::null_mut(),
+            );
+            if data.is_null() {
+                return Err(Error::InvalidPresetData);
+            }
+            let len = CFDataGetLength(data) as usize;
+            let bytes = match len {
+                0 => Vec::new(),
+                _ => slice::from_raw_parts(CFDataGetBytePtr(data), len).to_vec(),
+            };
+            cf::release(data);
+            Ok(bytes)
+        }
+    }
+
+    /// Deserialize a preset from the bytes of a property list, e.g. those produced by `to_bytes`.
+    ///
+    /// Both the XML and binary property list formats are accepted.
+    pub fn from_bytes(bytes: &[u8]) -> Result<PresetData, Error> {
+        unsafe {
+            let data = CFDataCreate(kCFAllocatorDefault, bytes.as_ptr(), bytes.len() as CFIndex);
+            if data.is_null() {
+                return Err(Error::InvalidPresetData);
+            }
+            let plist = CFPropertyListCreateWithData(
+                kCFAllocatorDefault,
+                data,
+                kCFPropertyListImmutable,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            cf::release(data);
+            PresetData::from_retained(plist)
+        }
+    }
+
+    /// Read a preset from a property list file, such as an `.aupreset` file saved by `save`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<PresetData, Error> {
+        let bytes = fs::read(path)?;
+        PresetData::from_bytes(&bytes)
+    }
+
+    /// Write the preset to the given path as an XML property list, the format of `.aupreset`
+    /// files.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let bytes = self.to_bytes()?;
+        fs::write(path, bytes)?;
+        Ok(())
+    }
+}
+
+impl Clone for PresetData {
+    fn clone(&self) -> Self {
+        unsafe {
+            CFRetain(self.plist);
+        }
+        PresetData { plist: self.plist }
+    }
+}
+
+impl Drop for PresetData {
+    fn drop(&mut self) {
+        unsafe {
+            cf::release(self.plist);
+        }
+    }
+}
+
+// Core Foundation reference counting is thread safe and the property list is never mutated through a
+// **PresetData**, so it is safe to share between threads.
+unsafe impl Send for PresetData {}
+unsafe impl Sync for PresetData {}
+
+impl AudioUnit {
+    /// Capture the full state of the **AudioUnit**.
+    pub fn class_info(&self) -> Result<PresetData, Error> {
+        let id = property::CLASS_INFO;
+        // The caller is responsible for releasing the returned property list.
+        let plist: CFPropertyListRef =
+            super::get_property_raw(self.instance, id, Scope::Global, 0)?;
+        unsafe { PresetData::from_retained(plist) }
+    }
+
+    /// Restore the state of the **AudioUnit** from a previously captured **PresetData**.
+    pub fn set_class_info(&mut self, preset: &PresetData) -> Result<(), Error> {
+        let id = property::CLASS_INFO;
+        super::set_property_raw(self.instance, id, Scope::Global, 0, Some(&preset.plist))
+    }
+
+    /// Save the full state of the **AudioUnit** to an `.aupreset` file at the given path.
+    pub fn save_preset<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        self.class_info()?.save(path)
+    }
+
+    /// Restore the state of the **AudioUnit** from an `.aupreset` file at the given path.
+    ///
+    /// Units reject presets saved by a different kind of unit with an `InvalidPropertyValue`
+    /// error.
+    pub fn load_preset<P: AsRef<Path>>(&mut self, path: P) -> Result<(), Error> {
+        let preset = PresetData::load(path)?;
+        self.set_class_info(&preset)
+    }
+
+    /// Load the unit's first factory preset, if it has any, returning the unit.
+    ///
+    /// Some units start in an unconfigured state, for which the first factory preset provides
+    /// sensible defaults. Units without factory presets are returned unchanged, e.g.
+    /// `AudioUnit::new(ty)?.with_default_preset()?`.
+    pub fn with_default_preset(mut self) -> Result<AudioUnit, Error> {
+        if let Some(preset) = self.factory_presets()?.first() {
+            self.set_factory_preset(preset.number)?;
+        }
+        Ok(self)
+    }
+
+    /// The factory presets of the **AudioUnit**, empty for units without any.
+    pub fn factory_presets(&self) -> Result<Vec<FactoryPreset>, Error> {
+        self.with_factory_presets(|presets| {
+            presets
+                .iter()
+                .map(|preset| FactoryPreset {
+                    number: preset.presetNumber,
+                    name: unsafe { cf::string_from_cf_string(preset.presetName as _) }
+                        .unwrap_or_default(),
+                })
+                .collect()
+        })
+    }
+
+    /// Load the factory preset with the given number, as listed by `factory_presets`.
+    ///
+    /// Returns `Error::AudioUnit(InvalidPropertyValue)` if the unit has no such preset.
+    pub fn set_factory_preset(&mut self, number: i32) -> Result<(), Error> {
+        let instance = self.instance;
+        self.with_factory_presets(|presets| {
+            let preset = presets
+                .iter()
+                .find(|preset| preset.presetNumber == number)
+                .ok_or(Error::AudioUnit(AudioUnitError::InvalidPropertyValue))?;
+            let id = property::PRESENT_PRESET;
+            super::set_property_raw(instance, id, Scope::Global, 0, Some(*preset))
+        })?
+    }
+
+    /// The preset the **AudioUnit**'s current state was loaded from or labelled as, either via
+    /// `set_factory_preset` or `set_present_preset_named`.
+    ///
+    /// User presets have a negative preset number, typically `-1`.
+    pub fn present_preset(&self) -> Result<FactoryPreset, Error> {
+        let id = property::PRESENT_PRESET;
+        let preset: sys::AUPreset = super::get_property_raw(self.instance, id, Scope::Global, 0)?;
+        // The caller is responsible for releasing the returned name.
+        let name = unsafe { cf::string_from_cf_string(preset.presetName as _) };
+        unsafe { cf::release(preset.presetName) };
+        Ok(FactoryPreset {
+            number: preset.presetNumber,
+            name: name.unwrap_or_default(),
+        })
+    }
+
+    /// Call `f` with the unit's factory presets, which are only valid for the duration of the call.
+    fn with_factory_presets<F, R>(&self, f: F) -> Result<R, Error>
+    where
+        F: FnOnce(&[&sys::AUPreset]) -> R,
+    {
+        let id = property::FACTORY_PRESETS;
+        // The caller is responsible for releasing the returned array.
+        let array = super::get_property_raw::<CFArrayRef>(self.instance, id, Scope::Global, 0);
+        let array = match array {
+            Ok(array) => array,
+            Err(Error::AudioUnit(AudioUnitError::InvalidProperty)) => return Ok(f(&[])),
+            Err(err) => return Err(err),
+        };
+        // The array holds pointers to `AUPreset`s rather than Core Foundation objects.
+        let presets: Vec<&sys::AUPreset> = unsafe {
+            (0..CFArrayGetCount(array))
+                .map(|i| &*(CFArrayGetValueAtIndex(array, i) as *const sys::AUPreset))
+                .collect()
+        };
+        let result = f(&presets);
+        unsafe { cf::release(array) };
+        Ok(result)
+    }
+
+    /// Mark the **AudioUnit**'s current state as a user preset with the given name.
+    ///
+    /// This sets the present preset to an `AUPreset` with a preset number of `-1`, which hosts use
+    /// to label the current user preset (as opposed to one of the unit's factory presets).
+    pub fn set_present_preset_named(&mut self, name: &str) -> Result<(), Error> {
+        let preset_name = cf::cf_string_from_str(name);
+        if preset_name.is_null() {
+            return Err(Error::Unspecified);
+        }
+        let preset = sys::AUPreset {
+            presetNumber: -1,
+            presetName: preset_name as _,
+        };
+        let id = property::PRESENT_PRESET;
+        let result = super::set_property_raw(self.instance, id, Scope::Global, 0, Some(&preset));
+        // The unit retains the name if it needs to keep it.
+        unsafe { cf::release(preset_name) };
+        result
+    }
+}
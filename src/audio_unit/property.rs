@@ -0,0 +1,116 @@
+//! Named identifiers for the commonly used **AudioUnit** properties.
+//!
+//! These may be passed to the generic [**AudioUnit::get_property**](../struct.AudioUnit.html#method.get_property)
+//! and [**AudioUnit::set_property**](../struct.AudioUnit.html#method.set_property) methods in
+//! place of the raw `sys` constants.
+//!
+//! The properties are grouped by the **Scope**s on which they are applicable. The type of each
+//! property's value is noted alongside it.
+//!
+//! Find the original Audio Unit Properties reference [here](https://developer.apple.com/library/ios/documentation/AudioUnit/Reference/AudioUnitPropertiesReference/index.html#//apple_ref/doc/uid/TP40007288)
+//! and the I/O unit properties [here](https://developer.apple.com/documentation/audiotoolbox/1534116-i_o_audio_unit_properties).
+
+use sys;
+
+/// The identifier of an **AudioUnit** property.
+pub type PropertyId = u32;
+
+// Properties applicable to the `Global` scope.
+
+/// The preset or state of the unit as a `CFPropertyListRef`.
+pub const CLASS_INFO: PropertyId = sys::kAudioUnitProperty_ClassInfo;
+/// The CPU load limit of the unit as an `f64` between `0.0` and `1.0`.
+pub const CPU_LOAD: PropertyId = sys::kAudioUnitProperty_CPULoad;
+/// The processing latency of the unit in seconds as an `f64`.
+pub const LATENCY: PropertyId = sys::kAudioUnitProperty_Latency;
+/// The channel configurations supported by the unit as an array of `AUChannelInfo`.
+pub const SUPPORTED_NUM_CHANNELS: PropertyId = sys::kAudioUnitProperty_SupportedNumChannels;
+/// The maximum number of frames the unit will be asked to render in a single call as a `u32`.
+pub const MAXIMUM_FRAMES_PER_SLICE: PropertyId = sys::kAudioUnitProperty_MaximumFramesPerSlice;
+/// The time in seconds for the unit's output to decay to silence after its input stops, as an
+/// `f64`.
+pub const TAIL_TIME: PropertyId = sys::kAudioUnitProperty_TailTime;
+/// Whether or not an effect unit is bypassed, as a `u32` where non-zero means bypassed.
+pub const BYPASS_EFFECT: PropertyId = sys::kAudioUnitProperty_BypassEffect;
+/// The `OSStatus` of the most recent render error, as an `i32`.
+pub const LAST_RENDER_ERROR: PropertyId = sys::kAudioUnitProperty_LastRenderError;
+/// The factory presets of the unit as a `CFArrayRef` of `AUPreset`s.
+pub const FACTORY_PRESETS: PropertyId = sys::kAudioUnitProperty_FactoryPresets;
+/// The render quality of the unit as a `u32` between `0` (minimum) and `127` (maximum).
+pub const RENDER_QUALITY: PropertyId = sys::kAudioUnitProperty_RenderQuality;
+/// Whether or not the unit processes in place, as a `u32` where non-zero means in place.
+pub const IN_PLACE_PROCESSING: PropertyId = sys::kAudioUnitProperty_InPlaceProcessing;
+/// The currently loaded preset of the unit as an `AUPreset`.
+pub const PRESENT_PRESET: PropertyId = sys::kAudioUnitProperty_PresentPreset;
+/// Whether or not the unit is being used for offline rendering, as a `u32`.
+pub const OFFLINE_RENDER: PropertyId = sys::kAudioUnitProperty_OfflineRender;
+/// A user-facing name of the unit instance as a `CFStringRef`.
+pub const NICK_NAME: PropertyId = sys::kAudioUnitProperty_NickName;
+/// The callbacks through which the unit queries the host's tempo, beat and transport state, as a
+/// `HostCallbackInfo`.
+pub const HOST_CALLBACKS: PropertyId = sys::kAudioUnitProperty_HostCallbacks;
+/// A callback describing which input samples are represented in the unit's output, as an
+/// `AUInputSamplesInOutputCallbackStruct`.
+pub const INPUT_SAMPLES_IN_OUTPUT: PropertyId = sys::kAudioUnitProperty_InputSamplesInOutput;
+
+// Properties applicable to the `Input` and `Output` scopes.
+
+/// The sample rate of the element in hertz as an `f64`.
+pub const SAMPLE_RATE: PropertyId = sys::kAudioUnitProperty_SampleRate;
+/// The stream format of the element as an `AudioStreamBasicDescription`.
+pub const STREAM_FORMAT: PropertyId = sys::kAudioUnitProperty_StreamFormat;
+/// The channel layout of the element as an `AudioChannelLayout`.
+pub const AUDIO_CHANNEL_LAYOUT: PropertyId = sys::kAudioUnitProperty_AudioChannelLayout;
+/// The channel layout tags supported by the element as an array of `AudioChannelLayoutTag`.
+pub const SUPPORTED_CHANNEL_LAYOUT_TAGS: PropertyId =
+    sys::kAudioUnitProperty_SupportedChannelLayoutTags;
+/// Whether or not the unit should allocate its own buffers for the element, as a `u32`.
+pub const SHOULD_ALLOCATE_BUFFER: PropertyId = sys::kAudioUnitProperty_ShouldAllocateBuffer;
+/// The name of the element as a `CFStringRef`.
+pub const ELEMENT_NAME: PropertyId = sys::kAudioUnitProperty_ElementName;
+
+// Properties applicable to any scope.
+
+/// The number of elements in the scope as a `u32`.
+pub const ELEMENT_COUNT: PropertyId = sys::kAudioUnitProperty_ElementCount;
+/// The identifiers of the parameters within the scope as an array of `ParameterId`s.
+pub const PARAMETER_LIST: PropertyId = sys::kAudioUnitProperty_ParameterList;
+/// A description of a parameter as an `AudioUnitParameterInfo`, addressed by using the parameter
+/// ID as the element.
+pub const PARAMETER_INFO: PropertyId = sys::kAudioUnitProperty_ParameterInfo;
+/// The names of the values of an indexed parameter as a `CFArrayRef` of `CFStringRef`s.
+pub const PARAMETER_VALUE_STRINGS: PropertyId = sys::kAudioUnitProperty_ParameterValueStrings;
+
+// Properties applicable to the `Input` scope.
+
+/// Connects the output of another unit to an input element, as an `AudioUnitConnection`.
+pub const MAKE_CONNECTION: PropertyId = sys::kAudioUnitProperty_MakeConnection;
+/// The callback used to supply input to an element as an `AURenderCallbackStruct`.
+pub const SET_RENDER_CALLBACK: PropertyId = sys::kAudioUnitProperty_SetRenderCallback;
+
+// Properties of I/O units, applicable to the `Global` scope unless noted otherwise.
+
+/// The device the I/O unit is bound to as an `AudioDeviceID`.
+#[cfg(target_os = "macos")]
+pub const CURRENT_DEVICE: PropertyId = sys::kAudioOutputUnitProperty_CurrentDevice;
+/// Whether or not the I/O unit is running, as a `u32`.
+pub const IS_RUNNING: PropertyId = sys::kAudioOutputUnitProperty_IsRunning;
+/// The mapping of device channels to the I/O unit's channels as an array of `i32`s.
+///
+/// Applicable to the `Input` and `Output` scopes.
+pub const CHANNEL_MAP: PropertyId = sys::kAudioOutputUnitProperty_ChannelMap;
+/// Whether or not input or output is enabled on an element, as a `u32`.
+///
+/// Applicable to the `Input` scope of the `Input` element and the `Output` scope of the `Output`
+/// element.
+pub const ENABLE_IO: PropertyId = sys::kAudioOutputUnitProperty_EnableIO;
+/// Whether or not the I/O unit has input or output on an element, as a `u32`.
+///
+/// Applicable to the `Input` scope of the `Input` element and the `Output` scope of the `Output`
+/// element.
+pub const HAS_IO: PropertyId = sys::kAudioOutputUnitProperty_HasIO;
+/// The callback notified when input is available as an `AURenderCallbackStruct`.
+pub const SET_INPUT_CALLBACK: PropertyId = sys::kAudioOutputUnitProperty_SetInputCallback;
+/// Whether or not the I/O unit's timestamps start at zero, as a `u32`.
+pub const START_TIMESTAMPS_AT_ZERO: PropertyId =
+    sys::kAudioOutputUnitProperty_StartTimestampsAtZero;
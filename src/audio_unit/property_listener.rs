@@ -0,0 +1,337 @@
+//! Listening for changes to the properties of an **AudioUnit**.
+//!
+//! Property listeners are called by Core Audio whenever the value of the property they were
+//! registered for changes, e.g. when the sample rate of the device underlying an I/O unit is
+//! changed by another application. Note that listeners may be called on any thread.
+
+use super::{property, AudioUnit, Element, PropertyId, Scope};
+use crate::error::Error;
+use std::os::raw::{c_uint, c_void};
+use std::sync::{Arc, Mutex};
+use sys;
+
+/// Property changes that have occurred since they were last drained, as raw `(id, scope, element)`
+/// triples without duplicates.
+pub(crate) type PropertyChanges = Arc<Mutex<Vec<(PropertyId, c_uint, c_uint)>>>;
+
+/// A boxed property listener along with the property it was registered for.
+pub(crate) struct PropertyListener {
+    id: PropertyId,
+    callback: Box<dyn FnMut(sys::AudioUnit, c_uint, c_uint) + Send>,
+}
+
+/// Identifies a listener registered via `AudioUnit::add_property_listener`, so that it may later
+/// be removed via `AudioUnit::remove_property_listener`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PropertyListenerId(usize);
+
+impl AudioUnit {
+    /// Register a callback that is called with the new sample rate whenever the sample rate of
+    /// the **AudioUnit**'s output changes.
+    ///
+    /// This is the rate reported by `actual_sample_rate`. For I/O units bound to a device this
+    /// changes when the device's nominal sample rate changes mid-session, e.g. due to a clock
+    /// source switch. Oscillators, resamplers and timers driven by the render callback should be
+    /// updated accordingly.
+    ///
+    /// Sample rate changes are reported by the unit as changes to its stream format, so the
+    /// callback is only called when the rate actually differs from the last one observed.
+    ///
+    /// The callback may be called on any thread. It remains registered until the **AudioUnit**
+    /// is dropped.
+    pub fn on_sample_rate_change<F>(&mut self, mut f: F) -> Result<(), Error>
+    where
+        F: FnMut(f64) + Send + 'static,
+    {
+        let mut last_sample_rate = self.actual_sample_rate().ok();
+        self.add_raw_property_listener(property::STREAM_FORMAT, move |au, scope, elem| {
+            if scope != Scope::Output as c_uint || elem != Element::Output as c_uint {
+                return;
+            }
+            let id = property::STREAM_FORMAT;
+            let asbd: sys::AudioStreamBasicDescription =
+                match super::get_property(au, id, Scope::Output, Element::Output) {
+                    Ok(asbd) => asbd,
+                    Err(_) => return,
+                };
+            if last_sample_rate != Some(asbd.mSampleRate) {
+                last_sample_rate = Some(asbd.mSampleRate);
+                f(asbd.mSampleRate);
+            }
+        })?;
+        Ok(())
+    }
+
+    /// Register a callback that is called with the new latency in seconds whenever the latency of
+    /// the **AudioUnit** changes.
+    ///
+    /// The latency of some units depends on their parameters, e.g. whether a limiter's look-ahead
+    /// is enabled, so hosts should update their plugin delay compensation from this callback.
+    ///
+    /// The callback may be called on any thread. It remains registered until the **AudioUnit**
+    /// is dropped.
+    pub fn on_latency_change<F>(&mut self, mut f: F) -> Result<(), Error>
+    where
+        F: FnMut(f64) + Send + 'static,
+    {
+        self.add_raw_property_listener(property::LATENCY, move |au, _scope, _elem| {
+            let id = property::LATENCY;
+            if let Ok(latency) = super::get_property(au, id, Scope::Global, Element::Output) {
+                f(latency);
+            }
+        })?;
+        Ok(())
+    }
+
+    /// The error returned by the **AudioUnit**'s most recent failed render, if any.
+    ///
+    /// Errors on the render thread, e.g. `AudioUnitError::CannotDoInCurrentContext`, can't be
+    /// returned to the application directly, so the unit records them for polling from another
+    /// thread. See `on_render_error` to be notified instead.
+    pub fn last_render_error(&self) -> Result<Option<Error>, Error> {
+        let id = property::LAST_RENDER_ERROR;
+        let status: sys::OSStatus = self.get_property(id, Scope::Global, Element::Output)?;
+        Ok(Error::from_os_status(status).err())
+    }
+
+    /// Register a callback that is called with the error whenever a render of the **AudioUnit**
+    /// fails.
+    ///
+    /// Units typically report the error from the render thread itself, so the callback should
+    /// hand the error off to another thread, e.g. via a channel, rather than reacting to it
+    /// directly. It remains registered until the **AudioUnit** is dropped.
+    pub fn on_render_error<F>(&mut self, mut f: F) -> Result<(), Error>
+    where
+        F: FnMut(Error) + Send + 'static,
+    {
+        self.add_raw_property_listener(property::LAST_RENDER_ERROR, move |au, _scope, _elem| {
+            let id = property::LAST_RENDER_ERROR;
+            let status: Result<sys::OSStatus, _> =
+                super::get_property(au, id, Scope::Global, Element::Output);
+            if let Ok(Err(err)) = status.map(Error::from_os_status) {
+                f(err);
+            }
+        })?;
+        Ok(())
+    }
+
+    /// Whether or not the inter-app audio unit is currently connected to its counterpart in
+    /// another app, e.g. a remote instrument connected to its host.
+    #[cfg(target_os = "ios")]
+    pub fn is_inter_app_connected(&self) -> Result<bool, Error> {
+        let id = sys::kAudioUnitProperty_IsInterAppConnected;
+        let connected: u32 = self.get_property(id, Scope::Global, Element::Output)?;
+        Ok(connected != 0)
+    }
+
+    /// Register a callback that is called with the new connection state whenever the inter-app
+    /// audio unit connects to or disconnects from its counterpart in another app.
+    ///
+    /// Apps may use this to show their connection status and to route audio to or from the
+    /// connected app. The callback may be called on any thread. It remains registered until the
+    /// **AudioUnit** is dropped.
+    #[cfg(target_os = "ios")]
+    pub fn on_inter_app_connection_change<F>(&mut self, mut f: F) -> Result<(), Error>
+    where
+        F: FnMut(bool) + Send + 'static,
+    {
+        let id = sys::kAudioUnitProperty_IsInterAppConnected;
+        self.add_raw_property_listener(id, move |au, _scope, _elem| {
+            let id = sys::kAudioUnitProperty_IsInterAppConnected;
+            let connected = super::get_property::<u32, _>(au, id, Scope::Global, Element::Output);
+            if let Ok(connected) = connected {
+                f(connected != 0);
+            }
+        })?;
+        Ok(())
+    }
+
+    /// Record changes to the property with the given ID, to be collected via
+    /// `drain_property_changes`.
+    ///
+    /// Rather than handling each notification as it arrives, changes are accumulated until they
+    /// are drained, with repeated changes to the same property, scope and element recorded only
+    /// once. This suits UIs which poll for changes at their refresh rate, as a rapid sweep of a
+    /// property results in a single redraw rather than one per notification.
+    pub fn coalesce_property_changes(&mut self, id: PropertyId) -> Result<(), Error> {
+        let changes = self
+            .property_changes
+            .get_or_insert_with(|| Arc::new(Mutex::new(Vec::new())))
+            .clone();
+        self.add_raw_property_listener(id, move |_au, scope, elem| {
+            let mut changes = changes
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            let change = (id, scope, elem);
+            if !changes.contains(&change) {
+                changes.push(change);
+            }
+        })?;
+        Ok(())
+    }
+
+    /// Take all changes recorded since the last call for the properties registered via
+    /// `coalesce_property_changes`, in the order they first occurred.
+    ///
+    /// Each change is given as the property ID, scope and raw element of the change.
+    pub fn drain_property_changes(&self) -> Vec<(PropertyId, Scope, u32)> {
+        let changes = match self.property_changes {
+            Some(ref changes) => changes,
+            None => return Vec::new(),
+        };
+        let mut changes = changes
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        changes
+            .drain(..)
+            .filter_map(|(id, scope, elem)| Scope::from_u32(scope).map(|scope| (id, scope, elem)))
+            .collect()
+    }
+
+    /// Register a callback that is called whenever the property with the given ID changes on any
+    /// scope or element, e.g. `StreamFormat` or `MaximumFramesPerSlice`.
+    ///
+    /// The callback is called with the scope and raw element of the change, and should re-read
+    /// the property if it needs the new value. It may be called on any thread. It remains
+    /// registered until it is removed via `remove_property_listener` with the returned
+    /// **PropertyListenerId**, or until the **AudioUnit** is dropped.
+    pub fn add_property_listener<F>(
+        &mut self,
+        id: PropertyId,
+        mut f: F,
+    ) -> Result<PropertyListenerId, Error>
+    where
+        F: FnMut(Scope, u32) + Send + 'static,
+    {
+        self.add_raw_property_listener(id, move |_au, scope, elem| {
+            if let Some(scope) = Scope::from_u32(scope) {
+                f(scope, elem);
+            }
+        })
+    }
+
+    /// Register a callback that is called whenever the property with the given ID changes within
+    /// the given scope and element, such as the stream format of a single mixer input bus.
+    ///
+    /// Core Audio notifies of a change to a property on any scope or element, so changes to other
+    /// scopes and elements are filtered out. Otherwise this is the same as
+    /// `add_property_listener`, and the listener may be removed via `remove_property_listener`.
+    pub fn add_property_listener_for<F>(
+        &mut self,
+        id: PropertyId,
+        scope: Scope,
+        elem: u32,
+        mut f: F,
+    ) -> Result<PropertyListenerId, Error>
+    where
+        F: FnMut(Scope, u32) + Send + 'static,
+    {
+        self.add_raw_property_listener(id, move |_au, changed_scope, changed_elem| {
+            if changed_scope == scope as c_uint && changed_elem == elem {
+                f(scope, elem);
+            }
+        })
+    }
+
+    /// Unregister and drop the listener registered via `add_property_listener` or
+    /// `add_property_listener_for`.
+    ///
+    /// Removing a listener that has already been removed has no effect.
+    pub fn remove_property_listener(&mut self, listener: PropertyListenerId) -> Result<(), Error> {
+        let index = self
+            .property_listeners
+            .iter()
+            .position(|&listener_ptr| listener_ptr as usize == listener.0);
+        let listener_ptr = match index {
+            Some(index) => self.property_listeners.remove(index),
+            None => return Ok(()),
+        };
+        unsafe {
+            if let Err(err) = remove_property_listener(self.instance, listener_ptr) {
+                // The unit may still call the listener, so keep it alive until the unit is dropped.
+                self.property_listeners.push(listener_ptr);
+                return Err(err);
+            }
+            drop(Box::from_raw(listener_ptr));
+        }
+        Ok(())
+    }
+
+    /// Register a listener for changes to the property with the given ID.
+    ///
+    /// The listener is called with the raw audio unit, scope and element of the change.
+    pub(crate) fn add_raw_property_listener<F>(
+        &mut self,
+        id: PropertyId,
+        f: F,
+    ) -> Result<PropertyListenerId, Error>
+    where
+        F: FnMut(sys::AudioUnit, c_uint, c_uint) + Send + 'static,
+    {
+        let listener = Box::new(PropertyListener {
+            id,
+            callback: Box::new(f),
+        });
+
+        // Relinquish ownership of the listener so that it can be passed to Core Audio as the user
+        // data. The pointer is converted back into a `Box` once the listener is removed, either in
+        // `remove_property_listener` or `free_property_listeners`.
+        let listener_ptr = Box::into_raw(listener);
+        let status = unsafe {
+            sys::AudioUnitAddPropertyListener(
+                self.instance,
+                id,
+                Some(property_listener_proc),
+                listener_ptr as *mut c_void,
+            )
+        };
+        if let Err(err) = Error::from_os_status(status) {
+            unsafe {
+                drop(Box::from_raw(listener_ptr));
+            }
+            return Err(err);
+        }
+
+        self.property_listeners.push(listener_ptr);
+        Ok(PropertyListenerId(listener_ptr as usize))
+    }
+
+    /// Unregister and drop all property listeners.
+    pub(crate) fn free_property_listeners(&mut self) {
+        for listener_ptr in self.property_listeners.drain(..) {
+            unsafe {
+                // We don't want to panic in `drop`, so we'll ignore returned errors.
+                remove_property_listener(self.instance, listener_ptr).ok();
+                drop(Box::from_raw(listener_ptr));
+            }
+        }
+    }
+}
+
+/// Unregister the given listener from the audio unit. The caller remains responsible for dropping
+/// the listener once it is no longer registered.
+unsafe fn remove_property_listener(
+    instance: sys::AudioUnit,
+    listener_ptr: *mut PropertyListener,
+) -> Result<(), Error> {
+    let id = (*listener_ptr).id;
+    let status = sys::AudioUnitRemovePropertyListenerWithUserData(
+        instance,
+        id,
+        Some(property_listener_proc),
+        listener_ptr as *mut c_void,
+    );
+    Error::from_os_status(status)
+}
+
+/// Callback procedure that will be called each time a listened-to property changes.
+unsafe extern "C" fn property_listener_proc(
+    in_ref_con: *mut c_void,
+    in_unit: sys::AudioUnit,
+    _in_id: sys::AudioUnitPropertyID,
+    in_scope: sys::AudioUnitScope,
+    in_element: sys::AudioUnitElement,
+) {
+    let listener = in_ref_con as *mut PropertyListener;
+    (*(*listener).callback)(in_unit, in_scope, in_element);
+}
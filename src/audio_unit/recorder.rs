@@ -0,0 +1,232 @@
+//! Recording the input of an audio device to a WAV file.
+//!
+//! Captured audio is handed from the input callback to a background thread via a lock-free ring
+//! buffer, so disk I/O never blocks the render thread.
+
+use super::audio_format::LinearPcmFlags;
+use super::macos_helpers::{audio_unit_from_device_id, get_default_device_id};
+use super::render_callback::{self, data};
+use super::ring_buffer::RingBuffer;
+use super::wav::{WavSampleFormat, WavSpec, WavWriter};
+use super::{AudioUnit, Element, SampleFormat, Scope, StreamFormat};
+use crate::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use sys;
+
+/// The number of seconds of audio the ring buffer is able to hold.
+const RING_BUFFER_SECONDS: usize = 2;
+
+/// How long the writer thread sleeps for when there is no audio to write.
+const WRITER_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A callback called with the peak level of each block of audio written to the file.
+type LevelCallback = Box<dyn FnMut(f32) + Send>;
+
+/// State shared between the **Recorder**, the input callback and the writer thread.
+struct Shared {
+    ring_buffer: RingBuffer,
+    // Whether or not the writer thread should stop once the ring buffer is drained.
+    stop: AtomicBool,
+    // The number of samples dropped because the ring buffer was full.
+    dropped_samples: AtomicUsize,
+}
+
+/// The writer thread, returning the level callback so that it may be used again.
+type WriterThread = thread::JoinHandle<(Result<(), Error>, Option<LevelCallback>)>;
+
+/// Records the input of an audio device to a WAV file.
+///
+/// Audio is captured as interleaved `f32` samples at the device's sample rate and channel count,
+/// and converted to the chosen **WavSampleFormat** as it's written.
+pub struct Recorder {
+    audio_unit: AudioUnit,
+    shared: Arc<Shared>,
+    path: PathBuf,
+    spec: WavSpec,
+    level_callback: Option<LevelCallback>,
+    writer_thread: Option<WriterThread>,
+}
+
+impl Recorder {
+    /// Construct a **Recorder** capturing the default input device to the file at `path`.
+    pub fn new<P: AsRef<Path>>(path: P, sample_format: WavSampleFormat) -> Result<Self, Error> {
+        let device_id =
+            get_default_device_id(true).ok_or(Error::NoMatchingDefaultAudioUnitFound)?;
+        Recorder::from_device_id(device_id, path, sample_format)
+    }
+
+    /// Construct a **Recorder** capturing the device with the given ID to the file at `path`.
+    pub fn from_device_id<P: AsRef<Path>>(
+        device_id: sys::AudioDeviceID,
+        path: P,
+        sample_format: WavSampleFormat,
+    ) -> Result<Self, Error> {
+        let mut audio_unit = audio_unit_from_device_id(device_id, true)?;
+
+        // Capture interleaved `f32` samples in the device's own rate and channel count.
+        let id = sys::kAudioUnitProperty_StreamFormat;
+        let device_asbd: sys::AudioStreamBasicDescription =
+            audio_unit.get_property(id, Scope::Input, Element::Input)?;
+        let stream_format = StreamFormat {
+            sample_rate: device_asbd.mSampleRate,
+            sample_format: SampleFormat::F32,
+            flags: LinearPcmFlags::IS_FLOAT | LinearPcmFlags::IS_PACKED,
+            channels: device_asbd.mChannelsPerFrame,
+        };
+        audio_unit.set_stream_format(stream_format, Scope::Output, Element::Input)?;
+
+        let spec = WavSpec {
+            channels: stream_format.channels as u16,
+            sample_rate: stream_format.sample_rate as u32,
+            sample_format,
+        };
+        let capacity = RING_BUFFER_SECONDS * spec.sample_rate as usize * spec.channels as usize;
+        let shared = Arc::new(Shared {
+            ring_buffer: RingBuffer::new(capacity),
+            stop: AtomicBool::new(false),
+            dropped_samples: AtomicUsize::new(0),
+        });
+
+        let callback_shared = shared.clone();
+        type Args = render_callback::Args<data::Interleaved<f32>>;
+        audio_unit.set_input_callback(move |args: Args| {
+            let pushed = callback_shared.ring_buffer.push(args.data.buffer);
+            let dropped = args.data.buffer.len() - pushed;
+            if dropped > 0 {
+                callback_shared
+                    .dropped_samples
+                    .fetch_add(dropped, Ordering::Relaxed);
+            }
+            Ok(())
+        })?;
+
+        Ok(Recorder {
+            audio_unit,
+            shared,
+            path: path.as_ref().to_path_buf(),
+            spec,
+            level_callback: None,
+            writer_thread: None,
+        })
+    }
+
+    /// A description of the audio written to the file.
+    pub fn spec(&self) -> WavSpec {
+        self.spec
+    }
+
+    /// Set a callback to be called with the peak level of each block of audio written to the
+    /// file, within the range `0.0..=1.0` for unclipped audio.
+    ///
+    /// The callback is called on the writer thread rather than the render thread, so it may block,
+    /// but the level is delayed slightly relative to the input. It takes effect from the next call
+    /// to `start`.
+    pub fn set_level_callback<F>(&mut self, f: F)
+    where
+        F: FnMut(f32) + Send + 'static,
+    {
+        self.level_callback = Some(Box::new(f));
+    }
+
+    /// Create or truncate the file and start recording to it.
+    ///
+    /// Calling this while already recording has no effect.
+    pub fn start(&mut self) -> Result<(), Error> {
+        if self.writer_thread.is_some() {
+            return Ok(());
+        }
+        let mut writer = WavWriter::create(&self.path, self.spec)?;
+
+        // Discard anything captured before a previous `stop`.
+        self.shared.ring_buffer.pop_into(&mut Vec::new());
+        self.shared.stop.store(false, Ordering::Relaxed);
+        self.shared.dropped_samples.store(0, Ordering::Relaxed);
+
+        let shared = self.shared.clone();
+        let mut level_callback = self.level_callback.take();
+        let writer_thread = thread::spawn(move || {
+            let result = write_until_stopped(&shared, &mut writer, &mut level_callback)
+                .and_then(|()| writer.finalize().map(|_| ()));
+            (result, level_callback)
+        });
+        self.writer_thread = Some(writer_thread);
+
+        if let Err(err) = self.audio_unit.start() {
+            self.stop().ok();
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    /// Stop recording, waiting for all captured audio to be written and the file to be finalized.
+    ///
+    /// Returns any error that occurred while writing the file.
+    pub fn stop(&mut self) -> Result<(), Error> {
+        let writer_thread = match self.writer_thread.take() {
+            Some(writer_thread) => writer_thread,
+            None => return Ok(()),
+        };
+        let stop_result = self.audio_unit.stop();
+        self.shared.stop.store(true, Ordering::Release);
+        // The writer thread never panics unless the level callback does.
+        let (write_result, level_callback) = writer_thread
+            .join()
+            .unwrap_or((Err(Error::Unspecified), None));
+        self.level_callback = level_callback;
+        stop_result.and(write_result)
+    }
+
+    /// Whether or not the recorder is currently recording.
+    pub fn is_recording(&self) -> bool {
+        self.writer_thread.is_some()
+    }
+
+    /// The number of samples dropped since the last call to `start` because the file could not be
+    /// written quickly enough.
+    pub fn dropped_samples(&self) -> usize {
+        self.shared.dropped_samples.load(Ordering::Relaxed)
+    }
+
+    /// A reference to the underlying input **AudioUnit**.
+    pub fn audio_unit(&self) -> &AudioUnit {
+        &self.audio_unit
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        // We don't want to panic in `drop`, so we'll ignore returned errors.
+        self.stop().ok();
+    }
+}
+
+/// Write audio from the ring buffer to the file until the recorder is stopped.
+fn write_until_stopped(
+    shared: &Shared,
+    writer: &mut WavWriter<impl std::io::Write + std::io::Seek>,
+    level_callback: &mut Option<LevelCallback>,
+) -> Result<(), Error> {
+    let mut samples = Vec::new();
+    loop {
+        // Check the flag before draining, so that everything captured before the stop is written.
+        let stopping = shared.stop.load(Ordering::Acquire);
+        samples.clear();
+        if shared.ring_buffer.pop_into(&mut samples) > 0 {
+            for &sample in &samples {
+                writer.write_sample(sample)?;
+            }
+            if let Some(ref mut level_callback) = *level_callback {
+                let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+                level_callback(peak);
+            }
+        } else if stopping {
+            return Ok(());
+        } else {
+            thread::sleep(WRITER_POLL_INTERVAL);
+        }
+    }
+}
@@ -1,12 +1,14 @@
 use super::audio_format::LinearPcmFlags;
-use super::{AudioUnit, Element, Scope};
-use crate::error::{self, Error};
+use super::no_alloc::RenderGuard;
+use super::{AudioUnit, Element, Sample, Scope};
+use crate::error::{self, AudioUnitError, Error};
 use std::mem;
 use std::os::raw::c_void;
-use std::slice;
+use std::ptr;
+use std::sync::Arc;
 use sys;
 
-pub use self::action_flags::ActionFlags;
+pub use self::action_flags::{ActionFlags, RenderFlags};
 pub use self::data::Data;
 
 /// When `set_render_callback` is called, a closure of this type will be used to wrap the given
@@ -22,11 +24,61 @@ pub type InputProcFn = dyn FnMut(
     *mut sys::AudioBufferList,
 ) -> sys::OSStatus;
 
+/// An `InputProcFn` that may borrow for the lifetime `'a`, e.g. from the scope of
+/// `AudioUnit::set_render_callback_scoped`.
+type ScopedInputProcFn<'a> = dyn FnMut(
+        *mut sys::AudioUnitRenderActionFlags,
+        *const sys::AudioTimeStamp,
+        sys::UInt32,
+        sys::UInt32,
+        *mut sys::AudioBufferList,
+    ) -> sys::OSStatus
+    + 'a;
+
 /// This type allows us to safely wrap a boxed `RenderCallback` to use within the input proc.
 pub struct InputProcFnWrapper {
     callback: Box<InputProcFn>,
 }
 
+/// A render callback that was replaced via `AudioUnit::replace_render_callback`.
+///
+/// This is handed to the new render callback on each call so that it may call through to the
+/// previous callback with the same arguments.
+pub struct PreviousRenderCallback<'a> {
+    wrapper: &'a mut InputProcFnWrapper,
+    io_action_flags: *mut sys::AudioUnitRenderActionFlags,
+    in_time_stamp: *const sys::AudioTimeStamp,
+    in_bus_number: sys::UInt32,
+    in_number_frames: sys::UInt32,
+    io_data: *mut sys::AudioBufferList,
+}
+
+impl PreviousRenderCallback<'_> {
+    /// Call the previous render callback, rendering into the buffer of the current call.
+    pub fn call(&mut self) -> Result<(), Error> {
+        let status = (self.wrapper.callback)(
+            self.io_action_flags,
+            self.in_time_stamp,
+            self.in_bus_number,
+            self.in_number_frames,
+            self.io_data,
+        );
+        Error::from_os_status(status)
+    }
+}
+
+/// Owns a render callback replaced via `AudioUnit::replace_render_callback`, dropping it along with
+/// the callback that replaced it.
+struct ReplacedRenderCallback(*mut InputProcFnWrapper);
+
+impl Drop for ReplacedRenderCallback {
+    fn drop(&mut self) {
+        unsafe {
+            drop(Box::from_raw(self.0));
+        }
+    }
+}
+
 /// Arguments given to the render callback function.
 #[derive(Debug)]
 pub struct Args<D> {
@@ -47,6 +99,235 @@ pub struct Args<D> {
     pub flags: action_flags::Handle,
 }
 
+impl<D> Args<D> {
+    /// Whether the time stamp holds all of the given `kAudioTimeStamp*Valid` fields.
+    fn is_valid(&self, flags: sys::AudioTimeStampFlags) -> bool {
+        self.time_stamp.mFlags & flags == flags
+    }
+
+    /// The sample time of the first frame of the buffer, if the time stamp holds one.
+    pub fn sample_time(&self) -> Option<f64> {
+        if self.is_valid(sys::kAudioTimeStampSampleTimeValid) {
+            Some(self.time_stamp.mSampleTime)
+        } else {
+            None
+        }
+    }
+
+    /// The host time, in `mach_absolute_time` units, at which the first frame of the buffer is
+    /// played or was captured, if the time stamp holds one.
+    pub fn host_time(&self) -> Option<u64> {
+        if self.is_valid(sys::kAudioTimeStampHostTimeValid) {
+            Some(self.time_stamp.mHostTime)
+        } else {
+            None
+        }
+    }
+
+    /// The ratio of the device's actual sample rate to its nominal sample rate, if the time
+    /// stamp holds one, e.g. for correcting the drift between two devices.
+    pub fn rate_scalar(&self) -> Option<f64> {
+        if self.is_valid(sys::kAudioTimeStampRateScalarValid) {
+            Some(self.time_stamp.mRateScalar)
+        } else {
+            None
+        }
+    }
+
+    /// The word clock time of the first frame of the buffer, if the time stamp holds one.
+    pub fn word_clock_time(&self) -> Option<u64> {
+        if self.is_valid(sys::kAudioTimeStampWordClockTimeValid) {
+            Some(self.time_stamp.mWordClockTime)
+        } else {
+            None
+        }
+    }
+
+    /// Indicate to the unit that the rendered buffer holds only silence, so that it and the units
+    /// downstream of it may skip processing the buffer.
+    ///
+    /// This is a hint rather than an instruction: the buffer must still be filled with zeroes.
+    pub fn set_output_is_silence(&mut self) {
+        self.flags.insert(ActionFlags::OUTPUT_IS_SILENCE);
+    }
+}
+
+/// Access to an **AudioUnit** while a render callback borrowing from the enclosing scope is
+/// installed, as given to the body of `AudioUnit::set_render_callback_scoped`.
+///
+/// The scope dereferences to the **AudioUnit** by shared reference only, so that the unit may be
+/// inspected but not replaced while the callback is installed.
+pub struct RenderScope<'a> {
+    audio_unit: &'a mut AudioUnit,
+}
+
+impl RenderScope<'_> {
+    /// Start the unit, which calls the scoped render callback from the render thread.
+    pub fn start(&mut self) -> Result<(), Error> {
+        self.audio_unit.start()
+    }
+
+    /// Stop the unit.
+    pub fn stop(&mut self) -> Result<(), Error> {
+        self.audio_unit.stop()
+    }
+}
+
+impl ::std::ops::Deref for RenderScope<'_> {
+    type Target = AudioUnit;
+    fn deref(&self) -> &AudioUnit {
+        self.audio_unit
+    }
+}
+
+/// Removes the scoped render callback of an **AudioUnit** when dropped.
+struct ScopedRenderCallbackGuard<'a> {
+    audio_unit: &'a mut AudioUnit,
+}
+
+impl Drop for ScopedRenderCallbackGuard<'_> {
+    fn drop(&mut self) {
+        if self.audio_unit.is_running() {
+            error::report_drop_error(self.audio_unit.stop());
+        }
+        // A unit pulled by a downstream unit via `connect_input` renders whether or not it was
+        // started, so the unit is also uninitialized while the callback is removed, which waits
+        // for any render in progress and prevents further renders.
+        //
+        // The callback borrows from a scope that is about to end, so it must not remain installed
+        // where the unit could call it. Should it not be possible to remove it, abort.
+        let mut guard = match self.audio_unit.uninitialized_guard() {
+            Ok(guard) => guard,
+            Err(_) => ::std::process::abort(),
+        };
+        if guard.unregister_render_callback().is_err() {
+            ::std::process::abort();
+        }
+        guard.free_render_callback();
+        error::report_drop_error(guard.finish());
+    }
+}
+
+/// A pool of pre-allocated `f32` samples from which a callback may take temporary buffers, e.g.
+/// for deinterleaving or mixing, without allocating on the render thread.
+///
+/// See `AudioUnit::set_render_callback_with_scratch`. The whole pool is available again on each
+/// call of the callback.
+#[derive(Debug)]
+pub struct RenderScratch<'a> {
+    remaining: &'a mut [f32],
+}
+
+impl<'a> RenderScratch<'a> {
+    /// Create a **RenderScratch** taking its buffers from the given pool.
+    pub fn new(pool: &'a mut [f32]) -> Self {
+        RenderScratch { remaining: pool }
+    }
+
+    /// Take a zeroed buffer of `len` samples from the pool, or `None` if fewer than `len` samples
+    /// remain.
+    ///
+    /// Buffers taken from the same **RenderScratch** never overlap, so several may be used at
+    /// once.
+    pub fn take(&mut self, len: usize) -> Option<&'a mut [f32]> {
+        if len > self.remaining.len() {
+            return None;
+        }
+        let (taken, remaining) = mem::take(&mut self.remaining).split_at_mut(len);
+        self.remaining = remaining;
+        for sample in taken.iter_mut() {
+            *sample = 0.0;
+        }
+        Some(taken)
+    }
+
+    /// The number of samples remaining in the pool.
+    pub fn remaining(&self) -> usize {
+        self.remaining.len()
+    }
+}
+
+/// Configures the buffer into which an input callback's audio is captured.
+///
+/// See `AudioUnit::set_input_callback_with_config`. The default configuration sizes the buffer
+/// for the channels of the input stream format and the unit's `max_frames_per_slice`, and does
+/// not zero the buffer on underrun.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct InputCaptureConfig {
+    /// The number of channels to capture, which must match the input stream format.
+    ///
+    /// Defaults to the channel count of the input stream format.
+    pub channels: Option<u32>,
+    /// The largest number of frames the buffer is able to hold.
+    ///
+    /// Defaults to the unit's `max_frames_per_slice`.
+    pub max_frames: Option<u32>,
+    /// If `true`, failing to render input (e.g. because no input is available yet) calls the
+    /// callback with a zeroed buffer and the `OUTPUT_IS_SILENCE` flag set, rather than skipping
+    /// the callback and returning the error to the unit.
+    pub zero_on_underrun: bool,
+}
+
+/// The buffer into which an input callback's audio is captured.
+///
+/// The buffer is allocated once when the callback is registered and never reallocated, so that
+/// capturing input doesn't allocate on the render thread.
+pub(crate) struct InputBuffer {
+    // A single `AudioBuffer` whose data is allocated by a `Vec<u8>`.
+    buffer_list: sys::AudioBufferList,
+    // The capacity of the `Vec<u8>` allocation in bytes.
+    capacity: usize,
+}
+
+impl InputBuffer {
+    /// Allocate a zeroed buffer of `byte_size` bytes holding `channels` interleaved channels.
+    fn new(byte_size: usize, channels: u32) -> Self {
+        let mut data = vec![0u8; byte_size];
+        let audio_buffer = sys::AudioBuffer {
+            mDataByteSize: byte_size as u32,
+            mNumberChannels: channels,
+            mData: data.as_mut_ptr() as *mut _,
+        };
+        // Relieve ownership of the `Vec` until the `InputBuffer` is dropped.
+        let capacity = data.capacity();
+        mem::forget(data);
+        InputBuffer {
+            buffer_list: sys::AudioBufferList {
+                mNumberBuffers: 1,
+                mBuffers: [audio_buffer],
+            },
+            capacity,
+        }
+    }
+
+    /// Set the size of the audio buffer to `byte_size` bytes.
+    ///
+    /// Returns `false` without changing the size if `byte_size` exceeds the capacity allocated
+    /// when the buffer was created.
+    fn set_byte_size(&mut self, byte_size: usize) -> bool {
+        if byte_size > self.capacity {
+            return false;
+        }
+        self.buffer_list.mBuffers[0].mDataByteSize = byte_size as u32;
+        true
+    }
+
+    /// Zero the contents of the audio buffer.
+    unsafe fn zero(&mut self) {
+        let buffer = &mut self.buffer_list.mBuffers[0];
+        ptr::write_bytes(buffer.mData as *mut u8, 0, buffer.mDataByteSize as usize);
+    }
+}
+
+impl Drop for InputBuffer {
+    fn drop(&mut self) {
+        let ptr = self.buffer_list.mBuffers[0].mData as *mut u8;
+        unsafe {
+            drop(Vec::from_raw_parts(ptr, 0, self.capacity));
+        }
+    }
+}
+
 /// Format specific render callback data.
 pub mod data {
     use super::super::Sample;
@@ -67,6 +348,13 @@ pub mod data {
             -> Self;
     }
 
+    /// Whether samples of type `S` are of the stream format's sample format, including its bit
+    /// depth, which the flags alone don't describe.
+    fn does_sample_format_match<S: Sample>(stream_format: &StreamFormat) -> bool {
+        stream_format.sample_format == S::sample_format()
+            && S::sample_format().does_match_flags(stream_format.flags)
+    }
+
     /// A raw pointer to the audio data so that the user may handle it themselves.
     #[derive(Debug)]
     pub struct Raw {
@@ -191,7 +479,7 @@ pub mod data {
             stream_format
                 .flags
                 .contains(LinearPcmFlags::IS_NON_INTERLEAVED)
-                && S::sample_format().does_match_flags(stream_format.flags)
+                && does_sample_format_match::<S>(stream_format)
         }
 
         #[allow(non_snake_case)]
@@ -216,7 +504,7 @@ pub mod data {
             !stream_format
                 .flags
                 .contains(LinearPcmFlags::IS_NON_INTERLEAVED)
-                && S::sample_format().does_match_flags(stream_format.flags)
+                && does_sample_format_match::<S>(stream_format)
         }
 
         #[allow(non_snake_case)]
@@ -257,7 +545,7 @@ pub mod data {
             !stream_format
                 .flags
                 .contains(LinearPcmFlags::IS_NON_INTERLEAVED)
-                && S::sample_format().does_match_flags(stream_format.flags)
+                && does_sample_format_match::<S>(stream_format)
         }
 
         #[allow(non_snake_case)]
@@ -352,6 +640,13 @@ pub mod action_flags {
         }
     }
 
+    /// The flags passed to and returned from a render of an audio unit, e.g. via
+    /// `AudioUnit::render_into`.
+    ///
+    /// These are the same flags a render callback sees, with `OUTPUT_IS_SILENCE` set on return if
+    /// the rendered buffers contain only silence.
+    pub type RenderFlags = ActionFlags;
+
     /// A safe handle around the `AudioUnitRenderActionFlags` pointer provided by the render
     /// callback.
     ///
@@ -460,25 +755,125 @@ pub mod action_flags {
 
 impl AudioUnit {
     /// Pass a render callback (aka "Input Procedure") to the **AudioUnit**.
-    pub fn set_render_callback<F, D>(&mut self, mut f: F) -> Result<(), Error>
+    pub fn set_render_callback<F, D>(&mut self, f: F) -> Result<(), Error>
     where
         F: FnMut(Args<D>) -> Result<(), ()> + 'static,
         D: Data,
     {
-        // First, we'll retrieve the stream format so that we can ensure that the given callback
-        // format matches the audio unit's format.
-        let stream_format = self.output_stream_format()?;
+        self.check_render_callback_format::<D>()?;
+        self.install_render_callback(render_proc_fn(f))
+    }
 
-        // If the stream format does not match, return an error indicating this.
-        if !D::does_stream_format_match(&stream_format) {
-            return Err(Error::RenderCallbackBufferFormatDoesNotMatchAudioUnitStreamFormat);
-        }
+    /// Pass a render callback that borrows from the enclosing scope to the **AudioUnit** for the
+    /// duration of `body`, much like `std::thread::scope`.
+    ///
+    /// The callback replaces any previously installed render callback. `body` may start and
+    /// stop the unit via the given **RenderScope**. When `body` returns or panics, the unit is
+    /// stopped and uninitialized while the callback is removed and dropped, so that the callback
+    /// isn't running even if the unit is pulled by another unit via `connect_input`. The callback
+    /// therefore only needs to outlive this call rather than be `'static`, so it may borrow e.g.
+    /// a buffer on the stack without wrapping it in an `Arc`.
+    ///
+    /// The unit is left stopped and without a render callback, and is reinitialized if it was
+    /// initialized before. The process is aborted should the callback fail to be removed, as the
+    /// unit could otherwise call it after its borrows have ended.
+    pub fn set_render_callback_scoped<'env, F, D, B, R>(
+        &mut self,
+        f: F,
+        body: B,
+    ) -> Result<R, Error>
+    where
+        F: FnMut(Args<D>) -> Result<(), ()> + Send + 'env,
+        D: Data,
+        B: FnOnce(&mut RenderScope) -> R,
+    {
+        self.check_render_callback_format::<D>()?;
+        // Raw pointer casts may not extend the lifetime of a trait object, so the callback is
+        // boxed once more to cast a thin pointer to it instead.
+        let input_proc_fn: Box<Box<ScopedInputProcFn<'env>>> =
+            Box::new(render_proc_fn::<'env, _, _>(f));
+        let input_proc_fn_ptr = Box::into_raw(input_proc_fn).cast::<Box<InputProcFn>>();
+        // SAFETY: `Box<ScopedInputProcFn<'env>>` and `Box<InputProcFn>` differ only in the
+        // lifetime bound of the trait object, so they share a layout and vtable. Erasing the
+        // lifetime is sound as the guard below removes and drops the callback before this function
+        // returns, i.e. before `'env` ends, having quiesced the unit so that it is no longer
+        // running the callback. The unit is only exposed to `body` by shared reference, so it
+        // cannot be moved out from under the guard, nor the callback replaced.
+        let input_proc_fn: Box<InputProcFn> = unsafe { *Box::from_raw(input_proc_fn_ptr) };
+        self.install_render_callback(input_proc_fn)?;
+
+        let guard = ScopedRenderCallbackGuard { audio_unit: self };
+        let mut scope = RenderScope {
+            audio_unit: &mut *guard.audio_unit,
+        };
+        Ok(body(&mut scope))
+    }
+
+    /// Pass a render callback to the **AudioUnit** along with some shared state, which is handed to
+    /// the callback by reference on each call.
+    ///
+    /// This suits state that is shared with the rest of the application, such as lookup tables or
+    /// configuration, which may be mutated from other threads via interior mutability within `S`
+    /// (e.g. atomics). Keep in mind that the callback runs on the render thread, so it should not
+    /// block on locks held by other threads.
+    ///
+    /// The callback's reference to the state is dropped when the callback is freed, i.e. when it
+    /// is replaced or cleared, or when the **AudioUnit** is dropped.
+    pub fn set_render_callback_with_state<S, F, D>(
+        &mut self,
+        state: Arc<S>,
+        mut f: F,
+    ) -> Result<(), Error>
+    where
+        S: Send + Sync + 'static,
+        F: FnMut(&S, Args<D>) -> Result<(), ()> + 'static,
+        D: Data,
+    {
+        self.set_render_callback(move |args: Args<D>| f(&state, args))
+    }
+
+    /// Pass a render callback to the **AudioUnit** along with a **RenderScratch** from which it
+    /// may take temporary buffers on each call.
+    ///
+    /// The scratch buffers are taken from `pool`, which is allocated by the caller before the
+    /// callback is installed, so the callback never needs to allocate. `pool` should be sized for
+    /// the largest number of frames the callback may be asked to render, i.e.
+    /// `max_frames_per_slice`.
+    pub fn set_render_callback_with_scratch<F, D>(
+        &mut self,
+        mut pool: Vec<f32>,
+        mut f: F,
+    ) -> Result<(), Error>
+    where
+        F: FnMut(Args<D>, &mut RenderScratch) -> Result<(), ()> + 'static,
+        D: Data,
+    {
+        self.set_render_callback(move |args: Args<D>| f(args, &mut RenderScratch::new(&mut pool)))
+    }
+
+    /// Replace the render callback of the **AudioUnit**, handing the previously installed render
+    /// callback (if any) to the new one so that it may be called through to.
+    ///
+    /// This allows for layering callbacks, e.g. a metering callback that calls the previous
+    /// callback to produce the audio and then inspects the rendered buffer.
+    ///
+    /// The previous callback is owned by the new callback from here on and is dropped along with
+    /// it. `PreviousRenderCallback::call` renders into the same buffer that was given to the new
+    /// callback, so avoid reading from or writing to `args.data` while calling it.
+    pub fn replace_render_callback<F, D>(&mut self, mut f: F) -> Result<(), Error>
+    where
+        F: FnMut(Args<D>, Option<PreviousRenderCallback>) -> Result<(), ()> + 'static,
+        D: Data,
+    {
+        self.check_render_callback_format::<D>()?;
+
+        // Take ownership of the previous callback. It remains registered with the audio unit until
+        // the new callback is installed, so it must not be dropped before then. If installing the
+        // new callback fails, the new callback is leaked rather than dropped, so that ownership of
+        // the previous callback may be handed back to the unit.
+        let previous_ptr = self.maybe_render_callback.take();
+        let mut maybe_previous = previous_ptr.map(ReplacedRenderCallback);
 
-        // Here, we call the given render callback function within a closure that matches the
-        // arguments of the required coreaudio "input_proc".
-        //
-        // This allows us to take advantage of rust's type system and provide format-specific
-        // `Args` types which can be checked at compile time.
         let input_proc_fn = move |io_action_flags: *mut sys::AudioUnitRenderActionFlags,
                                   in_time_stamp: *const sys::AudioTimeStamp,
                                   in_bus_number: sys::UInt32,
@@ -486,25 +881,56 @@ impl AudioUnit {
                                   io_data: *mut sys::AudioBufferList|
               -> sys::OSStatus {
             let args = unsafe {
-                let data = D::from_input_proc_args(in_number_frames, io_data);
-                let flags = action_flags::Handle::from_ptr(io_action_flags);
-                Args {
-                    data,
-                    time_stamp: *in_time_stamp,
-                    flags,
-                    bus_number: in_bus_number as u32,
-                    num_frames: in_number_frames as usize,
-                }
+                render_callback_args(
+                    io_action_flags,
+                    in_time_stamp,
+                    in_bus_number,
+                    in_number_frames,
+                    io_data,
+                )
             };
+            let previous = maybe_previous
+                .as_mut()
+                .map(|previous| PreviousRenderCallback {
+                    wrapper: unsafe { &mut *previous.0 },
+                    io_action_flags,
+                    in_time_stamp,
+                    in_bus_number,
+                    in_number_frames,
+                    io_data,
+                });
 
-            match f(args) {
+            match f(args, previous) {
                 Ok(()) => 0,
                 Err(()) => error::Error::Unspecified.as_os_status(),
             }
         };
 
+        let result = self.install_render_callback(Box::new(input_proc_fn));
+        if result.is_err() {
+            // The previous callback is still the one registered with the audio unit.
+            self.maybe_render_callback = previous_ptr;
+        }
+        result
+    }
+
+    /// Ensure that the render callback data type `D` matches the audio unit's stream format.
+    fn check_render_callback_format<D: Data>(&self) -> Result<(), Error> {
+        // First, we'll retrieve the stream format so that we can ensure that the given callback
+        // format matches the audio unit's format.
+        let stream_format = self.output_stream_format()?;
+
+        // If the stream format does not match, return an error indicating this.
+        if !D::does_stream_format_match(&stream_format) {
+            return Err(Error::RenderCallbackBufferFormatDoesNotMatchAudioUnitStreamFormat);
+        }
+        Ok(())
+    }
+
+    /// Set the given input proc closure as the render callback, freeing the previous one.
+    fn install_render_callback(&mut self, input_proc_fn: Box<InputProcFn>) -> Result<(), Error> {
         let input_proc_fn_wrapper = Box::new(InputProcFnWrapper {
-            callback: Box::new(input_proc_fn),
+            callback: input_proc_fn,
         });
 
         // Setup render callback. Notice that we relinquish ownership of the Callback
@@ -530,8 +956,74 @@ impl AudioUnit {
         Ok(())
     }
 
+    /// Remove the render callback of the **AudioUnit**, if any, and drop it.
+    ///
+    /// The unit is stopped and uninitialized while the callback is removed, so that the callback
+    /// is guaranteed not to be running on the render thread when it is dropped, even if the unit
+    /// is pulled by a downstream unit (see `connect_input`) rather than running itself. The unit
+    /// is then initialized and started again as before, rendering silence or pulling from
+    /// whatever else provides its input.
+    pub fn clear_render_callback(&mut self) -> Result<(), Error> {
+        if self.maybe_render_callback.is_none() {
+            return Ok(());
+        }
+        let mut guard = self.uninitialized_guard()?;
+        let result = guard.unregister_render_callback();
+        if result.is_ok() {
+            guard.free_render_callback();
+        }
+        guard.finish()?;
+        result
+    }
+
+    /// Unregister the render callback from the audio unit without dropping it.
+    fn unregister_render_callback(&mut self) -> Result<(), Error> {
+        let render_callback = sys::AURenderCallbackStruct {
+            inputProc: None,
+            inputProcRefCon: ptr::null_mut(),
+        };
+        self.set_property(
+            sys::kAudioUnitProperty_SetRenderCallback,
+            Scope::Input,
+            Element::Output,
+            Some(&render_callback),
+        )
+    }
+
+    /// Whether or not a render callback is currently installed on the **AudioUnit**.
+    pub fn has_render_callback(&self) -> bool {
+        self.maybe_render_callback.is_some()
+    }
+
+    /// Whether or not an input callback is currently installed on the **AudioUnit**.
+    pub fn has_input_callback(&self) -> bool {
+        self.maybe_input_callback.is_some()
+    }
+
     /// Pass an input callback (aka "Input Procedure") to the **AudioUnit**.
-    pub fn set_input_callback<F, D>(&mut self, mut f: F) -> Result<(), Error>
+    ///
+    /// The buffer into which input is captured is allocated according to the default
+    /// **InputCaptureConfig**. See `set_input_callback_with_config`.
+    pub fn set_input_callback<F, D>(&mut self, f: F) -> Result<(), Error>
+    where
+        F: FnMut(Args<D>) -> Result<(), ()> + 'static,
+        D: Data,
+    {
+        self.set_input_callback_with_config(InputCaptureConfig::default(), f)
+    }
+
+    /// Pass an input callback (aka "Input Procedure") to the **AudioUnit**, allocating the buffer
+    /// into which input is captured according to the given **InputCaptureConfig**.
+    ///
+    /// The buffer is allocated up front with room for `config.max_frames` frames and is never
+    /// reallocated, so capturing input doesn't allocate on the render thread. Should the unit
+    /// deliver more frames than that in a single call, the callback is skipped for that call and
+    /// `AudioUnitError::TooManyFramesToProcess` is returned to the unit.
+    pub fn set_input_callback_with_config<F, D>(
+        &mut self,
+        config: InputCaptureConfig,
+        mut f: F,
+    ) -> Result<(), Error>
     where
         F: FnMut(Args<D>) -> Result<(), ()> + 'static,
         D: Data,
@@ -552,47 +1044,29 @@ impl AudioUnit {
 
         // Pre-allocate a buffer list for input stream.
         //
-        // First, get the current buffer size for pre-allocating the `AudioBuffer`s.
-        #[cfg(target_os = "macos")]
-        let mut buffer_frame_size: u32 = {
-            let id = sys::kAudioDevicePropertyBufferFrameSize;
-            let buffer_frame_size: u32 = self.get_property(id, Scope::Global, Element::Output)?;
-            buffer_frame_size
-        };
-        #[cfg(target_os = "ios")]
-        let mut buffer_frame_size: u32 = {
-            let id = sys::kAudioSessionProperty_CurrentHardwareIOBufferDuration;
-            let seconds: f32 = super::audio_session_get_property(id)?;
-            let id = sys::kAudioSessionProperty_CurrentHardwareSampleRate;
-            let sample_rate: f64 = super::audio_session_get_property(id)?;
-            (sample_rate * seconds as f64).round() as u32
+        // First, determine the number of frames for pre-allocating the `AudioBuffer`s, which
+        // defaults to the largest number of frames the unit may render at once.
+        let max_frames = match config.max_frames {
+            Some(max_frames) => max_frames,
+            None => self.max_frames_per_slice()?,
         };
         let sample_bytes = stream_format.sample_format.size_in_bytes();
-        let n_channels = stream_format.channels;
+        let n_channels = config.channels.unwrap_or(stream_format.channels);
+        if n_channels != stream_format.channels {
+            return Err(Error::RenderCallbackBufferFormatDoesNotMatchAudioUnitStreamFormat);
+        }
         if non_interleaved && n_channels > 1 {
             return Err(Error::NonInterleavedInputOnlySupportsMono);
         }
+        let zero_on_underrun = config.zero_on_underrun;
 
-        let data_byte_size = buffer_frame_size * sample_bytes as u32 * n_channels;
-        let mut data = vec![0u8; data_byte_size as usize];
-        let mut buffer_capacity = data_byte_size as usize;
-        let audio_buffer = sys::AudioBuffer {
-            mDataByteSize: data_byte_size,
-            mNumberChannels: n_channels,
-            mData: data.as_mut_ptr() as *mut _,
-        };
-        // Relieve ownership of the `Vec` until we're ready to drop the `AudioBufferList`.
-        // TODO: This leaks the len & capacity fields, since only the buffer pointer is released
-        mem::forget(data);
-
-        let audio_buffer_list = Box::new(sys::AudioBufferList {
-            mNumberBuffers: 1,
-            mBuffers: [audio_buffer],
-        });
+        let frame_byte_size = sample_bytes * n_channels as usize;
+        let data_byte_size = max_frames as usize * frame_byte_size;
+        let input_buffer = Box::new(InputBuffer::new(data_byte_size, n_channels));
 
-        // Relinquish ownership of the audio buffer list. Instead, we'll store a raw pointer and
+        // Relinquish ownership of the input buffer. Instead, we'll store a raw pointer and
         // convert it back into a `Box` when `free_input_callback` is next called.
-        let audio_buffer_list_ptr = Box::into_raw(audio_buffer_list);
+        let input_buffer_ptr = Box::into_raw(input_buffer);
 
         // Here, we call the given input callback function within a closure that matches the
         // arguments of the required coreaudio "input_proc".
@@ -606,43 +1080,13 @@ impl AudioUnit {
                                   in_number_frames: sys::UInt32,
                                   _io_data: *mut sys::AudioBufferList|
               -> sys::OSStatus {
-            // If the buffer size has changed, ensure the AudioBuffer is the correct size.
-            if buffer_frame_size != in_number_frames {
-                unsafe {
-                    // Retrieve the up-to-date stream format.
-                    let id = sys::kAudioUnitProperty_StreamFormat;
-                    let asbd =
-                        match super::get_property(audio_unit, id, Scope::Output, Element::Input) {
-                            Err(err) => return err.as_os_status(),
-                            Ok(asbd) => asbd,
-                        };
-                    let stream_format = match super::StreamFormat::from_asbd(asbd) {
-                        Err(err) => return err.as_os_status(),
-                        Ok(fmt) => fmt,
-                    };
-                    let sample_bytes = stream_format.sample_format.size_in_bytes();
-                    let n_channels = stream_format.channels;
-                    let data_byte_size =
-                        in_number_frames as usize * sample_bytes * n_channels as usize;
-                    let ptr = (*audio_buffer_list_ptr).mBuffers.as_ptr() as *mut sys::AudioBuffer;
-                    let len = (*audio_buffer_list_ptr).mNumberBuffers as usize;
-
-                    let buffers: &mut [sys::AudioBuffer] = slice::from_raw_parts_mut(ptr, len);
-                    let old_capacity = buffer_capacity;
-                    for buffer in buffers {
-                        let current_len = buffer.mDataByteSize as usize;
-                        let audio_buffer_ptr = buffer.mData as *mut u8;
-                        let mut vec: Vec<u8> =
-                            Vec::from_raw_parts(audio_buffer_ptr, current_len, old_capacity);
-                        vec.resize(data_byte_size, 0u8);
-
-                        buffer_capacity = vec.capacity();
-                        buffer.mData = vec.as_mut_ptr() as *mut _;
-                        buffer.mDataByteSize = data_byte_size as u32;
-                        mem::forget(vec);
-                    }
-                }
-                buffer_frame_size = in_number_frames;
+            let audio_buffer_list_ptr = unsafe { &mut (*input_buffer_ptr).buffer_list as *mut _ };
+
+            // Size the AudioBuffer for the number of frames, which must not exceed the number of
+            // frames allocated for, as reallocating on the render thread could cause a dropout.
+            let data_byte_size = in_number_frames as usize * frame_byte_size;
+            if unsafe { !(*input_buffer_ptr).set_byte_size(data_byte_size) } {
+                return Error::AudioUnit(AudioUnitError::TooManyFramesToProcess).as_os_status();
             }
 
             unsafe {
@@ -655,7 +1099,12 @@ impl AudioUnit {
                     audio_buffer_list_ptr,
                 );
                 if status != 0 {
-                    return status;
+                    if !zero_on_underrun {
+                        return status;
+                    }
+                    // Present the callback with silence in place of the missing input.
+                    (*input_buffer_ptr).zero();
+                    *io_action_flags |= sys::kAudioUnitRenderAction_OutputIsSilence;
                 }
             }
 
@@ -700,7 +1149,7 @@ impl AudioUnit {
         )?;
 
         let input_callback = super::InputCallback {
-            buffer_list: audio_buffer_list_ptr,
+            buffer: input_buffer_ptr,
             callback: input_proc_fn_wrapper_ptr as *mut InputProcFnWrapper,
         };
         self.free_input_callback();
@@ -708,6 +1157,58 @@ impl AudioUnit {
         Ok(())
     }
 
+    /// Pass an input callback (aka "Input Procedure") to the **AudioUnit** that always receives
+    /// the captured audio as planar (non-interleaved) channels.
+    ///
+    /// If the input stream format is interleaved, each captured buffer is deinterleaved into one
+    /// buffer per channel before the callback is invoked. If the input stream format is already
+    /// non-interleaved this behaves the same as `set_input_callback`.
+    ///
+    /// Whether or not the input is interleaved is detected from the flags of the input stream
+    /// format. The planar buffers are allocated up front for the unit's `max_frames_per_slice`,
+    /// as is the buffer into which the input is captured, so that the render thread never
+    /// allocates.
+    pub fn set_input_callback_deinterleaved<F, S>(&mut self, mut f: F) -> Result<(), Error>
+    where
+        F: FnMut(Args<data::NonInterleaved<S>>) -> Result<(), ()> + 'static,
+        S: Sample + Copy + Default + 'static,
+    {
+        let stream_format = self.input_stream_format()?;
+        if stream_format
+            .flags
+            .contains(LinearPcmFlags::IS_NON_INTERLEAVED)
+        {
+            return self.set_input_callback(f);
+        }
+
+        let max_frames = self.max_frames_per_slice()?;
+        let mut planar = PlanarBuffers::<S>::new(stream_format.channels as usize, max_frames);
+        let config = InputCaptureConfig {
+            max_frames: Some(max_frames),
+            ..InputCaptureConfig::default()
+        };
+        self.set_input_callback_with_config(config, move |args: Args<data::Interleaved<S>>| {
+            let Args {
+                data,
+                time_stamp,
+                bus_number,
+                num_frames,
+                flags,
+            } = args;
+            let buffer_list = planar.load(data.buffer).ok_or(())?;
+            let data = unsafe {
+                data::NonInterleaved::from_input_proc_args(num_frames as u32, buffer_list)
+            };
+            f(Args {
+                data,
+                time_stamp,
+                bus_number,
+                num_frames,
+                flags,
+            })
+        })
+    }
+
     /// Retrieves ownership over the render callback and returns it where it can be re-used or
     /// safely dropped.
     pub fn free_render_callback(&mut self) -> Option<Box<InputProcFnWrapper>> {
@@ -724,23 +1225,10 @@ impl AudioUnit {
     /// safely dropped.
     pub fn free_input_callback(&mut self) -> Option<Box<InputProcFnWrapper>> {
         if let Some(input_callback) = self.maybe_input_callback.take() {
-            let super::InputCallback {
-                buffer_list,
-                callback,
-            } = input_callback;
+            let super::InputCallback { buffer, callback } = input_callback;
             unsafe {
-                // Take ownership over the AudioBufferList in order to safely free it.
-                let buffer_list: Box<sys::AudioBufferList> = Box::from_raw(buffer_list);
-                // Free the allocated data from the individual audio buffers.
-                let ptr = buffer_list.mBuffers.as_ptr() as *const sys::AudioBuffer;
-                let len = buffer_list.mNumberBuffers as usize;
-                let buffers: &[sys::AudioBuffer] = slice::from_raw_parts(ptr, len);
-                for &buffer in buffers {
-                    let ptr = buffer.mData as *mut u8;
-                    let len = buffer.mDataByteSize as usize;
-                    let cap = len;
-                    let _ = Vec::from_raw_parts(ptr, len, cap);
-                }
+                // Take ownership over the input buffer in order to safely free it.
+                drop(Box::from_raw(buffer));
                 // Take ownership over the callback so that it can be freed.
                 let callback: Box<InputProcFnWrapper> = Box::from_raw(callback);
                 return Some(callback);
@@ -750,6 +1238,60 @@ impl AudioUnit {
     }
 }
 
+/// Wrap the given render callback function within a closure that matches the arguments of the
+/// required coreaudio "input_proc".
+///
+/// This allows us to take advantage of rust's type system and provide format-specific `Args`
+/// types which can be checked at compile time.
+fn render_proc_fn<'a, F, D>(mut f: F) -> Box<ScopedInputProcFn<'a>>
+where
+    F: FnMut(Args<D>) -> Result<(), ()> + 'a,
+    D: Data,
+{
+    Box::new(
+        move |io_action_flags: *mut sys::AudioUnitRenderActionFlags,
+              in_time_stamp: *const sys::AudioTimeStamp,
+              in_bus_number: sys::UInt32,
+              in_number_frames: sys::UInt32,
+              io_data: *mut sys::AudioBufferList|
+              -> sys::OSStatus {
+            let args = unsafe {
+                render_callback_args(
+                    io_action_flags,
+                    in_time_stamp,
+                    in_bus_number,
+                    in_number_frames,
+                    io_data,
+                )
+            };
+
+            match f(args) {
+                Ok(()) => 0,
+                Err(()) => error::Error::Unspecified.as_os_status(),
+            }
+        },
+    )
+}
+
+/// Construct the `Args` given to a render callback from the arguments of the input proc.
+unsafe fn render_callback_args<D: Data>(
+    io_action_flags: *mut sys::AudioUnitRenderActionFlags,
+    in_time_stamp: *const sys::AudioTimeStamp,
+    in_bus_number: sys::UInt32,
+    in_number_frames: sys::UInt32,
+    io_data: *mut sys::AudioBufferList,
+) -> Args<D> {
+    let data = D::from_input_proc_args(in_number_frames, io_data);
+    let flags = action_flags::Handle::from_ptr(io_action_flags);
+    Args {
+        data,
+        time_stamp: *in_time_stamp,
+        flags,
+        bus_number: in_bus_number as u32,
+        num_frames: in_number_frames as usize,
+    }
+}
+
 /// Callback procedure that will be called each time our audio_unit requests audio.
 extern "C" fn input_proc(
     in_ref_con: *mut c_void,
@@ -760,6 +1302,7 @@ extern "C" fn input_proc(
     io_data: *mut sys::AudioBufferList,
 ) -> sys::OSStatus {
     let wrapper = in_ref_con as *mut InputProcFnWrapper;
+    let _guard = RenderGuard::enter();
     unsafe {
         (*(*wrapper).callback)(
             io_action_flags,
@@ -770,3 +1313,165 @@ extern "C" fn input_proc(
         )
     }
 }
+
+/// Owned storage for planar audio along with an `AudioBufferList` describing it.
+///
+/// Used to present deinterleaved input to callbacks expecting `data::NonInterleaved`. The storage
+/// is allocated for a maximum number of frames up front, so loading input never allocates.
+struct PlanarBuffers<S> {
+    // The samples of every channel, with the current frames of each channel stored one channel
+    // after another. Allocated for `max_frames` frames.
+    samples: Vec<S>,
+    // Storage for a variable-length `AudioBufferList` with one `AudioBuffer` per channel.
+    //
+    // The extra element leaves room for the `mNumberBuffers` header preceding the buffers.
+    buffer_list: Vec<sys::AudioBuffer>,
+    channels: usize,
+    max_frames: usize,
+}
+
+impl<S> PlanarBuffers<S>
+where
+    S: Copy + Default,
+{
+    fn new(channels: usize, max_frames: u32) -> Self {
+        let empty_buffer = sys::AudioBuffer {
+            mNumberChannels: 1,
+            mDataByteSize: 0,
+            mData: std::ptr::null_mut(),
+        };
+        let max_frames = max_frames as usize;
+        PlanarBuffers {
+            samples: vec![S::default(); channels * max_frames],
+            buffer_list: vec![empty_buffer; channels + 1],
+            channels,
+            max_frames,
+        }
+    }
+
+    /// Deinterleave the given interleaved frames into the planar buffers, returning the
+    /// `AudioBufferList` describing them.
+    ///
+    /// Returns `None` if there are more frames than the buffers were allocated for.
+    fn load(&mut self, interleaved: &[S]) -> Option<*mut sys::AudioBufferList> {
+        let frames = interleaved.len() / self.channels.max(1);
+        if frames > self.max_frames {
+            return None;
+        }
+        let samples = &mut self.samples[..frames * self.channels];
+        deinterleave(interleaved, self.channels, samples);
+
+        let list = self.buffer_list.as_mut_ptr() as *mut sys::AudioBufferList;
+        let channel_byte_size = frames * mem::size_of::<S>();
+        unsafe {
+            (*list).mNumberBuffers = self.channels as u32;
+            let buffers = (*list).mBuffers.as_mut_ptr();
+            for channel in 0..self.channels {
+                *buffers.add(channel) = sys::AudioBuffer {
+                    mNumberChannels: 1,
+                    mDataByteSize: channel_byte_size as u32,
+                    mData: samples.as_mut_ptr().add(channel * frames) as *mut c_void,
+                };
+            }
+        }
+        Some(list)
+    }
+}
+
+/// Deinterleave the frames of `interleaved` into `planar`, storing each channel one after another.
+///
+/// `planar` must be the same length as `interleaved`.
+fn deinterleave<S: Copy>(interleaved: &[S], channels: usize, planar: &mut [S]) {
+    if channels == 0 {
+        return;
+    }
+    let frames = interleaved.len() / channels;
+    for (i, frame) in interleaved.chunks(channels).enumerate() {
+        for (channel, &sample) in frame.iter().enumerate() {
+            planar[channel * frames + i] = sample;
+        }
+    }
+}
+
+#[test]
+fn test_args_time_stamp() {
+    let mut raw_flags: sys::AudioUnitRenderActionFlags = 0;
+    let mut time_stamp: sys::AudioTimeStamp = unsafe { mem::zeroed() };
+    time_stamp.mSampleTime = 512.0;
+    time_stamp.mHostTime = 123_456_789;
+    time_stamp.mWordClockTime = 42;
+    time_stamp.mFlags = sys::kAudioTimeStampSampleHostTimeValid;
+    let mut args = Args {
+        data: data::Raw {
+            data: ptr::null_mut(),
+        },
+        time_stamp,
+        bus_number: 0,
+        num_frames: 0,
+        flags: action_flags::Handle::from_ptr(&mut raw_flags),
+    };
+    assert_eq!(args.sample_time(), Some(512.0));
+    assert_eq!(args.host_time(), Some(123_456_789));
+    assert_eq!(args.rate_scalar(), None);
+    assert_eq!(args.word_clock_time(), None);
+    args.set_output_is_silence();
+    assert_eq!(raw_flags, sys::kAudioUnitRenderAction_OutputIsSilence);
+}
+
+#[test]
+fn test_deinterleave_stereo() {
+    let interleaved = [0.0f32, 1.0, 0.1, 1.1, 0.2, 1.2, 0.3, 1.3];
+    let mut planar = [0.0f32; 8];
+    deinterleave(&interleaved, 2, &mut planar);
+    assert_eq!(planar, [0.0, 0.1, 0.2, 0.3, 1.0, 1.1, 1.2, 1.3]);
+}
+
+#[test]
+fn test_planar_buffers_load() {
+    let interleaved = [0.0f32, 1.0, 0.1, 1.1, 0.2, 1.2];
+    let mut planar = PlanarBuffers::<f32>::new(2, 4);
+    let list = planar.load(&interleaved).unwrap();
+    unsafe {
+        assert_eq!((*list).mNumberBuffers, 2);
+        let right = *(*list).mBuffers.as_ptr().add(1);
+        assert_eq!(right.mDataByteSize, 12);
+        let right = std::slice::from_raw_parts(right.mData as *const f32, 3);
+        assert_eq!(right, [1.0, 1.1, 1.2]);
+    }
+    assert!(planar.load(&[0.0; 10]).is_none());
+}
+
+#[cfg(feature = "assert_no_alloc")]
+#[test]
+fn test_input_buffers_do_not_allocate_while_rendering() {
+    let mut input_buffer = InputBuffer::new(4 * 2 * mem::size_of::<f32>(), 2);
+    let mut planar = PlanarBuffers::<f32>::new(2, 4);
+    let interleaved = [0.5f32; 8];
+    let mut scratch_pool = vec![0.0f32; 8];
+
+    // The allocator aborts the test should any of these allocate or free memory.
+    let guard = RenderGuard::enter();
+    assert!(input_buffer.set_byte_size(3 * 2 * mem::size_of::<f32>()));
+    assert!(!input_buffer.set_byte_size(5 * 2 * mem::size_of::<f32>()));
+    unsafe { input_buffer.zero() };
+    assert!(planar.load(&interleaved).is_some());
+    assert!(planar.load(&[0.0; 10]).is_none());
+    let mut scratch = RenderScratch::new(&mut scratch_pool);
+    assert!(scratch.take(8).is_some());
+    drop(guard);
+}
+
+#[test]
+fn test_render_scratch_take() {
+    let mut pool = vec![1.0f32; 8];
+    let mut scratch = RenderScratch::new(&mut pool);
+    let left = scratch.take(3).unwrap();
+    let right = scratch.take(3).unwrap();
+    left[0] = 0.5;
+    right[0] = -0.5;
+    assert_eq!(left, [0.5, 0.0, 0.0]);
+    assert_eq!(right, [-0.5, 0.0, 0.0]);
+    assert_eq!(scratch.remaining(), 2);
+    assert!(scratch.take(3).is_none());
+    assert_eq!(scratch.take(2).unwrap(), [0.0, 0.0]);
+}
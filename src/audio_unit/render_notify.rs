@@ -0,0 +1,287 @@
+//! Observing the render cycle of an **AudioUnit** via a render notification callback.
+//!
+//! Render notifications are called on the render thread immediately before and after each render
+//! of the unit, so everything they do must be real-time safe.
+
+use super::no_alloc::RenderGuard;
+use super::render_callback::action_flags::ActionFlags;
+use super::AudioUnit;
+use crate::error::Error;
+use std::mem;
+use std::os::raw::c_void;
+use std::sync::atomic::{self, AtomicU32, AtomicU64, Ordering};
+use sys;
+
+/// Arguments given to a render notification registered via `AudioUnit::add_render_notify`.
+#[derive(Debug)]
+pub struct RenderNotifyArgs {
+    /// Whether the notification precedes (`PRE_RENDER`) or follows (`POST_RENDER`) the render,
+    /// along with the other flags of the render, e.g. `OUTPUT_IS_SILENCE` after a silent render.
+    pub flags: ActionFlags,
+    /// Timing information for the render.
+    pub time_stamp: sys::AudioTimeStamp,
+    /// The output bus being rendered.
+    pub bus_number: u32,
+    /// The number of frames being rendered.
+    pub num_frames: usize,
+    /// The buffers being rendered into.
+    ///
+    /// These only hold the rendered audio after the render, and are invalid if the render failed
+    /// as indicated by the `POST_RENDER_ERROR` flag.
+    pub data: *mut sys::AudioBufferList,
+}
+
+/// Identifies a notification registered via `AudioUnit::add_render_notify`, so that it may later
+/// be removed via `AudioUnit::remove_render_notify`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RenderNotifyId(usize);
+
+/// A boxed render notification closure.
+pub(crate) type RenderNotify = Box<dyn FnMut(&RenderNotifyArgs) + Send>;
+
+/// The most recent render `AudioTimeStamp`, shared between the render thread and other threads.
+///
+/// This is a sequence lock: the render thread is the only writer and never blocks, while readers
+/// retry until they observe a consistent snapshot.
+pub(crate) struct RenderTimestamp {
+    // Odd while a write is in progress, zero if nothing has been written yet.
+    sequence: AtomicU32,
+    sample_time: AtomicU64,
+    host_time: AtomicU64,
+    rate_scalar: AtomicU64,
+    flags: AtomicU32,
+}
+
+impl RenderTimestamp {
+    fn new() -> Self {
+        RenderTimestamp {
+            sequence: AtomicU32::new(0),
+            sample_time: AtomicU64::new(0),
+            host_time: AtomicU64::new(0),
+            rate_scalar: AtomicU64::new(0),
+            flags: AtomicU32::new(0),
+        }
+    }
+
+    /// Store the given timestamp. Must only be called from a single thread at a time.
+    fn store(&self, time_stamp: &sys::AudioTimeStamp) {
+        let sequence = self.sequence.load(Ordering::Relaxed);
+        self.sequence
+            .store(sequence.wrapping_add(1), Ordering::Relaxed);
+        atomic::fence(Ordering::Release);
+        self.sample_time
+            .store(time_stamp.mSampleTime.to_bits(), Ordering::Relaxed);
+        self.host_time
+            .store(time_stamp.mHostTime, Ordering::Relaxed);
+        self.rate_scalar
+            .store(time_stamp.mRateScalar.to_bits(), Ordering::Relaxed);
+        self.flags.store(time_stamp.mFlags, Ordering::Relaxed);
+        self.sequence
+            .store(sequence.wrapping_add(2), Ordering::Release);
+    }
+
+    /// Load the most recently stored timestamp, if any.
+    fn load(&self) -> Option<sys::AudioTimeStamp> {
+        loop {
+            let before = self.sequence.load(Ordering::Acquire);
+            if before == 0 {
+                return None;
+            }
+            if before % 2 == 1 {
+                std::hint::spin_loop();
+                continue;
+            }
+            let sample_time = self.sample_time.load(Ordering::Relaxed);
+            let host_time = self.host_time.load(Ordering::Relaxed);
+            let rate_scalar = self.rate_scalar.load(Ordering::Relaxed);
+            let flags = self.flags.load(Ordering::Relaxed);
+            atomic::fence(Ordering::Acquire);
+            if self.sequence.load(Ordering::Relaxed) != before {
+                continue;
+            }
+
+            // Only the sample time, host time and rate scalar are recorded.
+            let valid = sys::kAudioTimeStampSampleTimeValid
+                | sys::kAudioTimeStampHostTimeValid
+                | sys::kAudioTimeStampRateScalarValid;
+            let mut time_stamp: sys::AudioTimeStamp = unsafe { mem::zeroed() };
+            time_stamp.mSampleTime = f64::from_bits(sample_time);
+            time_stamp.mHostTime = host_time;
+            time_stamp.mRateScalar = f64::from_bits(rate_scalar);
+            time_stamp.mFlags = flags & valid;
+            return Some(time_stamp);
+        }
+    }
+}
+
+impl AudioUnit {
+    /// Start recording the timestamp of each render of the **AudioUnit**, so that it may be read
+    /// via `last_render_timestamp`.
+    ///
+    /// This installs a render notification on the unit. Calling this more than once has no
+    /// further effect.
+    pub fn enable_render_timestamps(&mut self) -> Result<(), Error> {
+        if self.render_timestamp.is_some() {
+            return Ok(());
+        }
+        let render_timestamp = Box::new(RenderTimestamp::new());
+        let ptr = &*render_timestamp as *const RenderTimestamp as *mut c_void;
+        let status = unsafe {
+            sys::AudioUnitAddRenderNotify(self.instance, Some(render_timestamp_notify), ptr)
+        };
+        Error::from_os_status(status)?;
+        self.render_timestamp = Some(render_timestamp);
+        Ok(())
+    }
+
+    /// The timestamp of the most recently completed render of the **AudioUnit**.
+    ///
+    /// Returns `None` if `enable_render_timestamps` has not been called or the unit has not
+    /// rendered since. Only the sample time, host time and rate scalar fields are recorded, as
+    /// indicated by the `mFlags` of the returned timestamp.
+    ///
+    /// Comparing the host times of the input and output units' renders gives the round-trip
+    /// latency of a duplex setup.
+    pub fn last_render_timestamp(&self) -> Option<sys::AudioTimeStamp> {
+        self.render_timestamp.as_ref().and_then(|ts| ts.load())
+    }
+
+    /// Register a closure that is called on the render thread immediately before and after each
+    /// render of the **AudioUnit**, e.g. for metering its output without replacing its render
+    /// callback.
+    ///
+    /// The closure is called twice per render, distinguished by the `PRE_RENDER` and
+    /// `POST_RENDER` flags of its arguments. It must be real-time safe. It remains registered
+    /// until it is removed via `remove_render_notify` or the **AudioUnit** is dropped.
+    pub fn add_render_notify<F>(&mut self, f: F) -> Result<RenderNotifyId, Error>
+    where
+        F: FnMut(&RenderNotifyArgs) + Send + 'static,
+    {
+        let notify: Box<RenderNotify> = Box::new(Box::new(f));
+        // Relinquish ownership of the closure so that it can be passed to Core Audio as the user
+        // data. The pointer is converted back into a `Box` once the notification is removed.
+        let notify_ptr = Box::into_raw(notify);
+        let status = unsafe {
+            sys::AudioUnitAddRenderNotify(self.instance, Some(render_notify_proc), notify_ptr as _)
+        };
+        if let Err(err) = Error::from_os_status(status) {
+            unsafe { drop(Box::from_raw(notify_ptr)) };
+            return Err(err);
+        }
+        self.render_notifies.push(notify_ptr);
+        Ok(RenderNotifyId(notify_ptr as usize))
+    }
+
+    /// Unregister and drop the notification registered via `add_render_notify`.
+    ///
+    /// Removing a notification that has already been removed has no effect.
+    pub fn remove_render_notify(&mut self, notify: RenderNotifyId) -> Result<(), Error> {
+        let index = self
+            .render_notifies
+            .iter()
+            .position(|&notify_ptr| notify_ptr as usize == notify.0);
+        let notify_ptr = match index {
+            Some(index) => self.render_notifies.remove(index),
+            None => return Ok(()),
+        };
+        let status = unsafe {
+            sys::AudioUnitRemoveRenderNotify(
+                self.instance,
+                Some(render_notify_proc),
+                notify_ptr as _,
+            )
+        };
+        if let Err(err) = Error::from_os_status(status) {
+            // The unit may still call the closure, so keep it alive until the unit is dropped.
+            self.render_notifies.push(notify_ptr);
+            return Err(err);
+        }
+        unsafe { drop(Box::from_raw(notify_ptr)) };
+        Ok(())
+    }
+
+    /// Unregister and drop all notifications registered via `add_render_notify`.
+    pub(crate) fn free_render_notifies(&mut self) {
+        for notify_ptr in self.render_notifies.drain(..) {
+            // We don't want to panic in `drop`, so we'll ignore returned errors.
+            let status = unsafe {
+                sys::AudioUnitRemoveRenderNotify(
+                    self.instance,
+                    Some(render_notify_proc),
+                    notify_ptr as _,
+                )
+            };
+            Error::from_os_status(status).ok();
+            unsafe { drop(Box::from_raw(notify_ptr)) };
+        }
+    }
+
+    /// Remove the render notification installed by `enable_render_timestamps`, if any.
+    pub(crate) fn free_render_timestamp(&mut self) {
+        if let Some(render_timestamp) = self.render_timestamp.take() {
+            let ptr = &*render_timestamp as *const RenderTimestamp as *mut c_void;
+            // We don't want to panic in `drop`, so we'll ignore returned errors.
+            let status = unsafe {
+                sys::AudioUnitRemoveRenderNotify(self.instance, Some(render_timestamp_notify), ptr)
+            };
+            Error::from_os_status(status).ok();
+        }
+    }
+}
+
+/// Render notification procedure calling a closure registered via `add_render_notify`.
+unsafe extern "C" fn render_notify_proc(
+    in_ref_con: *mut c_void,
+    io_action_flags: *mut sys::AudioUnitRenderActionFlags,
+    in_time_stamp: *const sys::AudioTimeStamp,
+    in_bus_number: sys::UInt32,
+    in_number_frames: sys::UInt32,
+    io_data: *mut sys::AudioBufferList,
+) -> sys::OSStatus {
+    if in_time_stamp.is_null() {
+        return 0;
+    }
+    let notify = &mut *(in_ref_con as *mut RenderNotify);
+    let _guard = RenderGuard::enter();
+    let args = RenderNotifyArgs {
+        flags: ActionFlags::from_bits_truncate(*io_action_flags),
+        time_stamp: *in_time_stamp,
+        bus_number: in_bus_number,
+        num_frames: in_number_frames as usize,
+        data: io_data,
+    };
+    notify(&args);
+    0
+}
+
+/// Render notification procedure recording the timestamp of each completed render.
+unsafe extern "C" fn render_timestamp_notify(
+    in_ref_con: *mut c_void,
+    io_action_flags: *mut sys::AudioUnitRenderActionFlags,
+    in_time_stamp: *const sys::AudioTimeStamp,
+    _in_bus_number: sys::UInt32,
+    _in_number_frames: sys::UInt32,
+    _io_data: *mut sys::AudioBufferList,
+) -> sys::OSStatus {
+    if *io_action_flags & sys::kAudioUnitRenderAction_PostRender != 0 && !in_time_stamp.is_null() {
+        let render_timestamp = &*(in_ref_con as *const RenderTimestamp);
+        render_timestamp.store(&*in_time_stamp);
+    }
+    0
+}
+
+#[test]
+fn test_render_timestamp_store_load() {
+    let render_timestamp = RenderTimestamp::new();
+    assert!(render_timestamp.load().is_none());
+    let mut time_stamp: sys::AudioTimeStamp = unsafe { mem::zeroed() };
+    time_stamp.mSampleTime = 512.0;
+    time_stamp.mHostTime = 123_456_789;
+    time_stamp.mRateScalar = 1.0;
+    time_stamp.mFlags = sys::kAudioTimeStampSampleHostTimeValid;
+    render_timestamp.store(&time_stamp);
+    let loaded = render_timestamp.load().unwrap();
+    assert_eq!(loaded.mSampleTime, 512.0);
+    assert_eq!(loaded.mHostTime, 123_456_789);
+    assert_eq!(loaded.mFlags, sys::kAudioTimeStampSampleHostTimeValid);
+}
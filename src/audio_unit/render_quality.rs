@@ -0,0 +1,88 @@
+//! Trading the quality of an **AudioUnit**'s rendering against its CPU usage.
+//!
+//! Find the original `kAudioUnitProperty_RenderQuality` and `kAudioUnitProperty_CPULoad`
+//! documentation [here](https://developer.apple.com/documentation/audiotoolbox/kaudiounitproperty_renderquality)
+//! and [here](https://developer.apple.com/documentation/audiotoolbox/kaudiounitproperty_cpuload).
+
+use super::{property, AudioUnit, Element, Scope};
+use crate::error::Error;
+
+/// The quality at which an **AudioUnit** renders, as used by e.g. converter, time-pitch and
+/// reverb units.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RenderQuality {
+    /// The lowest quality and cheapest rendering.
+    Min = 0,
+    /// Low quality rendering.
+    Low = 0x20,
+    /// Medium quality rendering.
+    Medium = 0x40,
+    /// High quality rendering, the default of most units.
+    High = 0x60,
+    /// The highest quality and most expensive rendering.
+    Max = 0x7F,
+}
+
+impl RenderQuality {
+    /// Create a **RenderQuality** from its associated `u32`.
+    ///
+    /// The property may hold any value from `0` to `0x7F`, so values between the named
+    /// qualities are rounded down to the nearest one.
+    pub fn from_u32(u: u32) -> RenderQuality {
+        match u {
+            0x7F..=u32::MAX => RenderQuality::Max,
+            0x60..=0x7E => RenderQuality::High,
+            0x40..=0x5F => RenderQuality::Medium,
+            0x20..=0x3F => RenderQuality::Low,
+            _ => RenderQuality::Min,
+        }
+    }
+}
+
+impl AudioUnit {
+    /// The quality at which the unit renders.
+    pub fn render_quality(&self) -> Result<RenderQuality, Error> {
+        let id = property::RENDER_QUALITY;
+        let quality: u32 = self.get_property(id, Scope::Global, Element::Output)?;
+        Ok(RenderQuality::from_u32(quality))
+    }
+
+    /// Set the quality at which the unit renders, e.g. lowering it to reduce the CPU usage of a
+    /// time-pitch or converter unit.
+    ///
+    /// Units that don't support choosing a quality return
+    /// `Error::AudioUnit(AudioUnitError::InvalidProperty)`.
+    pub fn set_render_quality(&mut self, quality: RenderQuality) -> Result<(), Error> {
+        let id = property::RENDER_QUALITY;
+        let quality = quality as u32;
+        self.set_property(id, Scope::Global, Element::Output, Some(&quality))
+    }
+
+    /// The fraction of the CPU, from `0.0` to `1.0`, that the unit is permitted to use.
+    ///
+    /// This is a limit set by the host rather than a measurement, and `0.0` means that no limit
+    /// has been set. Units that honour it degrade their rendering, e.g. by dropping reverb
+    /// reflections, to stay within it.
+    pub fn cpu_load(&self) -> Result<f64, Error> {
+        let id = property::CPU_LOAD;
+        self.get_property(id, Scope::Global, Element::Output)
+    }
+
+    /// Set the fraction of the CPU, from `0.0` to `1.0`, that the unit is permitted to use.
+    ///
+    /// The value is clamped to `0.0..=1.0`.
+    pub fn set_cpu_load(&mut self, load: f64) -> Result<(), Error> {
+        let id = property::CPU_LOAD;
+        let load = load.clamp(0.0, 1.0);
+        self.set_property(id, Scope::Global, Element::Output, Some(&load))
+    }
+}
+
+#[test]
+fn test_render_quality_from_u32() {
+    assert_eq!(RenderQuality::from_u32(0), RenderQuality::Min);
+    assert_eq!(RenderQuality::from_u32(0x20), RenderQuality::Low);
+    assert_eq!(RenderQuality::from_u32(0x5F), RenderQuality::Medium);
+    assert_eq!(RenderQuality::from_u32(0x60), RenderQuality::High);
+    assert_eq!(RenderQuality::from_u32(0x7F), RenderQuality::Max);
+}
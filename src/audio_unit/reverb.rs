@@ -0,0 +1,71 @@
+//! A typed wrapper around the **Reverb2** effect **AudioUnit**.
+//!
+//! The reverb is built from a series of delayed reflections whose level decays over time, with
+//! the decay time set independently at low (0 Hz) and high (Nyquist) frequencies.
+//!
+//! Find the original reverb parameter documentation [here](https://developer.apple.com/documentation/audiotoolbox/1534134-reverb2_parameters).
+
+use super::{EffectType, ParameterId};
+use crate::error::Error;
+
+// The `kReverb2Param_*` parameters, all of which live in the `Global` scope.
+const DRY_WET_MIX: ParameterId = 0;
+const GAIN: ParameterId = 1;
+const MIN_DELAY_TIME: ParameterId = 2;
+const MAX_DELAY_TIME: ParameterId = 3;
+const DECAY_TIME_AT_0_HZ: ParameterId = 4;
+const DECAY_TIME_AT_NYQUIST: ParameterId = 5;
+const RANDOMIZE_REFLECTIONS: ParameterId = 6;
+
+unit_wrapper!(
+    /// A **Reverb2** **AudioUnit** with typed setters for its parameters.
+    Reverb,
+    EffectType::Reverb2,
+    [EffectType::Reverb2],
+    "**Reverb2**",
+    "not a **Reverb2** unit"
+);
+
+impl Reverb {
+    /// Set the proportion of reverberated signal in the output as a percentage, within
+    /// `0.0..=100.0` where `0.0` is entirely dry.
+    pub fn set_dry_wet_mix(&mut self, percent: f32) -> Result<(), Error> {
+        self.set_clamped_parameter(DRY_WET_MIX, percent)
+    }
+
+    /// Set the gain of the output in dB, within `-20.0..=20.0`.
+    pub fn set_gain(&mut self, db: f32) -> Result<(), Error> {
+        self.set_clamped_parameter(GAIN, db)
+    }
+
+    /// Set the delay of the first reflection in seconds, within `0.0001..=1.0`.
+    ///
+    /// This acts as the pre-delay of the reverb.
+    pub fn set_min_delay_time(&mut self, seconds: f32) -> Result<(), Error> {
+        self.set_clamped_parameter(MIN_DELAY_TIME, seconds)
+    }
+
+    /// Set the delay of the last reflection in seconds, within `0.0001..=1.0`.
+    pub fn set_max_delay_time(&mut self, seconds: f32) -> Result<(), Error> {
+        self.set_clamped_parameter(MAX_DELAY_TIME, seconds)
+    }
+
+    /// Set the time taken for low frequencies to decay by 60 dB in seconds, within
+    /// `0.001..=20.0`.
+    pub fn set_decay_time_at_0_hz(&mut self, seconds: f32) -> Result<(), Error> {
+        self.set_clamped_parameter(DECAY_TIME_AT_0_HZ, seconds)
+    }
+
+    /// Set the time taken for high frequencies to decay by 60 dB in seconds, within
+    /// `0.001..=20.0`.
+    pub fn set_decay_time_at_nyquist(&mut self, seconds: f32) -> Result<(), Error> {
+        self.set_clamped_parameter(DECAY_TIME_AT_NYQUIST, seconds)
+    }
+
+    /// Set the number of randomized reflections, within `1..=1000`.
+    ///
+    /// More reflections produce a denser, more diffuse reverb at a higher CPU cost.
+    pub fn set_randomize_reflections(&mut self, reflections: u32) -> Result<(), Error> {
+        self.set_clamped_parameter(RANDOMIZE_REFLECTIONS, reflections as f32)
+    }
+}
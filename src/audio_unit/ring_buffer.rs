@@ -0,0 +1,103 @@
+//! A lock-free single-producer, single-consumer ring buffer of `f32` samples.
+//!
+//! Used to hand audio between a render callback and a non-real-time thread without the render
+//! thread ever blocking or allocating.
+
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+
+/// A fixed capacity ring buffer of `f32` samples.
+///
+/// Only a single thread may `push` and only a single thread may `pop` at any one time.
+pub(crate) struct RingBuffer {
+    // The bits of each `f32` sample. One slot is always left empty to distinguish full from empty.
+    slots: Vec<AtomicU32>,
+    // The index of the next slot to be read, only written by the consumer.
+    head: AtomicUsize,
+    // The index of the next slot to be written, only written by the producer.
+    tail: AtomicUsize,
+}
+
+impl RingBuffer {
+    /// Allocate a ring buffer able to hold `capacity` samples.
+    pub fn new(capacity: usize) -> Self {
+        RingBuffer {
+            slots: (0..capacity + 1).map(|_| AtomicU32::new(0)).collect(),
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        }
+    }
+
+    /// Push as many of the given samples as fit, returning the number pushed.
+    pub fn push(&self, samples: &[f32]) -> usize {
+        let head = self.head.load(Ordering::Acquire);
+        let mut tail = self.tail.load(Ordering::Relaxed);
+        let mut pushed = 0;
+        for &sample in samples {
+            let next = (tail + 1) % self.slots.len();
+            if next == head {
+                break;
+            }
+            self.slots[tail].store(sample.to_bits(), Ordering::Relaxed);
+            tail = next;
+            pushed += 1;
+        }
+        self.tail.store(tail, Ordering::Release);
+        pushed
+    }
+
+    /// Pop as many available samples as fit into `samples`, returning the number popped.
+    pub fn pop(&self, samples: &mut [f32]) -> usize {
+        let tail = self.tail.load(Ordering::Acquire);
+        let mut head = self.head.load(Ordering::Relaxed);
+        let mut popped = 0;
+        while head != tail && popped < samples.len() {
+            samples[popped] = f32::from_bits(self.slots[head].load(Ordering::Relaxed));
+            head = (head + 1) % self.slots.len();
+            popped += 1;
+        }
+        self.head.store(head, Ordering::Release);
+        popped
+    }
+
+    /// Pop all available samples, appending them to `samples`. Returns the number popped.
+    pub fn pop_into(&self, samples: &mut Vec<f32>) -> usize {
+        let tail = self.tail.load(Ordering::Acquire);
+        let mut head = self.head.load(Ordering::Relaxed);
+        let mut popped = 0;
+        while head != tail {
+            samples.push(f32::from_bits(self.slots[head].load(Ordering::Relaxed)));
+            head = (head + 1) % self.slots.len();
+            popped += 1;
+        }
+        self.head.store(head, Ordering::Release);
+        popped
+    }
+}
+
+#[test]
+fn test_ring_buffer_wraps_and_drops_overflow() {
+    let ring_buffer = RingBuffer::new(4);
+    assert_eq!(ring_buffer.push(&[1.0, 2.0, 3.0]), 3);
+    let mut samples = Vec::new();
+    assert_eq!(ring_buffer.pop_into(&mut samples), 3);
+    assert_eq!(samples, [1.0, 2.0, 3.0]);
+
+    // The buffer is now wrapped around its end, and only holds four samples.
+    assert_eq!(ring_buffer.push(&[4.0, 5.0, 6.0, 7.0, 8.0]), 4);
+    samples.clear();
+    assert_eq!(ring_buffer.pop_into(&mut samples), 4);
+    assert_eq!(samples, [4.0, 5.0, 6.0, 7.0]);
+    assert_eq!(ring_buffer.pop_into(&mut samples), 0);
+}
+
+#[test]
+fn test_ring_buffer_pop_is_bounded() {
+    let ring_buffer = RingBuffer::new(4);
+    assert_eq!(ring_buffer.push(&[1.0, 2.0, 3.0]), 3);
+    let mut samples = [0.0; 2];
+    assert_eq!(ring_buffer.pop(&mut samples), 2);
+    assert_eq!(samples, [1.0, 2.0]);
+    assert_eq!(ring_buffer.pop(&mut samples), 1);
+    assert_eq!(samples[0], 3.0);
+    assert_eq!(ring_buffer.pop(&mut samples), 0);
+}
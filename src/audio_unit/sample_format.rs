@@ -3,6 +3,8 @@ use super::audio_format::{self, LinearPcmFlags};
 /// Dynamic representation of audio data sample format.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum SampleFormat {
+    /// 64-bit float.
+    F64,
     /// 32-bit float.
     F32,
     /// 32-bit signed integer.
@@ -13,6 +15,8 @@ pub enum SampleFormat {
     I16,
     /// 8-bit signed integer.
     I8,
+    /// 32-bit signed 8.24 fixed-point, the canonical audio unit format on older versions of iOS.
+    Fixed8_24,
 }
 
 impl SampleFormat {
@@ -21,12 +25,35 @@ impl SampleFormat {
         let is_float = flags.contains(LinearPcmFlags::IS_FLOAT);
         let is_signed_integer = flags.contains(LinearPcmFlags::IS_SIGNED_INTEGER);
         let is_packed = flags.contains(LinearPcmFlags::IS_PACKED);
+        let is_integer = is_signed_integer && !is_float && fraction_bits(flags) == 0;
         match *self {
-            SampleFormat::F32 => is_float && !is_signed_integer && is_packed,
-            SampleFormat::I32 | SampleFormat::I16 | SampleFormat::I8 => {
-                is_signed_integer && !is_float && is_packed
+            SampleFormat::F64 | SampleFormat::F32 => is_float && !is_signed_integer && is_packed,
+            SampleFormat::I32 | SampleFormat::I16 | SampleFormat::I8 => is_integer && is_packed,
+            SampleFormat::I24 => is_integer,
+            SampleFormat::Fixed8_24 => {
+                is_signed_integer
+                    && !is_float
+                    && is_packed
+                    && fraction_bits(flags) == Fixed8_24::FRACTION_BITS
+            }
+        }
+    }
+
+    /// The flags describing packed samples of this format, to which e.g. `IS_NON_INTERLEAVED`
+    /// may be added to form the `flags` of a `StreamFormat`.
+    pub fn linear_pcm_flags(&self) -> LinearPcmFlags {
+        match *self {
+            SampleFormat::F64 | SampleFormat::F32 => {
+                LinearPcmFlags::IS_FLOAT | LinearPcmFlags::IS_PACKED
+            }
+            SampleFormat::I32 | SampleFormat::I24 | SampleFormat::I16 | SampleFormat::I8 => {
+                LinearPcmFlags::IS_SIGNED_INTEGER | LinearPcmFlags::IS_PACKED
+            }
+            SampleFormat::Fixed8_24 => {
+                let fraction =
+                    LinearPcmFlags::from_bits_truncate(Fixed8_24::FRACTION_BITS << FRACTION_SHIFT);
+                LinearPcmFlags::IS_SIGNED_INTEGER | LinearPcmFlags::IS_PACKED | fraction
             }
-            SampleFormat::I24 => is_signed_integer && !is_float,
         }
     }
 
@@ -39,14 +66,16 @@ impl SampleFormat {
         let sample_format = if flags.contains(LinearPcmFlags::IS_FLOAT) {
             match (bits_per_sample, packed) {
                 (32, true) => SampleFormat::F32,
+                (64, true) => SampleFormat::F64,
                 _ => return None,
             }
         } else if flags.contains(LinearPcmFlags::IS_SIGNED_INTEGER) {
-            match (bits_per_sample, packed) {
-                (8, true) => SampleFormat::I8,
-                (16, true) => SampleFormat::I16,
-                (24, _) => SampleFormat::I24,
-                (32, true) => SampleFormat::I32,
+            match (bits_per_sample, packed, fraction_bits(flags)) {
+                (8, true, 0) => SampleFormat::I8,
+                (16, true, 0) => SampleFormat::I16,
+                (24, _, 0) => SampleFormat::I24,
+                (32, true, 0) => SampleFormat::I32,
+                (32, true, Fixed8_24::FRACTION_BITS) => SampleFormat::Fixed8_24,
                 _ => return None,
             }
         } else {
@@ -60,26 +89,64 @@ impl SampleFormat {
     pub fn size_in_bytes(&self) -> usize {
         use std::mem::size_of;
         match *self {
+            SampleFormat::F64 => size_of::<f64>(),
             SampleFormat::F32 => size_of::<f32>(),
             SampleFormat::I32 => size_of::<i32>(),
             SampleFormat::I24 => 3 * size_of::<u8>(),
             SampleFormat::I16 => size_of::<i16>(),
             SampleFormat::I8 => size_of::<i8>(),
+            SampleFormat::Fixed8_24 => size_of::<Fixed8_24>(),
         }
     }
 
     /// Return the number of valid bits for one sample.
     pub fn size_in_bits(&self) -> u32 {
         match *self {
+            SampleFormat::F64 => 64,
             SampleFormat::F32 => 32,
             SampleFormat::I32 => 32,
             SampleFormat::I24 => 24,
             SampleFormat::I16 => 16,
             SampleFormat::I8 => 8,
+            SampleFormat::Fixed8_24 => 32,
         }
     }
 }
 
+/// The bit position of the fraction bitfield within the linear PCM flags.
+const FRACTION_SHIFT: u32 = 7;
+
+/// The number of fractional bits of a fixed-point format, as given by the flags.
+fn fraction_bits(flags: LinearPcmFlags) -> u32 {
+    (flags & LinearPcmFlags::FLAGS_SAMPLE_FRACTION_MASK).bits() >> FRACTION_SHIFT
+}
+
+/// A sample in the signed 8.24 fixed-point format, the canonical audio unit format on older
+/// versions of iOS (`AudioUnitSampleType`).
+///
+/// The wrapped `i32` holds 8 integer bits, including the sign, followed by 24 fractional bits, so
+/// `Fixed8_24(1 << 24)` represents `1.0`.
+#[repr(transparent)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fixed8_24(pub i32);
+
+impl Fixed8_24 {
+    /// The number of fractional bits.
+    pub const FRACTION_BITS: u32 = 24;
+
+    /// Convert from a float, saturating at the limits of the format, i.e. just under `-128.0`
+    /// and `128.0`.
+    pub fn from_f32(value: f32) -> Self {
+        // Float to integer casts saturate, mapping NaN to zero.
+        Fixed8_24((value * (1 << Self::FRACTION_BITS) as f32) as i32)
+    }
+
+    /// Convert to a float, for which `1.0` is full scale.
+    pub fn to_f32(self) -> f32 {
+        self.0 as f32 / (1 << Self::FRACTION_BITS) as f32
+    }
+}
+
 /// Audio data sample types.
 pub trait Sample {
     /// Dynamic representation of audio data sample format.
@@ -100,4 +167,40 @@ macro_rules! impl_sample {
     }
 }
 
-impl_sample!(f32 F32, i32 I32, i16 I16, i8 I8);
+impl_sample!(f64 F64, f32 F32, i32 I32, i16 I16, i8 I8);
+
+impl Sample for Fixed8_24 {
+    fn sample_format() -> SampleFormat {
+        SampleFormat::Fixed8_24
+    }
+}
+
+#[test]
+fn test_sample_format_from_flags() {
+    let formats = [
+        SampleFormat::F64,
+        SampleFormat::F32,
+        SampleFormat::I32,
+        SampleFormat::I24,
+        SampleFormat::I16,
+        SampleFormat::I8,
+        SampleFormat::Fixed8_24,
+    ];
+    for &format in &formats {
+        let flags = format.linear_pcm_flags();
+        assert!(format.does_match_flags(flags));
+        let parsed = SampleFormat::from_flags_and_bits_per_sample(flags, format.size_in_bits());
+        assert_eq!(parsed, Some(format));
+    }
+    // 32-bit integer and 8.24 fixed-point data are only told apart by the fraction bits.
+    assert!(!SampleFormat::I32.does_match_flags(SampleFormat::Fixed8_24.linear_pcm_flags()));
+    assert!(!SampleFormat::Fixed8_24.does_match_flags(SampleFormat::I32.linear_pcm_flags()));
+}
+
+#[test]
+fn test_fixed_8_24() {
+    assert_eq!(Fixed8_24::from_f32(1.0), Fixed8_24(1 << 24));
+    assert_eq!(Fixed8_24::from_f32(-0.5).to_f32(), -0.5);
+    assert_eq!(Fixed8_24::from_f32(1000.0), Fixed8_24(i32::MAX));
+    assert_eq!(Fixed8_24::from_f32(f32::NAN), Fixed8_24(0));
+}
@@ -0,0 +1,195 @@
+//! A sine wave generator playing through the default output device.
+//!
+//! This is both a handy test tone and a reference for correct render callback usage: the callback
+//! never allocates, locks or blocks, and all communication with it happens via atomics.
+
+use super::render_callback::{self, data};
+use super::{AudioUnit, IOType, SampleFormat};
+use crate::error::Error;
+use std::f64::consts::PI;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use sys;
+
+/// The frequency and amplitude of the generator, shared with the render callback.
+struct Controls {
+    // The bits of the `f32` frequency in Hz.
+    frequency: AtomicU32,
+    // The bits of the `f32` amplitude.
+    amplitude: AtomicU32,
+}
+
+impl Controls {
+    fn frequency(&self) -> f32 {
+        f32::from_bits(self.frequency.load(Ordering::Relaxed))
+    }
+
+    fn amplitude(&self) -> f32 {
+        f32::from_bits(self.amplitude.load(Ordering::Relaxed))
+    }
+}
+
+/// A sine oscillator whose phase follows the sample time of the renders it is driven by.
+struct Oscillator {
+    // The phase within the range `0.0..1.0`.
+    phase: f64,
+    // The sample time expected at the start of the next render.
+    next_sample_time: Option<f64>,
+}
+
+impl Oscillator {
+    fn new() -> Self {
+        Oscillator {
+            phase: 0.0,
+            next_sample_time: None,
+        }
+    }
+
+    /// Prepare to render `num_frames` frames starting at `sample_time`.
+    ///
+    /// If frames were skipped since the previous render (e.g. due to an overload), the phase is
+    /// advanced by the skipped frames so that the tone continues where it would have been.
+    fn begin_render(&mut self, sample_time: Option<f64>, num_frames: usize, increment: f64) {
+        if let (Some(expected), Some(actual)) = (self.next_sample_time, sample_time) {
+            let skipped = actual - expected;
+            if skipped > 0.0 {
+                self.phase = (self.phase + skipped * increment).fract();
+            }
+        }
+        self.next_sample_time = sample_time.map(|t| t + num_frames as f64);
+    }
+
+    /// Produce the next sample and advance the phase by `increment`.
+    fn next_sample(&mut self, increment: f64, amplitude: f32) -> f32 {
+        let sample = (self.phase * 2.0 * PI).sin() as f32 * amplitude;
+        self.phase = (self.phase + increment).fract();
+        sample
+    }
+}
+
+/// A sine wave generator rendering to the default output device.
+///
+/// The frequency and amplitude may be changed at any time via `set_frequency` and `set_amplitude`
+/// without blocking the render thread.
+pub struct SineGenerator {
+    audio_unit: AudioUnit,
+    controls: Arc<Controls>,
+}
+
+impl SineGenerator {
+    /// Construct a new **SineGenerator** with the given frequency in Hz and amplitude.
+    ///
+    /// The generator is silent until `start` is called.
+    ///
+    /// Returns `Error::UnsupportedStreamFormat` if the default output unit does not accept `f32`
+    /// samples.
+    pub fn new(frequency: f32, amplitude: f32) -> Result<SineGenerator, Error> {
+        let mut audio_unit = AudioUnit::new(IOType::DefaultOutput)?;
+        let stream_format = audio_unit.output_stream_format()?;
+        if stream_format.sample_format != SampleFormat::F32 {
+            return Err(Error::UnsupportedStreamFormat);
+        }
+        let sample_rate = stream_format.sample_rate;
+
+        let controls = Arc::new(Controls {
+            frequency: AtomicU32::new(frequency.to_bits()),
+            amplitude: AtomicU32::new(amplitude.to_bits()),
+        });
+        let callback_controls = controls.clone();
+        let mut oscillator = Oscillator::new();
+
+        type Args = render_callback::Args<data::NonInterleaved<f32>>;
+        audio_unit.set_render_callback(move |args: Args| {
+            let Args {
+                num_frames,
+                mut data,
+                time_stamp,
+                ..
+            } = args;
+            let increment = callback_controls.frequency() as f64 / sample_rate;
+            let amplitude = callback_controls.amplitude();
+            let sample_time = if time_stamp.mFlags & sys::kAudioTimeStampSampleTimeValid != 0 {
+                Some(time_stamp.mSampleTime)
+            } else {
+                None
+            };
+            oscillator.begin_render(sample_time, num_frames, increment);
+            for i in 0..num_frames {
+                let sample = oscillator.next_sample(increment, amplitude);
+                for channel in data.channels_mut() {
+                    channel[i] = sample;
+                }
+            }
+            Ok(())
+        })?;
+
+        Ok(SineGenerator {
+            audio_unit,
+            controls,
+        })
+    }
+
+    /// Start playing the tone.
+    pub fn start(&mut self) -> Result<(), Error> {
+        self.audio_unit.start()
+    }
+
+    /// Stop playing the tone.
+    pub fn stop(&mut self) -> Result<(), Error> {
+        self.audio_unit.stop()
+    }
+
+    /// The frequency of the tone in Hz.
+    pub fn frequency(&self) -> f32 {
+        self.controls.frequency()
+    }
+
+    /// Set the frequency of the tone in Hz.
+    ///
+    /// This is real-time safe and takes effect from the next render.
+    pub fn set_frequency(&self, frequency: f32) {
+        self.controls
+            .frequency
+            .store(frequency.to_bits(), Ordering::Relaxed);
+    }
+
+    /// The amplitude of the tone, where `1.0` is full scale.
+    pub fn amplitude(&self) -> f32 {
+        self.controls.amplitude()
+    }
+
+    /// Set the amplitude of the tone, where `1.0` is full scale.
+    ///
+    /// This is real-time safe and takes effect from the next render.
+    pub fn set_amplitude(&self, amplitude: f32) {
+        self.controls
+            .amplitude
+            .store(amplitude.to_bits(), Ordering::Relaxed);
+    }
+
+    /// A reference to the underlying **AudioUnit**.
+    pub fn audio_unit(&self) -> &AudioUnit {
+        &self.audio_unit
+    }
+
+    /// A mutable reference to the underlying **AudioUnit**.
+    pub fn audio_unit_mut(&mut self) -> &mut AudioUnit {
+        &mut self.audio_unit
+    }
+}
+
+#[test]
+fn test_oscillator_skips_dropped_frames() {
+    let increment = 0.25;
+    let mut oscillator = Oscillator::new();
+    oscillator.begin_render(Some(0.0), 2, increment);
+    assert_eq!(oscillator.next_sample(increment, 1.0), 0.0);
+    assert_eq!(oscillator.next_sample(increment, 1.0), 1.0);
+    assert_eq!(oscillator.phase, 0.5);
+
+    // The render starting at frame 2 was dropped, so the phase jumps ahead by two frames.
+    oscillator.begin_render(Some(4.0), 2, increment);
+    assert_eq!(oscillator.phase, 0.0);
+    assert_eq!(oscillator.next_sample(increment, 0.5), 0.0);
+    assert_eq!(oscillator.next_sample(increment, 0.5), 0.5);
+}
@@ -0,0 +1,102 @@
+//! A typed wrapper around the spatial mixer **AudioUnit** for positioning sources in 3D space.
+//!
+//! Each mono input bus of the mixer is rendered as a source at some position relative to the
+//! listener. The position is given in polar coordinates:
+//!
+//! - **Azimuth**: the horizontal angle in degrees within the range `-180.0..=180.0`. `0.0` is
+//!   directly in front of the listener, positive values move the source clockwise (to the
+//!   listener's right) and negative values move it anti-clockwise (to the listener's left).
+//!   `180.0` and `-180.0` are both directly behind the listener.
+//! - **Elevation**: the vertical angle in degrees within the range `-90.0..=90.0`. `0.0` is level
+//!   with the listener, `90.0` is directly above and `-90.0` directly below.
+//! - **Distance**: the distance from the listener in meters, starting at `0.0`. Sources further
+//!   away are attenuated according to the mixer's distance attenuation settings.
+//!
+//! Find the original spatial mixer parameter documentation [here](https://developer.apple.com/documentation/audiotoolbox/1584673-3d_mixer_parameters).
+
+use super::{parameter, MixerType, ParameterId, Scope};
+use crate::error::Error;
+use sys;
+
+// The positioning parameters are shared between the spatial mixer and the deprecated 3D mixer
+// (`kSpatialMixerParam_*` and `k3DMixerParam_*` respectively).
+const AZIMUTH: ParameterId = 0;
+const ELEVATION: ParameterId = 1;
+const DISTANCE: ParameterId = 2;
+
+/// The algorithm used by the mixer to render a source at its position.
+///
+/// Original documentation [here](https://developer.apple.com/documentation/audiotoolbox/auspatializationalgorithm).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SpatializationAlgorithm {
+    /// Simple equal power panning, the cheapest of the algorithms.
+    EqualPowerPanning = 0,
+    /// A spherical head model, suited to headphones.
+    SphericalHead = 1,
+    /// Head related transfer function rendering, suited to headphones.
+    Hrtf = 2,
+    /// Rendering via an ambisonic sound field.
+    SoundField = 3,
+    /// Vector based amplitude panning, suited to multichannel speaker layouts.
+    VectorBasedPanning = 4,
+    /// The input is passed straight through to the output without spatialization.
+    StereoPassThrough = 5,
+    /// A higher quality variant of `Hrtf`.
+    HrtfHq = 6,
+    /// Let the mixer choose the algorithm based on the output type.
+    UseOutputType = 7,
+}
+
+unit_wrapper!(
+    /// A spatial mixer **AudioUnit** with typed setters for its positioning parameters.
+    ///
+    /// Units of the deprecated **Mixer3D** subtype are also accepted, as they share the same
+    /// positioning parameters.
+    SpatialMixer,
+    MixerType::SpatialMixer,
+    [MixerType::SpatialMixer, MixerType::Mixer3D],
+    "spatial mixer",
+    "neither a spatial mixer nor a 3D mixer"
+);
+
+impl SpatialMixer {
+    /// Set the azimuth of the source on the given input bus in degrees.
+    ///
+    /// See the [module documentation](./index.html) for the coordinate conventions.
+    pub fn set_input_azimuth(&mut self, bus: u32, degrees: f32) -> Result<(), Error> {
+        self.set_input_parameter(AZIMUTH, bus, degrees)
+    }
+
+    /// Set the elevation of the source on the given input bus in degrees.
+    ///
+    /// See the [module documentation](./index.html) for the coordinate conventions.
+    pub fn set_input_elevation(&mut self, bus: u32, degrees: f32) -> Result<(), Error> {
+        self.set_input_parameter(ELEVATION, bus, degrees)
+    }
+
+    /// Set the distance of the source on the given input bus from the listener in meters.
+    pub fn set_input_distance(&mut self, bus: u32, meters: f32) -> Result<(), Error> {
+        self.set_input_parameter(DISTANCE, bus, meters)
+    }
+
+    /// Set the algorithm used to render the source on the given input bus.
+    pub fn set_rendering_algorithm(
+        &mut self,
+        bus: u32,
+        algorithm: SpatializationAlgorithm,
+    ) -> Result<(), Error> {
+        let id = sys::kAudioUnitProperty_SpatializationAlgorithm;
+        let algorithm = algorithm as u32;
+        super::set_property_raw(
+            self.audio_unit.instance,
+            id,
+            Scope::Input,
+            bus,
+            Some(&algorithm),
+        )
+    }
+
+    fn set_input_parameter(&mut self, id: ParameterId, bus: u32, value: f32) -> Result<(), Error> {
+        parameter::set_parameter(self.audio_unit.instance, id, Scope::Input, bus, value)
+    }
+}
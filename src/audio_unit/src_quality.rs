@@ -0,0 +1,71 @@
+//! Selecting the quality of the sample rate conversion performed by converter units.
+//!
+//! Find the original `kAudioUnitProperty_SampleRateConverterComplexity` documentation [here](https://developer.apple.com/documentation/audiotoolbox/kaudiounitproperty_sampleratecomplexity).
+
+use super::{AudioUnit, Element, FormatConverterType, IOType, Scope, Type};
+use crate::error::{AudioUnitError, Error};
+use sys;
+
+/// The algorithm used by a converter unit to convert between sample rates, trading CPU usage for
+/// quality.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SrcQuality {
+    /// Linear interpolation, the cheapest and lowest quality algorithm.
+    Linear = 1818848869,
+    /// The default algorithm, suited to real-time use.
+    Normal = 1852797549,
+    /// The highest quality algorithm, best suited to offline rendering as it is expensive.
+    Mastering = 1650553971,
+}
+
+impl SrcQuality {
+    /// Create a **SrcQuality** from its associated `u32`.
+    pub fn from_u32(u: u32) -> Option<SrcQuality> {
+        match u {
+            1818848869 => Some(SrcQuality::Linear),
+            1852797549 => Some(SrcQuality::Normal),
+            1650553971 => Some(SrcQuality::Mastering),
+            _ => None,
+        }
+    }
+}
+
+impl AudioUnit {
+    /// The algorithm the unit uses to convert between sample rates.
+    ///
+    /// Returns `Error::AudioUnit(AudioUnitError::InvalidProperty)` for units that do not convert
+    /// sample rates. See `set_src_quality`.
+    pub fn src_quality(&self) -> Result<SrcQuality, Error> {
+        self.expect_sample_rate_converter()?;
+        let id = sys::kAudioUnitProperty_SampleRateConverterComplexity;
+        let quality: u32 = self.get_property(id, Scope::Global, Element::Output)?;
+        SrcQuality::from_u32(quality).ok_or(Error::Unspecified)
+    }
+
+    /// Set the algorithm the unit uses to convert between sample rates.
+    ///
+    /// This applies to format converter units and to I/O units, which convert between the stream
+    /// format of the client and that of the device. For any other unit this returns
+    /// `Error::AudioUnit(AudioUnitError::InvalidProperty)`.
+    ///
+    /// The algorithm may only be changed while the unit is uninitialized on some units.
+    pub fn set_src_quality(&mut self, quality: SrcQuality) -> Result<(), Error> {
+        self.expect_sample_rate_converter()?;
+        let id = sys::kAudioUnitProperty_SampleRateConverterComplexity;
+        let quality = quality as u32;
+        self.set_property(id, Scope::Global, Element::Output, Some(&quality))
+    }
+
+    /// Returns `Ok` if the unit is of a type that converts sample rates.
+    fn expect_sample_rate_converter(&self) -> Result<(), Error> {
+        let component_type = self.component_description()?.componentType;
+        // Only the type of these is compared, so any subtype will do.
+        let converter = Type::FormatConverter(FormatConverterType::AUConverter);
+        let io = Type::IO(IOType::GenericOutput);
+        if component_type == converter.as_u32() || component_type == io.as_u32() {
+            Ok(())
+        } else {
+            Err(Error::AudioUnit(AudioUnitError::InvalidProperty))
+        }
+    }
+}
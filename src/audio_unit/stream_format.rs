@@ -5,7 +5,7 @@
 use super::audio_format::AudioFormat;
 use super::audio_format::LinearPcmFlags;
 use super::SampleFormat;
-use crate::error::{self, Error};
+use crate::error::{self, Error, StreamFormatError};
 use sys;
 
 /// A representation of the AudioStreamBasicDescription specifically for use with the AudioUnit API.
@@ -102,6 +102,38 @@ impl StreamFormat {
         })
     }
 
+    /// Check the StreamFormat for obviously invalid values, returning the first problem found.
+    ///
+    /// This catches formats that an **AudioUnit** would otherwise reject with a less descriptive
+    /// `FormatNotSupported` error.
+    pub fn validate(&self) -> Result<(), Error> {
+        let invalid = |err| Err(Error::InvalidStreamFormat(err));
+        if !self.sample_rate.is_finite() || self.sample_rate <= 0.0 {
+            return invalid(StreamFormatError::InvalidSampleRate);
+        }
+        if self.channels == 0 {
+            return invalid(StreamFormatError::NoChannels);
+        }
+        // `to_asbd` always describes packed samples.
+        if !self
+            .sample_format
+            .does_match_flags(self.flags | LinearPcmFlags::IS_PACKED)
+        {
+            return invalid(StreamFormatError::FlagsDoNotMatchSampleFormat);
+        }
+        let bytes_per_sample = self.sample_format.size_in_bytes() as u32;
+        if bytes_per_sample.checked_mul(self.channels).is_none() {
+            return invalid(StreamFormatError::BytesPerFrameOverflow);
+        }
+        Ok(())
+    }
+
+    /// The same as `to_asbd`, but first checks the StreamFormat via `validate`.
+    pub fn to_asbd_checked(self) -> Result<sys::AudioStreamBasicDescription, Error> {
+        self.validate()?;
+        Ok(self.to_asbd())
+    }
+
     /// Convert a StreamFormat into an AudioStreamBasicDescription.
     /// Note that this function assumes that only packed formats are used.
     /// This only affects I24, since all other formats supported by `StreamFormat`
@@ -117,7 +149,7 @@ impl StreamFormat {
         let (format, maybe_flag) =
             AudioFormat::LinearPCM(flags | LinearPcmFlags::IS_PACKED).as_format_and_flag();
 
-        let flag = maybe_flag.unwrap_or(::std::u32::MAX - 2147483647);
+        let flag = maybe_flag.unwrap_or(u32::MAX - 2147483647);
 
         let non_interleaved = flags.contains(LinearPcmFlags::IS_NON_INTERLEAVED);
         let bytes_per_frame = if non_interleaved {
@@ -142,3 +174,79 @@ impl StreamFormat {
         }
     }
 }
+
+#[cfg(test)]
+fn test_format() -> StreamFormat {
+    StreamFormat {
+        sample_rate: 44_100.0,
+        sample_format: SampleFormat::F32,
+        flags: LinearPcmFlags::IS_FLOAT | LinearPcmFlags::IS_PACKED,
+        channels: 2,
+    }
+}
+
+#[cfg(test)]
+fn validation_error(format: StreamFormat) -> Option<StreamFormatError> {
+    match format.validate() {
+        Ok(()) => None,
+        Err(Error::InvalidStreamFormat(err)) => Some(err),
+        Err(err) => panic!("unexpected error: {}", err),
+    }
+}
+
+#[test]
+fn test_validate_valid_format() {
+    assert_eq!(validation_error(test_format()), None);
+    assert!(test_format().to_asbd_checked().is_ok());
+}
+
+#[test]
+fn test_validate_invalid_sample_rate() {
+    for &sample_rate in &[0.0, -44_100.0, f64::NAN, f64::INFINITY] {
+        let format = StreamFormat {
+            sample_rate,
+            ..test_format()
+        };
+        assert_eq!(
+            validation_error(format),
+            Some(StreamFormatError::InvalidSampleRate)
+        );
+    }
+}
+
+#[test]
+fn test_validate_no_channels() {
+    let format = StreamFormat {
+        channels: 0,
+        ..test_format()
+    };
+    assert_eq!(
+        validation_error(format),
+        Some(StreamFormatError::NoChannels)
+    );
+}
+
+#[test]
+fn test_validate_flags_do_not_match_sample_format() {
+    let format = StreamFormat {
+        sample_format: SampleFormat::I16,
+        ..test_format()
+    };
+    assert_eq!(
+        validation_error(format),
+        Some(StreamFormatError::FlagsDoNotMatchSampleFormat)
+    );
+}
+
+#[test]
+fn test_validate_bytes_per_frame_overflow() {
+    let format = StreamFormat {
+        channels: u32::MAX,
+        ..test_format()
+    };
+    assert_eq!(
+        validation_error(format),
+        Some(StreamFormatError::BytesPerFrameOverflow)
+    );
+    assert!(format.to_asbd_checked().is_err());
+}
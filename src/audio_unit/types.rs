@@ -123,8 +123,140 @@ impl Type {
             _ => None,
         }
     }
+
+    /// Reconstruct a `Type` from the `u32` associated with it and the `u32` associated with its
+    /// subtype, e.g. as found in an `AudioComponentDescription`.
+    ///
+    /// The subtype is ignored for types without subtypes. Returns `None` if either is unknown.
+    pub fn from_u32_pair(ty: u32, sub_type: u32) -> Option<Type> {
+        let ty = match ty {
+            1635086197 => Type::IO(IOType::from_u32(sub_type)?),
+            1635085685 => Type::MusicDevice(MusicDeviceType::from_u32(sub_type)?),
+            1635085670 => Type::MusicEffect,
+            1635083875 => Type::FormatConverter(FormatConverterType::from_u32(sub_type)?),
+            1635083896 => Type::Effect(EffectType::from_u32(sub_type)?),
+            1635085688 => Type::Mixer(MixerType::from_u32(sub_type)?),
+            1635086446 => Type::Panner,
+            1635084142 => Type::Generator(GeneratorType::from_u32(sub_type)?),
+            1635086188 => Type::OfflineEffect,
+            1635085673 => Type::MidiProcessor,
+            _ => return None,
+        };
+        Some(ty)
+    }
+}
+
+/// The broad kind of an **AudioUnit**, for hosts that treat units differently depending on what
+/// they do, e.g. only sending MIDI to instruments.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum UnitCategory {
+    /// Processes audio input into audio output, including panners, offline effects and effects
+    /// that respond to MIDI.
+    Effect,
+    /// Creates audio in response to MIDI, i.e. a `MusicDevice`.
+    Instrument,
+    /// Mixes a number of inputs into one or more outputs.
+    Mixer,
+    /// Provides audio output that has no audio input.
+    Generator,
+    /// Provides input from or output to an audio device, i.e. an `IO` unit.
+    Output,
+    /// Transforms audio formats, e.g. converting sample rates.
+    FormatConverter,
+    /// Processes MIDI rather than audio.
+    MidiProcessor,
+}
+
+impl UnitCategory {
+    /// The category of units with the given component type `u32`, or `None` if it is unknown.
+    pub fn from_type_u32(ty: u32) -> Option<UnitCategory> {
+        let category = match ty {
+            1635086197 => UnitCategory::Output,
+            1635085685 => UnitCategory::Instrument,
+            1635085670 | 1635083896 | 1635086446 | 1635086188 => UnitCategory::Effect,
+            1635083875 => UnitCategory::FormatConverter,
+            1635085688 => UnitCategory::Mixer,
+            1635084142 => UnitCategory::Generator,
+            1635085673 => UnitCategory::MidiProcessor,
+            _ => return None,
+        };
+        Some(category)
+    }
+}
+
+/// Implement `from_u32` for a subtype enum, given every one of its variants.
+macro_rules! impl_subtype_from_u32 {
+    ($subtype:ident { $($variant:ident),* $(,)? }) => {
+        impl $subtype {
+            /// Create the subtype from its associated `u32`.
+            pub fn from_u32(u: u32) -> Option<$subtype> {
+                match u {
+                    $(u if u == $subtype::$variant as u32 => Some($subtype::$variant),)*
+                    _ => None,
+                }
+            }
+        }
+    };
 }
 
+impl_subtype_from_u32!(EffectType {
+    PeakLimiter,
+    DynamicsProcessor,
+    LowPassFilter,
+    HighPassFilter,
+    BandPassFilter,
+    HighShelfFilter,
+    LowShelfFilter,
+    ParametricEQ,
+    Distortion,
+    Delay,
+    SampleDelay,
+    GraphicEQ,
+    MultiBandCompressor,
+    MatrixReverb,
+    Reverb2,
+    Pitch,
+    AUFilter,
+    NetSend,
+    RogerBeep,
+    NBandEQ,
+});
+
+impl_subtype_from_u32!(FormatConverterType {
+    AUConverter,
+    NewTimePitch,
+    TimePitch,
+    DeferredRenderer,
+    Splitter,
+    Merger,
+    Varispeed,
+    AUiPodTimeOther,
+});
+
+impl_subtype_from_u32!(MixerType {
+    MultiChannelMixer,
+    StereoMixer,
+    Mixer3D,
+    SpatialMixer,
+    MatrixMixer,
+});
+
+impl_subtype_from_u32!(GeneratorType {
+    ScheduledSoundPlayer,
+    AudioFilePlayer,
+});
+
+impl_subtype_from_u32!(MusicDeviceType { DLSSynth, Sampler });
+
+impl_subtype_from_u32!(IOType {
+    GenericOutput,
+    HalOutput,
+    DefaultOutput,
+    SystemOutput,
+    VoiceProcessingIO,
+    RemoteIO,
+});
+
 impl From<EffectType> for Type {
     fn from(ty: EffectType) -> Self {
         Type::Effect(ty)
@@ -227,6 +359,11 @@ pub enum EffectType {
     ///
     /// **Available** in OS X v10.2 and later.
     MatrixReverb = 1836213622,
+    /// An audio unit that provides a reverberation effect with separately adjustable low and high
+    /// frequency decay times.
+    ///
+    /// **Available** in OS X v10.7 and later.
+    Reverb2 = 1920361010,
     /// An audio unit for modifying the pitch of a signal.
     ///
     /// **Available** in OS X v10.4 and later.
@@ -339,6 +476,14 @@ pub enum MixerType {
     ///
     /// **Deprecated** in OS X v10.10.
     Mixer3D = 862219640,
+    /// An audio unit that can have any number of input buses and one output bus, rendering each
+    /// input at a position in 3D space relative to the listener.
+    ///
+    /// This supersedes the **Mixer3D** and supports the same positioning parameters along with a
+    /// choice of spatialization algorithms, including HRTF rendering for headphones.
+    ///
+    /// **Available** in OS X v10.10 and later.
+    SpatialMixer = 862217581,
     /// An audio unit that can have any number of input and output buses with any number of
     /// channels on each bus.
     ///
@@ -443,3 +588,30 @@ pub enum IOType {
     /// **Available** in iOS.
     RemoteIO = 1919512419,
 }
+
+#[test]
+fn test_type_from_u32_pair() {
+    let ty = Type::from(EffectType::Reverb2);
+    let sub_type = ty.as_subtype_u32().unwrap();
+    match Type::from_u32_pair(ty.as_u32(), sub_type) {
+        Some(Type::Effect(EffectType::Reverb2)) => (),
+        other => panic!("unexpected type {:?}", other),
+    }
+    match Type::from_u32_pair(Type::Panner.as_u32(), 0) {
+        Some(Type::Panner) => (),
+        other => panic!("unexpected type {:?}", other),
+    }
+    assert!(Type::from_u32_pair(ty.as_u32(), 0).is_none());
+    assert!(Type::from_u32_pair(0, sub_type).is_none());
+}
+
+#[test]
+fn test_unit_category_from_type_u32() {
+    let ty = Type::from(EffectType::Reverb2).as_u32();
+    assert_eq!(UnitCategory::from_type_u32(ty), Some(UnitCategory::Effect));
+    let ty = Type::MusicEffect.as_u32();
+    assert_eq!(UnitCategory::from_type_u32(ty), Some(UnitCategory::Effect));
+    let ty = Type::from(MixerType::MultiChannelMixer).as_u32();
+    assert_eq!(UnitCategory::from_type_u32(ty), Some(UnitCategory::Mixer));
+    assert_eq!(UnitCategory::from_type_u32(0), None);
+}
@@ -0,0 +1,89 @@
+//! The `unit_wrapper!` macro, defining the typed wrappers around particular kinds of
+//! **AudioUnit**, e.g. **PeakLimiter** or **SpatialMixer**.
+
+/// Define a wrapper struct around an **AudioUnit** of the given type, along with the constructors
+/// and accessors shared by every wrapper.
+///
+/// `new` constructs the system's unit of `$unit_type`, while `from_audio_unit` accepts any unit
+/// whose subtype is one of the listed subtypes (by default just that of `$unit_type`).
+/// `$description` names the unit in the generated docs, and `$mismatch` describes a unit that is
+/// rejected.
+///
+/// The typed setters of a wrapper set their parameter via the generated `set_clamped_parameter`,
+/// which clamps the value to the range reported by the unit's `parameter_info` rather than the
+/// range documented for the system's unit, as some ranges depend on the sample rate.
+macro_rules! unit_wrapper {
+    (
+        @define $(#[$attr:meta])* $name:ident, $unit_type:expr, [$($subtype:expr),+],
+        $description:expr, [$($mismatch:expr),+]
+    ) => {
+        $(#[$attr])*
+        pub struct $name {
+            audio_unit: $crate::audio_unit::AudioUnit,
+        }
+
+        impl $name {
+            #[doc = concat!(
+                "Construct a new **", stringify!($name), "** from the system's ", $description,
+                " **AudioUnit**."
+            )]
+            pub fn new() -> Result<$name, $crate::error::Error> {
+                let audio_unit = $crate::audio_unit::AudioUnit::new($unit_type)?;
+                Ok($name { audio_unit })
+            }
+
+            /// Wrap an existing **AudioUnit**.
+            ///
+            #[doc = concat!(
+                "Returns `Error::UnexpectedSubtype` if the unit is ", $($mismatch,)+ "."
+            )]
+            pub fn from_audio_unit(
+                audio_unit: $crate::audio_unit::AudioUnit,
+            ) -> Result<$name, $crate::error::Error> {
+                audio_unit.expect_subtype(&[$($subtype as u32),+])?;
+                Ok($name { audio_unit })
+            }
+
+            /// A reference to the underlying **AudioUnit**.
+            pub fn audio_unit(&self) -> &$crate::audio_unit::AudioUnit {
+                &self.audio_unit
+            }
+
+            /// A mutable reference to the underlying **AudioUnit**.
+            pub fn audio_unit_mut(&mut self) -> &mut $crate::audio_unit::AudioUnit {
+                &mut self.audio_unit
+            }
+
+            /// Unwrap the underlying **AudioUnit**.
+            pub fn into_audio_unit(self) -> $crate::audio_unit::AudioUnit {
+                self.audio_unit
+            }
+
+            /// Set the given `Global` parameter, clamped to the range described by its info.
+            // Not every wrapper has typed setters.
+            #[allow(dead_code)]
+            fn set_clamped_parameter(
+                &mut self,
+                id: $crate::audio_unit::ParameterId,
+                value: f32,
+            ) -> Result<(), $crate::error::Error> {
+                let scope = $crate::audio_unit::Scope::Global;
+                self.audio_unit.set_clamped_parameter(id, scope, 0, value)
+            }
+        }
+    };
+    ($(#[$attr:meta])* $name:ident, $unit_type:expr, $description:expr) => {
+        unit_wrapper!(
+            @define $(#[$attr])* $name, $unit_type, [$unit_type], $description,
+            ["not a ", $description]
+        );
+    };
+    (
+        $(#[$attr:meta])* $name:ident, $unit_type:expr, [$($subtype:expr),+], $description:expr,
+        $mismatch:expr
+    ) => {
+        unit_wrapper!(
+            @define $(#[$attr])* $name, $unit_type, [$($subtype),+], $description, [$mismatch]
+        );
+    };
+}
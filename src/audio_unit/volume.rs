@@ -0,0 +1,35 @@
+//! Conversion between scalar volumes and decibels.
+//!
+//! Mixer volume parameters such as those of the **MultiChannelMixer** are linear gains, where
+//! `1.0` is unity gain and `0.0` is silence. These convert to and from decibels via the standard
+//! amplitude curve `db = 20 * log10(scalar)`, so halving the scalar lowers the volume by roughly
+//! 6 dB.
+//!
+//! Note that the *scalar* volumes of hardware devices are not linear gains. Devices map their
+//! scalar onto a perceptual taper of their own choosing, and report the equivalent level in
+//! decibels via `kAudioDevicePropertyVolumeDecibels`, which should be used instead of these
+//! functions where the device's level in decibels is needed.
+
+/// Convert a linear gain to decibels, where a gain of `1.0` is `0.0` dB.
+///
+/// A gain of `0.0` (silence) converts to negative infinity.
+pub fn scalar_to_db(scalar: f32) -> f32 {
+    20.0 * scalar.log10()
+}
+
+/// Convert decibels to a linear gain, where `0.0` dB is a gain of `1.0`.
+///
+/// Negative infinity converts to a gain of `0.0` (silence).
+pub fn db_to_scalar(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+#[test]
+fn test_scalar_db_round_trip() {
+    assert_eq!(scalar_to_db(1.0), 0.0);
+    assert_eq!(db_to_scalar(0.0), 1.0);
+    assert_eq!(scalar_to_db(0.0), f32::NEG_INFINITY);
+    assert_eq!(db_to_scalar(f32::NEG_INFINITY), 0.0);
+    assert!((scalar_to_db(0.5) + 6.0206).abs() < 1e-3);
+    assert!((db_to_scalar(-20.0) - 0.1).abs() < 1e-6);
+}
@@ -0,0 +1,210 @@
+//! A minimal writer for RIFF WAVE files, used to write rendered audio to disk.
+//!
+//! Only interleaved 32-bit float and 16-bit integer PCM are supported.
+
+use crate::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// The size of the header written before the sample data.
+const HEADER_LEN: u32 = 44;
+
+/// The maximum number of bytes of sample data, such that the size of the RIFF chunk fits in its
+/// 32-bit field.
+const MAX_DATA_LEN: u32 = u32::MAX - (HEADER_LEN - 8);
+
+/// The format in which samples are stored within a WAV file.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WavSampleFormat {
+    /// 32-bit IEEE float samples.
+    F32,
+    /// 16-bit signed integer PCM samples.
+    I16,
+}
+
+impl WavSampleFormat {
+    /// The WAVE format tag associated with the sample format.
+    fn format_tag(&self) -> u16 {
+        match *self {
+            WavSampleFormat::F32 => 3,
+            WavSampleFormat::I16 => 1,
+        }
+    }
+
+    /// The size of a single sample in bytes.
+    pub fn size_in_bytes(&self) -> u16 {
+        match *self {
+            WavSampleFormat::F32 => 4,
+            WavSampleFormat::I16 => 2,
+        }
+    }
+}
+
+/// A description of the audio stored within a WAV file.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct WavSpec {
+    /// The number of interleaved channels.
+    pub channels: u16,
+    /// The number of frames per second.
+    pub sample_rate: u32,
+    /// The format of each sample.
+    pub sample_format: WavSampleFormat,
+}
+
+/// Writes interleaved samples to a WAV file.
+///
+/// The header is rewritten with the final data size by `finalize`, which must be called once all
+/// samples have been written.
+pub struct WavWriter<W: Write + Seek> {
+    writer: W,
+    spec: WavSpec,
+    // The number of bytes of sample data written so far.
+    data_len: u32,
+}
+
+impl WavWriter<BufWriter<File>> {
+    /// Create or truncate the file at the given path and write a WAV header to it.
+    pub fn create<P: AsRef<Path>>(path: P, spec: WavSpec) -> Result<Self, Error> {
+        let file = File::create(path)?;
+        WavWriter::new(BufWriter::new(file), spec)
+    }
+}
+
+impl<W: Write + Seek> WavWriter<W> {
+    /// Write a WAV header to the given writer.
+    pub fn new(mut writer: W, spec: WavSpec) -> Result<Self, Error> {
+        write_header(&mut writer, &spec, 0)?;
+        Ok(WavWriter {
+            writer,
+            spec,
+            data_len: 0,
+        })
+    }
+
+    /// The description of the audio being written.
+    pub fn spec(&self) -> WavSpec {
+        self.spec
+    }
+
+    /// Write a single sample, converting it to the sample format of the file.
+    ///
+    /// Samples must be written in interleaved order. Samples outside `-1.0..=1.0` are clamped when
+    /// writing 16-bit integer PCM.
+    ///
+    /// Returns an `InvalidInput` I/O error without writing the sample if the file has reached the
+    /// 4 GiB size limit of the format.
+    pub fn write_sample(&mut self, sample: f32) -> Result<(), Error> {
+        let data_len = self
+            .data_len
+            .checked_add(self.spec.sample_format.size_in_bytes() as u32)
+            .filter(|&len| len <= MAX_DATA_LEN)
+            .ok_or_else(|| {
+                let msg = "The WAV file has reached its 4 GiB size limit";
+                std::io::Error::new(std::io::ErrorKind::InvalidInput, msg)
+            })?;
+        match self.spec.sample_format {
+            WavSampleFormat::F32 => self.writer.write_all(&sample.to_le_bytes())?,
+            WavSampleFormat::I16 => {
+                let sample = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+                self.writer.write_all(&sample.to_le_bytes())?;
+            }
+        }
+        self.data_len = data_len;
+        Ok(())
+    }
+
+    /// Update the header with the size of the written data and flush the writer, returning it.
+    pub fn finalize(mut self) -> Result<W, Error> {
+        self.writer.seek(SeekFrom::Start(0))?;
+        write_header(&mut self.writer, &self.spec, self.data_len)?;
+        self.writer.seek(SeekFrom::End(0))?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Write the RIFF, fmt and data chunk headers for `data_len` bytes of sample data.
+fn write_header<W: Write>(writer: &mut W, spec: &WavSpec, data_len: u32) -> Result<(), Error> {
+    let bytes_per_sample = spec.sample_format.size_in_bytes();
+    let block_align = spec.channels * bytes_per_sample;
+    let byte_rate = spec.sample_rate * block_align as u32;
+
+    let mut header = Vec::with_capacity(HEADER_LEN as usize);
+    header.extend_from_slice(b"RIFF");
+    header.extend_from_slice(&(HEADER_LEN - 8 + data_len).to_le_bytes());
+    header.extend_from_slice(b"WAVE");
+    header.extend_from_slice(b"fmt ");
+    header.extend_from_slice(&16u32.to_le_bytes());
+    header.extend_from_slice(&spec.sample_format.format_tag().to_le_bytes());
+    header.extend_from_slice(&spec.channels.to_le_bytes());
+    header.extend_from_slice(&spec.sample_rate.to_le_bytes());
+    header.extend_from_slice(&byte_rate.to_le_bytes());
+    header.extend_from_slice(&block_align.to_le_bytes());
+    header.extend_from_slice(&(bytes_per_sample * 8).to_le_bytes());
+    header.extend_from_slice(b"data");
+    header.extend_from_slice(&data_len.to_le_bytes());
+    writer.write_all(&header)?;
+    Ok(())
+}
+
+#[test]
+fn test_wav_header_f32() {
+    let spec = WavSpec {
+        channels: 2,
+        sample_rate: 44_100,
+        sample_format: WavSampleFormat::F32,
+    };
+    let mut writer = WavWriter::new(std::io::Cursor::new(Vec::new()), spec).unwrap();
+    for &sample in &[0.0, 0.5, -0.5, 1.0] {
+        writer.write_sample(sample).unwrap();
+    }
+    let bytes = writer.finalize().unwrap().into_inner();
+    assert_eq!(bytes.len(), 44 + 16);
+    assert_eq!(&bytes[0..4], b"RIFF");
+    assert_eq!(&bytes[4..8], &(36u32 + 16).to_le_bytes());
+    assert_eq!(&bytes[8..16], b"WAVEfmt ");
+    assert_eq!(&bytes[20..22], &3u16.to_le_bytes());
+    assert_eq!(&bytes[22..24], &2u16.to_le_bytes());
+    assert_eq!(&bytes[24..28], &44_100u32.to_le_bytes());
+    assert_eq!(&bytes[28..32], &(44_100u32 * 8).to_le_bytes());
+    assert_eq!(&bytes[32..34], &8u16.to_le_bytes());
+    assert_eq!(&bytes[34..36], &32u16.to_le_bytes());
+    assert_eq!(&bytes[36..40], b"data");
+    assert_eq!(&bytes[40..44], &16u32.to_le_bytes());
+    assert_eq!(&bytes[48..52], &0.5f32.to_le_bytes());
+}
+
+#[test]
+fn test_wav_i16_samples_are_clamped() {
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 48_000,
+        sample_format: WavSampleFormat::I16,
+    };
+    let mut writer = WavWriter::new(std::io::Cursor::new(Vec::new()), spec).unwrap();
+    for &sample in &[2.0, -2.0, 0.0] {
+        writer.write_sample(sample).unwrap();
+    }
+    let bytes = writer.finalize().unwrap().into_inner();
+    assert_eq!(&bytes[20..22], &1u16.to_le_bytes());
+    assert_eq!(&bytes[34..36], &16u16.to_le_bytes());
+    assert_eq!(&bytes[40..44], &6u32.to_le_bytes());
+    assert_eq!(&bytes[44..46], &i16::MAX.to_le_bytes());
+    assert_eq!(&bytes[46..48], &(-i16::MAX).to_le_bytes());
+    assert_eq!(&bytes[48..50], &0i16.to_le_bytes());
+}
+
+#[test]
+fn test_wav_size_limit() {
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 48_000,
+        sample_format: WavSampleFormat::F32,
+    };
+    let mut writer = WavWriter::new(std::io::Cursor::new(Vec::new()), spec).unwrap();
+    writer.data_len = MAX_DATA_LEN - 4;
+    writer.write_sample(0.0).unwrap();
+    assert!(writer.write_sample(0.0).is_err());
+    assert_eq!(writer.data_len, MAX_DATA_LEN);
+}
@@ -0,0 +1,123 @@
+//! Joining threads to the audio workgroup of the device an output **AudioUnit** is bound to.
+//!
+//! On macOS 11 and later each device's I/O thread belongs to an `os_workgroup`. Auxiliary threads
+//! that do real-time work for the render thread, e.g. rendering voices in parallel, should join
+//! the workgroup so that the scheduler accounts for them when meeting the device's deadlines. This
+//! is particularly important on Apple Silicon, where threads outside the workgroup may be
+//! scheduled on efficiency cores.
+//!
+//! Find the original workgroup documentation [here](https://developer.apple.com/documentation/audiotoolbox/workgroup_management).
+
+use super::{AudioUnit, Element, Scope};
+use crate::error::Error;
+use std::io;
+use std::marker::PhantomData;
+use std::mem;
+use std::os::raw::{c_char, c_int, c_void};
+use std::ptr;
+use sys;
+
+/// An opaque `os_workgroup_t`.
+type OsWorkgroup = *mut c_void;
+
+/// The storage for an `os_workgroup_join_token_s`, filled in by `os_workgroup_join`.
+#[repr(C)]
+struct OsWorkgroupJoinToken {
+    sig: u32,
+    opaque: [c_char; 36],
+}
+
+extern "C" {
+    fn os_workgroup_join(wg: OsWorkgroup, token_out: *mut OsWorkgroupJoinToken) -> c_int;
+    fn os_workgroup_leave(wg: OsWorkgroup, token: *mut OsWorkgroupJoinToken);
+    fn os_release(object: *mut c_void);
+}
+
+/// A reference to the audio workgroup of a device, as returned by `AudioUnit::io_workgroup`.
+///
+/// The workgroup remains valid for as long as the token is alive, even if the unit is dropped.
+pub struct WorkGroupToken {
+    workgroup: OsWorkgroup,
+}
+
+/// The membership of the current thread in an audio workgroup, as returned by
+/// `WorkGroupToken::join`. The thread leaves the workgroup when this is dropped.
+///
+/// A thread must leave the workgroup from the same thread that joined it, so this can't be sent to
+/// another thread.
+pub struct WorkGroupMembership<'a> {
+    workgroup: OsWorkgroup,
+    token: OsWorkgroupJoinToken,
+    _not_send: PhantomData<(&'a WorkGroupToken, *const ())>,
+}
+
+impl AudioUnit {
+    /// The audio workgroup of the I/O thread of the device the unit is currently bound to.
+    ///
+    /// Returns an error for devices without a workgroup, which is the case for all devices prior
+    /// to macOS 11.
+    pub fn io_workgroup(&self) -> Result<WorkGroupToken, Error> {
+        let id = sys::kAudioOutputUnitProperty_CurrentDevice;
+        let device_id: sys::AudioDeviceID =
+            self.get_property(id, Scope::Global, Element::Output)?;
+        let address = sys::AudioObjectPropertyAddress {
+            mSelector: sys::kAudioDevicePropertyIOThreadOSWorkgroup,
+            mScope: sys::kAudioObjectPropertyScopeGlobal,
+            mElement: sys::kAudioObjectPropertyElementMaster,
+        };
+        let mut workgroup: OsWorkgroup = ptr::null_mut();
+        let mut data_size = mem::size_of::<OsWorkgroup>() as u32;
+        let status = unsafe {
+            sys::AudioObjectGetPropertyData(
+                device_id,
+                &address,
+                0,
+                ptr::null(),
+                &mut data_size,
+                &mut workgroup as *mut _ as *mut c_void,
+            )
+        };
+        Error::from_os_status(status)?;
+        if workgroup.is_null() {
+            return Err(Error::Unspecified);
+        }
+        // The caller is responsible for releasing the returned workgroup.
+        Ok(WorkGroupToken { workgroup })
+    }
+}
+
+impl WorkGroupToken {
+    /// Join the current thread to the workgroup until the returned membership is dropped.
+    ///
+    /// Fails if the thread is already a member of a workgroup, or if the workgroup has been
+    /// cancelled because the device has stopped.
+    pub fn join(&self) -> Result<WorkGroupMembership<'_>, Error> {
+        let mut token: OsWorkgroupJoinToken = unsafe { mem::zeroed() };
+        let result = unsafe { os_workgroup_join(self.workgroup, &mut token) };
+        if result != 0 {
+            // Workgroup functions return an `errno` code rather than an `OSStatus`.
+            return Err(io::Error::from_raw_os_error(result).into());
+        }
+        Ok(WorkGroupMembership {
+            workgroup: self.workgroup,
+            token,
+            _not_send: PhantomData,
+        })
+    }
+}
+
+impl Drop for WorkGroupToken {
+    fn drop(&mut self) {
+        unsafe { os_release(self.workgroup) };
+    }
+}
+
+// Workgroups are reference counted objects that may be joined from any thread.
+unsafe impl Send for WorkGroupToken {}
+unsafe impl Sync for WorkGroupToken {}
+
+impl<'a> Drop for WorkGroupMembership<'a> {
+    fn drop(&mut self) {
+        unsafe { os_workgroup_leave(self.workgroup, &mut self.token) };
+    }
+}
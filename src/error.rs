@@ -4,8 +4,36 @@ pub use self::audio::Error as AudioError;
 pub use self::audio_codec::Error as AudioCodecError;
 pub use self::audio_format::Error as AudioFormatError;
 pub use self::audio_unit::Error as AudioUnitError;
+use crate::four_char_code::FourCharCode;
+use std::mem;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use sys::OSStatus;
 
+/// The handler set via `set_drop_error_handler` as a `fn(&Error)` pointer, or `0` if none is set.
+static DROP_ERROR_HANDLER: AtomicUsize = AtomicUsize::new(0);
+
+/// Set a handler to be called with any error that occurs while an **AudioUnit** is torn down in
+/// `drop`, e.g. to log it.
+///
+/// `drop` never panics, so errors while stopping, uninitializing or disposing of a unit are
+/// otherwise ignored. No handler is set by default. The handler applies to every unit and may be
+/// called on whichever thread drops the unit.
+pub fn set_drop_error_handler(handler: fn(&Error)) {
+    DROP_ERROR_HANDLER.store(handler as usize, Ordering::Release);
+}
+
+/// Pass the error of a `drop` teardown step, if any, to the handler set via
+/// `set_drop_error_handler`.
+pub(crate) fn report_drop_error(result: Result<(), Error>) {
+    let handler = DROP_ERROR_HANDLER.load(Ordering::Acquire);
+    if let (Err(err), true) = (result, handler != 0) {
+        // Only ever set from a `fn(&Error)` in `set_drop_error_handler`.
+        let handler: fn(&Error) = unsafe { mem::transmute(handler) };
+        handler(&err);
+    }
+}
+
 pub mod audio {
     use sys::OSStatus;
 
@@ -244,17 +272,55 @@ pub mod audio_unit {
     }
 }
 
+/// The first problem found when validating a **StreamFormat**.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StreamFormatError {
+    /// The sample rate is zero, negative or not finite.
+    InvalidSampleRate,
+    /// There are no channels.
+    NoChannels,
+    /// The format flags do not describe the sample format.
+    FlagsDoNotMatchSampleFormat,
+    /// The number of bytes per frame does not fit within a `u32`.
+    BytesPerFrameOverflow,
+}
+
+impl ::std::fmt::Display for StreamFormatError {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> Result<(), ::std::fmt::Error> {
+        let description = match *self {
+            StreamFormatError::InvalidSampleRate => "The sample rate must be positive and finite",
+            StreamFormatError::NoChannels => "There must be at least one channel",
+            StreamFormatError::FlagsDoNotMatchSampleFormat => {
+                "The format flags do not match the sample format"
+            }
+            StreamFormatError::BytesPerFrameOverflow => "The number of bytes per frame is too large",
+        };
+        write!(f, "{}", description)
+    }
+}
+
 /// A wrapper around all possible Core Audio errors.
-#[derive(Copy, Clone, Debug)]
+///
+/// New variants may be added as more of the API is covered, so matches on the error must include
+/// a wildcard arm.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
 pub enum Error {
     Unspecified,
     SystemSoundClientMessageTimedOut,
     NoMatchingDefaultAudioUnitFound,
     RenderCallbackBufferFormatDoesNotMatchAudioUnitStreamFormat,
     NoKnownSubtype,
+    UnexpectedSubtype,
+    ElementOutOfRange,
+    BufferNotProvided,
+    ElementCountFixed(u32),
     NonInterleavedInputOnlySupportsMono,
     UnsupportedSampleRate,
     UnsupportedStreamFormat,
+    InvalidStreamFormat(StreamFormatError),
+    InvalidPresetData,
+    Io(Arc<std::io::Error>),
     Audio(AudioError),
     AudioCodec(AudioCodecError),
     AudioFormat(AudioFormatError),
@@ -302,16 +368,79 @@ impl Error {
             Error::NoMatchingDefaultAudioUnitFound => -1500,
             Error::RenderCallbackBufferFormatDoesNotMatchAudioUnitStreamFormat => -1500,
             Error::SystemSoundClientMessageTimedOut => -1501,
+            Error::ElementOutOfRange => AudioUnitError::InvalidElement as OSStatus,
             Error::Audio(err) => err as OSStatus,
             Error::AudioCodec(err) => err as OSStatus,
             Error::AudioUnit(err) => err as OSStatus,
             _ => -1500,
         }
     }
+
+    /// A friendly, non-technical description of the error, suitable for presenting to end users.
+    ///
+    /// Unlike the `Display` implementation, which describes what went wrong in Core Audio terms,
+    /// this describes the problem as a user would understand it. Errors without a more specific
+    /// explanation share a generic message.
+    pub fn user_message(&self) -> String {
+        // `kAudioHardwareBadDeviceError`, returned when a device has been unplugged.
+        const BAD_DEVICE: OSStatus = 560227702;
+
+        let message = match *self {
+            Error::NoMatchingDefaultAudioUnitFound => "No audio device is available",
+            Error::Unknown(BAD_DEVICE) => "The selected audio device is no longer available",
+            // Shares its code with `kAudioHardwareIllegalOperationError`, returned when a device
+            // is in use by another process.
+            Error::AudioCodec(AudioCodecError::IllegalOperation) => {
+                "The audio device is in use by another application"
+            }
+            Error::UnsupportedStreamFormat
+            | Error::InvalidStreamFormat(_)
+            | Error::RenderCallbackBufferFormatDoesNotMatchAudioUnitStreamFormat
+            | Error::NonInterleavedInputOnlySupportsMono
+            | Error::AudioCodec(AudioCodecError::UnsupportedFormat)
+            | Error::AudioFormat(AudioFormatError::UnsupportedDataFormat)
+            | Error::AudioUnit(AudioUnitError::FormatNotSupported) => {
+                "The requested audio format isn't supported by this device"
+            }
+            Error::UnsupportedSampleRate => {
+                "The requested sample rate isn't supported by this device"
+            }
+            Error::AudioUnit(AudioUnitError::Unauthorized) => {
+                "Permission to use the microphone has not been granted"
+            }
+            Error::Audio(AudioError::FileNotFound) | Error::Audio(AudioError::BadFilePath) => {
+                "The audio file could not be found"
+            }
+            Error::Io(ref err) if err.kind() == std::io::ErrorKind::NotFound => {
+                "The audio file could not be found"
+            }
+            Error::Audio(AudioError::FilePermission) => {
+                "Permission to access the audio file was denied"
+            }
+            Error::Io(ref err) if err.kind() == std::io::ErrorKind::PermissionDenied => {
+                "Permission to access the audio file was denied"
+            }
+            Error::AudioUnit(AudioUnitError::InvalidFile)
+            | Error::AudioFormat(AudioFormatError::UnknownFormat) => {
+                "The audio file is damaged or in an unsupported format"
+            }
+            Error::Io(_) => "The audio file could not be read or written",
+            Error::InvalidPresetData => "The preset is damaged or in an unknown format",
+            Error::Audio(AudioError::MemFull) => "There is not enough memory to process audio",
+            _ => "An unexpected problem occurred with audio playback or recording",
+        };
+        message.to_string()
+    }
 }
 
 impl std::error::Error for Error {}
 
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(Arc::new(err))
+    }
+}
+
 impl ::std::fmt::Display for Error {
     fn fmt(&self, f: &mut ::std::fmt::Formatter) -> Result<(), ::std::fmt::Error> {
         match *self {
@@ -321,14 +450,29 @@ impl ::std::fmt::Display for Error {
                 write!(f, "The given render callback buffer format does not match the `AudioUnit` `StreamFormat`"),
             Error::SystemSoundClientMessageTimedOut => write!(f, "The system sound client message timed out"),
             Error::NoKnownSubtype => write!(f, "The type has no known subtypes"),
+            Error::UnexpectedSubtype => write!(f, "The audio unit is not of the expected subtype"),
+            Error::ElementOutOfRange => write!(f, "The element does not exist within the scope"),
+            Error::BufferNotProvided => write!(f, "Buffer allocation is disabled for an element without a buffer being provided"),
+            Error::ElementCountFixed(count) => write!(f, "The number of elements is fixed at {}", count),
             Error::NonInterleavedInputOnlySupportsMono => write!(f, "In non-interleaved mode input only supports one channel"),
             Error::UnsupportedSampleRate => write!(f, "The requested sample rate is not available"),
             Error::UnsupportedStreamFormat => write!(f, "The requested stream format is not available"),
+            Error::InvalidStreamFormat(ref err) => write!(f, "The stream format is invalid: {}", err),
+            Error::InvalidPresetData => write!(f, "The preset data is not a valid property list"),
+            Error::Io(ref err) => write!(f, "An I/O error occurred: {}", err),
             Error::Audio(ref err) => write!(f, "{}", err),
             Error::AudioCodec(ref err) => write!(f, "{}", err),
             Error::AudioFormat(ref err) => write!(f, "{}", err),
             Error::AudioUnit(ref err) => write!(f, "{}", err),
-            Error::Unknown(_) => write!(f, "An unknown error unknown to the coreaudio-rs API occurred"),
+            Error::Unknown(status) => {
+                // Many Core Audio statuses are four-character codes, e.g. `'!dat'`.
+                let code = FourCharCode(status as u32);
+                if code.is_printable() {
+                    write!(f, "An error unknown to the coreaudio-rs API occurred: {}", code)
+                } else {
+                    write!(f, "An error unknown to the coreaudio-rs API occurred: {}", status)
+                }
+            }
         }
     }
 }
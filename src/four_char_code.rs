@@ -0,0 +1,110 @@
+//! Four-character codes, as used by Core Audio for component types, subtypes and manufacturers,
+//! as well as for many of its `OSStatus` error codes.
+//!
+//! A four-character code is a `u32` whose four big-endian bytes spell out a short ASCII tag, e.g.
+//! `'aufx'` for the `componentType` of an effect or `'appl'` for Apple's manufacturer code.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// A four-character code such as `'aufx'`.
+///
+/// Displays in the quoted form used by Apple's documentation, with bytes that aren't printable
+/// ASCII shown as `?`. Codes may be parsed from their four characters, e.g. for describing a
+/// third-party component: `"Xmpl".parse::<FourCharCode>()`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FourCharCode(pub u32);
+
+/// The error returned when parsing a string that isn't exactly four ASCII characters as a
+/// **FourCharCode**.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ParseFourCharCodeError;
+
+impl FourCharCode {
+    /// The four bytes of the code in the order they are written.
+    pub fn to_bytes(self) -> [u8; 4] {
+        self.0.to_be_bytes()
+    }
+
+    /// Whether every byte of the code is printable ASCII, i.e. whether the code is likely to be
+    /// a tag rather than a plain number.
+    pub fn is_printable(self) -> bool {
+        self.to_bytes().iter().all(|&byte| is_printable(byte))
+    }
+}
+
+fn is_printable(byte: u8) -> bool {
+    (0x20..=0x7E).contains(&byte)
+}
+
+impl From<u32> for FourCharCode {
+    fn from(code: u32) -> Self {
+        FourCharCode(code)
+    }
+}
+
+impl From<FourCharCode> for u32 {
+    fn from(code: FourCharCode) -> Self {
+        code.0
+    }
+}
+
+impl fmt::Display for FourCharCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let chars: String = self
+            .to_bytes()
+            .iter()
+            .map(|&byte| {
+                if is_printable(byte) {
+                    byte as char
+                } else {
+                    '?'
+                }
+            })
+            .collect();
+        write!(f, "'{}'", chars)
+    }
+}
+
+impl FromStr for FourCharCode {
+    type Err = ParseFourCharCodeError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = s.as_bytes();
+        if bytes.len() != 4 || !bytes.iter().all(|&byte| is_printable(byte)) {
+            return Err(ParseFourCharCodeError);
+        }
+        let code = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        Ok(FourCharCode(code))
+    }
+}
+
+impl fmt::Display for ParseFourCharCodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "A four-character code must be exactly four printable ASCII characters"
+        )
+    }
+}
+
+impl std::error::Error for ParseFourCharCodeError {}
+
+#[test]
+fn test_four_char_code() {
+    assert_eq!(FourCharCode(0x6175_6678).to_string(), "'aufx'");
+    assert_eq!(FourCharCode(0x6175_0001).to_string(), "'au??'");
+    assert_eq!(
+        "appl".parse(),
+        Ok(FourCharCode(sys::kAudioUnitManufacturer_Apple))
+    );
+    assert_eq!(
+        "!fmt".parse::<FourCharCode>().map(u32::from),
+        Ok(0x2166_6D74)
+    );
+    assert_eq!("aufxx".parse::<FourCharCode>(), Err(ParseFourCharCodeError));
+    assert_eq!(
+        "au\u{e9}".parse::<FourCharCode>(),
+        Err(ParseFourCharCodeError)
+    );
+    assert!(!FourCharCode(-50i32 as u32).is_printable());
+}
@@ -13,8 +13,10 @@ extern crate bitflags;
 extern crate core_foundation_sys;
 pub extern crate coreaudio_sys as sys;
 
-pub use error::Error;
+pub use error::{set_drop_error_handler, Error};
+pub use four_char_code::FourCharCode;
 
 #[cfg(feature = "audio_unit")]
 pub mod audio_unit;
 pub mod error;
+pub mod four_char_code;